@@ -70,7 +70,7 @@ fn try_parse_bet_action(buf: &str) -> Result<BetAction, Box<dyn Error>> {
             } else if words[1].is_empty() {
                 return Err("Empty second word".into());
             }
-            let amt: Currency = words[1].parse::<i32>()?;
+            let amt: Currency = words[1].parse::<Currency>()?;
             match words[0].chars().next().unwrap() {
                 'c' => BetAction::Call(amt),
                 'b' => BetAction::Bet(amt),
@@ -170,7 +170,7 @@ fn single_hand(
     match state.start_hand_with_seed(seed) {
         Ok(_) => {}
         Err(e) => match e {
-            GameError::NotEnoughPlayers => return Ok(true),
+            GameError::NotEnoughPlayers | GameError::NotEnoughFundedPlayers => return Ok(true),
             _ => return Err(e.into()),
         },
     };
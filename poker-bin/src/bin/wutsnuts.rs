@@ -1,5 +1,4 @@
-use itertools::Itertools;
-use poker_core::deck::{Card, Deck, ALL_RANKS, ALL_SUITS};
+use poker_core::deck::{Card, Deck};
 use poker_core::hand::{best_of_cards, Hand};
 use std::cmp::Ordering;
 use std::env;
@@ -36,12 +35,9 @@ fn find_nuts(community: &[Card]) -> Vec<([Card; 2], Hand)> {
         return nuts;
     }
     // Generate a sorted deck (AAAAKKKKQQQQ...2222)
-    let deck: Vec<Card> = ALL_RANKS
-        .iter()
-        .rev() // instead of 2->A, do A->2 so that pockets always start with higher card
-        .cartesian_product(ALL_SUITS.iter().rev()) // rev again just because I like SHDC better than the reverse
-        .map(|x| Card::new(*x.0, *x.1))
-        .collect();
+    // instead of 2->A, do A->2 so that pockets always start with higher card. This also makes the
+    // suits SHDC, which I like better than the reverse
+    let deck: Vec<Card> = Card::all().rev().collect();
     // for every possible pocket that doesn't contain a community card ...
     for idx1 in (0..deck.len() - 1).filter(|i| !community.contains(&deck[*i])) {
         for idx2 in (idx1 + 1..deck.len()).filter(|i| !community.contains(&deck[*i])) {
@@ -1,7 +1,7 @@
 use crate::utils::card_char;
 use poker_core::bet::BetStatus;
 use poker_core::deck::{Card, Suit};
-use poker_core::PlayerId;
+use poker_core::{Currency, PlayerId};
 use wasm_bindgen::JsCast;
 use web_sys::Element;
 
@@ -80,7 +80,7 @@ impl Elementable for Community {
     }
 }
 
-pub(crate) struct Pot(pub(crate) Vec<i32>);
+pub(crate) struct Pot(pub(crate) Vec<Currency>);
 
 impl Elementable for Pot {
     fn into_element(self) -> Element {
@@ -115,7 +115,7 @@ pub(crate) struct Pocket {
     // - player sitting and either has revealed both cards or its us: Some([Some(), Some()])
     pub(crate) cards: Option<[Option<Card>; 2]>,
     pub(crate) name: String,
-    pub(crate) stack: i32,
+    pub(crate) stack: Currency,
     pub(crate) seat_idx: usize,
     pub(crate) player_id: PlayerId,
     pub(crate) bet_status: BetStatus,
@@ -136,11 +136,9 @@ impl Elementable for Pocket {
         while let Some(child) = elm.last_child() {
             elm.remove_child(&child).unwrap();
         }
-        if self.cards.is_some() {
-            elm.append_child(&self.cards.unwrap()[0].into_element())
-                .unwrap();
-            elm.append_child(&self.cards.unwrap()[1].into_element())
-                .unwrap();
+        if let Some(cards) = self.cards {
+            elm.append_child(&cards[0].into_element()).unwrap();
+            elm.append_child(&cards[1].into_element()).unwrap();
         }
         let name_elm = base_element("p");
         let stack_elm = base_element("p");
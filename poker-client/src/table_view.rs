@@ -0,0 +1,383 @@
+use crate::elements::Pocket;
+use crate::player_info::PlayerInfo;
+use poker_core::bet::BetStatus;
+use poker_core::deck::Card;
+use poker_core::log::LogItem;
+use poker_core::pot;
+use poker_core::{Currency, PlayerId, SeatIdx, SeqNum, MAX_PLAYERS};
+use std::collections::HashMap;
+
+/// How the server identifies a table
+pub type TableId = i32;
+
+/// Something the page needs to do because the table changed. Kept separate from [`TableView`] so
+/// that a view can be updated (and tested) without a browser; see `run_effect` in lib.rs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Effect {
+    RequestPlayerInfo(PlayerId),
+    RedrawPocket(SeatIdx, String, Currency),
+    DealCardPocket(SeatIdx, u8, Option<Card>),
+    DealCardCommunity(u8, Card),
+    RevealCards(SeatIdx, Option<Card>, Option<Card>),
+    ShowHandResult(String),
+    ClearCommunity,
+    ClearBets,
+    ClearPot,
+    MakeBet(SeatIdx, Currency, Currency),
+    CollectPot(Vec<Currency>),
+    PushWinnings(Vec<SeatIdx>, Vec<Currency>),
+    NextToAct(SeatIdx),
+    RedrawActionButtons,
+}
+
+/// Everything the client remembers about a single table, built up from that table's log items.
+#[derive(Debug)]
+pub(crate) struct TableView {
+    pub(crate) saved_logs: Vec<(SeqNum, LogItem)>,
+    pub(crate) pockets: Vec<Pocket>,
+    pub(crate) community: [Option<Card>; 5],
+    pub(crate) current_bet_and_raise: (Currency, Currency),
+    pub(crate) nta: SeatIdx,
+    pub(crate) pot: Vec<Currency>,
+}
+
+impl Default for TableView {
+    fn default() -> Self {
+        Self {
+            saved_logs: Vec::new(),
+            pockets: Vec::with_capacity(MAX_PLAYERS),
+            community: [None; 5],
+            current_bet_and_raise: (0, 0),
+            nta: MAX_PLAYERS + 1,
+            pot: Vec::with_capacity(4),
+        }
+    }
+}
+
+impl TableView {
+    pub(crate) fn last_seq_num(&self) -> SeqNum {
+        self.saved_logs.last().map_or(0, |(seq, _)| *seq)
+    }
+
+    pub(crate) fn pocket_of(&self, player_id: PlayerId) -> Option<&Pocket> {
+        self.pockets
+            .iter()
+            .find(|&pocket| pocket.player_id == player_id)
+    }
+
+    pub(crate) fn is_nta(&self, player_id: PlayerId) -> bool {
+        self.pocket_of(player_id)
+            .is_some_and(|pocket| pocket.seat_idx == self.nta)
+    }
+
+    /// Update this view with the given log items. Return what the page needs to do to show the
+    /// changes, in order.
+    pub(crate) fn apply(
+        &mut self,
+        logs: &[(SeqNum, LogItem)],
+        player_info: &HashMap<PlayerId, PlayerInfo>,
+    ) -> Vec<Effect> {
+        self.saved_logs.extend(logs.iter().cloned());
+        let mut effects = vec![];
+        for (_, item) in logs.iter() {
+            self.apply_one(item, player_info, &mut effects);
+        }
+        effects
+    }
+
+    fn apply_one(
+        &mut self,
+        item: &LogItem,
+        player_info: &HashMap<PlayerId, PlayerInfo>,
+        effects: &mut Vec<Effect>,
+    ) {
+        match item {
+            LogItem::NewBaseState(bs) => {
+                self.pot.clear();
+                self.pockets.clear();
+                for (seat_idx, player) in bs
+                    .seats
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, seat)| seat.is_some())
+                    .map(|(idx, seat)| (idx, seat.unwrap()))
+                {
+                    let (name, needs_better_name) = match player_info.get(&player.id) {
+                        Some(pi) => (pi.username.clone(), false),
+                        None => {
+                            effects.push(Effect::RequestPlayerInfo(player.id));
+                            (format!("Player {}", player.id), true)
+                        }
+                    };
+                    effects.push(Effect::RedrawPocket(seat_idx, name.clone(), player.stack));
+                    let pocket = Pocket {
+                        cards: None,
+                        name,
+                        stack: player.stack,
+                        seat_idx,
+                        player_id: player.id,
+                        bet_status: BetStatus::Waiting,
+                        is_btn: false,
+                        is_sb: false,
+                        is_bb: false,
+                        needs_better_name,
+                    };
+                    self.pockets.push(pocket);
+                }
+                self.community = [None; 5];
+                effects.push(Effect::ClearCommunity);
+                effects.push(Effect::ClearBets);
+                effects.push(Effect::ClearPot);
+                effects.push(Effect::RedrawActionButtons);
+            }
+            LogItem::PocketDealt(player_id, cards) => {
+                for pocket in self.pockets.iter_mut() {
+                    if pocket.player_id == *player_id {
+                        pocket.cards = Some(match cards {
+                            None => [None, None],
+                            Some(cards) => [Some(cards[0]), Some(cards[1])],
+                        });
+                    }
+                }
+                if let Some(seat_idx) = self.pocket_of(*player_id).map(|p| p.seat_idx) {
+                    effects.push(Effect::DealCardPocket(seat_idx, 0, cards.map(|cs| cs[0])));
+                    effects.push(Effect::DealCardPocket(seat_idx, 1, cards.map(|cs| cs[1])));
+                }
+            }
+            LogItem::TokensSet(btn, sb, bb) => {
+                for pocket in self.pockets.iter_mut() {
+                    if pocket.seat_idx == *btn {
+                        pocket.is_btn = true;
+                    }
+                    if pocket.seat_idx == *sb {
+                        pocket.is_sb = true;
+                    }
+                    if pocket.seat_idx == *bb {
+                        pocket.is_bb = true;
+                    }
+                }
+            }
+            LogItem::NextToAct(seat) => {
+                self.nta = *seat;
+                effects.push(Effect::NextToAct(*seat));
+                effects.push(Effect::RedrawActionButtons);
+            }
+            LogItem::Pot(pot_item) => match pot_item {
+                pot::LogItem::Bet(player_id, bet_action) => {
+                    let bet_status: BetStatus = (*bet_action).into();
+                    for pocket in self.pockets.iter_mut() {
+                        if pocket.player_id == *player_id {
+                            let old_bet_status = pocket.bet_status;
+                            pocket.bet_status = bet_status;
+                            let old_wager = match old_bet_status {
+                                BetStatus::In(x) | BetStatus::AllIn(x) => x,
+                                BetStatus::Folded | BetStatus::Waiting => 0,
+                            };
+                            match bet_status {
+                                BetStatus::In(new_wager) | BetStatus::AllIn(new_wager) => {
+                                    pocket.stack += old_wager;
+                                    pocket.stack -= new_wager;
+                                    if old_wager != new_wager {
+                                        effects.push(Effect::MakeBet(
+                                            pocket.seat_idx,
+                                            pocket.stack,
+                                            new_wager,
+                                        ));
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                pot::LogItem::UncalledBetReturned(player_id, amount) => {
+                    if let Some(pocket) =
+                        self.pockets.iter_mut().find(|p| p.player_id == *player_id)
+                    {
+                        pocket.stack += amount;
+                        if let BetStatus::In(x) | BetStatus::AllIn(x) = &mut pocket.bet_status {
+                            *x -= amount;
+                            effects.push(Effect::MakeBet(pocket.seat_idx, pocket.stack, *x));
+                        }
+                    }
+                }
+                pot::LogItem::RoundEnd(_)
+                | pot::LogItem::EntireStakeInPot(_, _, _)
+                | pot::LogItem::PartialStakeInPot(_, _, _, _)
+                | pot::LogItem::NewPotCreated(_, _, _)
+                | pot::LogItem::PotStructure(_)
+                | pot::LogItem::BetsCollected(_)
+                | pot::LogItem::Rake(_) => {}
+                pot::LogItem::Payouts(subpot_id, amounts) => {
+                    if subpot_id.is_some() {
+                        return;
+                    }
+                    let mut seats = Vec::with_capacity(amounts.len());
+                    let mut winnings = Vec::with_capacity(amounts.len());
+                    for (player_id, amount) in amounts.iter() {
+                        if let Some(pocket) = self.pocket_of(*player_id) {
+                            seats.push(pocket.seat_idx);
+                            winnings.push(*amount);
+                        }
+                    }
+                    effects.push(Effect::PushWinnings(seats, winnings));
+                }
+                pot::LogItem::BetsSorted(v) => {
+                    for (_player_id, stake) in v.iter() {
+                        if self.pot.is_empty() {
+                            self.pot.push(0);
+                        }
+                        self.pot[0] += stake.amount;
+                    }
+                    effects.push(Effect::CollectPot(self.pot.clone()));
+                }
+            },
+            LogItem::CurrentBetSet(_, cb, _, mr) => {
+                self.current_bet_and_raise = (*cb, *mr);
+            }
+            LogItem::StateChange(old, new) => {
+                if old == new {
+                    return;
+                }
+                for pocket in self.pockets.iter_mut() {
+                    pocket.bet_status = BetStatus::Waiting;
+                }
+                effects.push(Effect::ClearBets);
+            }
+            LogItem::Flop(c1, c2, c3) => {
+                self.community[0] = Some(*c1);
+                self.community[1] = Some(*c2);
+                self.community[2] = Some(*c3);
+                effects.push(Effect::DealCardCommunity(0, *c1));
+                effects.push(Effect::DealCardCommunity(1, *c2));
+                effects.push(Effect::DealCardCommunity(2, *c3));
+            }
+            LogItem::Turn(c) => {
+                self.community[3] = Some(*c);
+                effects.push(Effect::DealCardCommunity(3, *c));
+            }
+            LogItem::River(c) => {
+                self.community[4] = Some(*c);
+                effects.push(Effect::DealCardCommunity(4, *c));
+            }
+            LogItem::HandReveal(player_id, cards) => {
+                for pocket in self.pockets.iter_mut() {
+                    if pocket.player_id == *player_id {
+                        pocket.cards = Some(*cards);
+                        effects.push(Effect::RevealCards(pocket.seat_idx, cards[0], cards[1]));
+                    }
+                }
+            }
+            LogItem::HandResult(player_id, class, _cards, plays_board) => {
+                let name = self
+                    .pocket_of(*player_id)
+                    .map_or_else(|| format!("Player {player_id}"), |p| p.name.clone());
+                let how = if *plays_board {
+                    "plays the board"
+                } else {
+                    "wins"
+                };
+                effects.push(Effect::ShowHandResult(format!(
+                    "{name} {how} with a {class}."
+                )));
+            }
+            LogItem::WonByFold(player_id) => {
+                let name = self
+                    .pocket_of(*player_id)
+                    .map_or_else(|| format!("Player {player_id}"), |p| p.name.clone());
+                effects.push(Effect::ShowHandResult(format!("{name} wins.")));
+            }
+            // the Pot log item that follows has everything needed to show the action
+            LogItem::ForcedAction(_, _) | LogItem::AutoAction { .. } | LogItem::AutoMuck { .. } => {
+            }
+            // nothing was shown, so there's nothing to draw
+            LogItem::Muck(_) => {}
+            // only of interest to whoever wants to check the shuffle afterwards
+            LogItem::DeckCommitment(_) => {}
+            // the blinds posted next show up as bets like any others
+            LogItem::BlindsChanged(_, _, _) => {}
+            // the NewBaseState before it already had the table redrawn for the new hand
+            LogItem::HandStart { .. } => {}
+            // there's no countdown drawn yet; the next action or a timeout comes after anyway
+            LogItem::ActionDeadline(_, _) => {}
+            // chat isn't drawn on the table
+            LogItem::Chat(_, _) => {}
+            // the straddle's chips show up with the pot bet that follows
+            LogItem::Straddle(_, _) => {}
+            LogItem::ChipsAdded(player_id, _amount, stack) => {
+                for pocket in self.pockets.iter_mut() {
+                    if pocket.player_id == *player_id {
+                        pocket.stack = *stack;
+                        effects.push(Effect::RedrawPocket(
+                            pocket.seat_idx,
+                            pocket.name.clone(),
+                            pocket.stack,
+                        ));
+                    }
+                }
+            }
+            LogItem::Deal(stacks) => {
+                for (player_id, stack) in stacks.iter() {
+                    for pocket in self.pockets.iter_mut() {
+                        if pocket.player_id == *player_id {
+                            pocket.stack = *stack;
+                            effects.push(Effect::RedrawPocket(
+                                pocket.seat_idx,
+                                pocket.name.clone(),
+                                pocket.stack,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_core::deck::{Rank, Suit};
+
+    /// Changes to one table's board and pot don't leak into another's.
+    #[test]
+    fn independent_tables() {
+        let info = HashMap::new();
+        let mut tables: HashMap<TableId, TableView> = HashMap::new();
+        let c1 = Card::new(Rank::RA, Suit::Spade);
+        let c2 = Card::new(Rank::RK, Suit::Heart);
+        let c3 = Card::new(Rank::R2, Suit::Club);
+        tables
+            .entry(1)
+            .or_default()
+            .apply(&[(1, LogItem::Flop(c1, c2, c3))], &info);
+        let effects = tables.entry(2).or_default().apply(
+            &[
+                (1, LogItem::Turn(c1)),
+                (
+                    2,
+                    LogItem::Pot(pot::LogItem::BetsSorted(vec![
+                        (1, (false, 10).into()),
+                        (2, (false, 10).into()),
+                    ])),
+                ),
+            ],
+            &info,
+        );
+        assert_eq!(
+            effects,
+            vec![
+                Effect::DealCardCommunity(3, c1),
+                Effect::CollectPot(vec![20])
+            ]
+        );
+        let t1 = &tables[&1];
+        let t2 = &tables[&2];
+        assert_eq!(t1.community, [Some(c1), Some(c2), Some(c3), None, None]);
+        assert_eq!(t2.community, [None, None, None, Some(c1), None]);
+        assert!(t1.pot.is_empty());
+        assert_eq!(t2.pot, vec![20]);
+        assert_eq!(t1.last_seq_num(), 1);
+        assert_eq!(t2.last_seq_num(), 2);
+    }
+}
@@ -1,6 +1,6 @@
 use poker_core::log::LogItem;
-use poker_core::state::GameState;
-use poker_core::{GameError, PlayerId, SeqNum};
+use poker_core::state::{GameConfig, GameState};
+use poker_core::{Currency, GameError, PlayerId, SeqNum};
 use poker_messages::{action, Msg};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
@@ -13,7 +13,10 @@ pub type OpaqueMsg = String;
 enum PyGameError {
     GameError(GameError),
     MessageNotAnAction,
-    //GameStateDeserializeError,
+    MessageNotChat,
+    GameStateDeserializeError(String),
+    ConfigDeserializeError(String),
+    MessageDeserializeError(String),
 }
 
 impl From<PyGameError> for PyErr {
@@ -28,6 +31,14 @@ impl From<GameError> for PyGameError {
     }
 }
 
+/// Read back the opaque state, upgrading it if it's from an older version of poker-core
+fn load(opaque_state: &str) -> Result<GameState, PyGameError> {
+    GameState::migrate(opaque_state).map_err(|e| match e {
+        GameError::InvalidState(msg) => PyGameError::GameStateDeserializeError(msg),
+        e => e.into(),
+    })
+}
+
 #[pyfunction]
 fn new_game_state() -> OpaqueState {
     serde_json::to_string(&GameState::default()).expect("Unable to encode GameState to JSON")
@@ -36,19 +47,34 @@ fn new_game_state() -> OpaqueState {
 #[pyfunction]
 fn seat_player(
     opaque_state: OpaqueState,
-    player_id: i32,
-    stack: i32,
+    player_id: PlayerId,
+    stack: Currency,
 ) -> Result<OpaqueState, PyGameError> {
-    let mut state: GameState =
-        serde_json::from_str(&opaque_state).expect("Unable to deserialize state");
+    let mut state = load(&opaque_state)?;
     state.try_sit(player_id, stack)?;
     Ok(serde_json::to_string(&state).unwrap())
 }
 
+/// The table's settings, as JSON
+#[pyfunction]
+fn game_config(opaque_state: OpaqueState) -> Result<String, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(serde_json::to_string(state.config()).unwrap())
+}
+
+/// Replace the table's settings with the given JSON config. Only allowed between hands.
+#[pyfunction]
+fn set_game_config(opaque_state: OpaqueState, config: String) -> Result<OpaqueState, PyGameError> {
+    let mut state = load(&opaque_state)?;
+    let config: GameConfig = serde_json::from_str(&config)
+        .map_err(|e| PyGameError::ConfigDeserializeError(e.to_string()))?;
+    state.set_config(config)?;
+    Ok(serde_json::to_string(&state).unwrap())
+}
+
 #[pyfunction]
 fn tick_state(opaque_state: OpaqueState) -> Result<OpaqueState, PyGameError> {
-    let mut state: GameState =
-        serde_json::from_str(&opaque_state).expect("Unable to deserialize state");
+    let mut state = load(&opaque_state)?;
     state.tick()?;
     Ok(serde_json::to_string(&state).unwrap())
 }
@@ -59,10 +85,9 @@ fn player_action(
     player_id: PlayerId,
     opaque_action: OpaqueMsg,
 ) -> Result<OpaqueState, PyGameError> {
-    let mut state: GameState =
-        serde_json::from_str(&opaque_state).expect("Unable to deserialize state");
-    let action: Msg =
-        serde_json::from_str(&opaque_action).expect("unable to deserialize player action");
+    let mut state = load(&opaque_state)?;
+    let action: Msg = serde_json::from_str(&opaque_action)
+        .map_err(|e| PyGameError::MessageDeserializeError(e.to_string()))?;
     if let Msg::Action(a) = action {
         match a {
             action::Msg::Fold => state.player_folds(player_id)?,
@@ -77,27 +102,353 @@ fn player_action(
     Ok(serde_json::to_string(&state).unwrap())
 }
 
+/// Log something the given player said to the table, given as a Chat message. Error if it isn't
+/// one, if it's from someone else, or if there's nothing left to say once it's cleaned up.
+#[pyfunction]
+fn chat(
+    opaque_state: OpaqueState,
+    player_id: PlayerId,
+    opaque_msg: OpaqueMsg,
+) -> Result<OpaqueState, PyGameError> {
+    let mut state = load(&opaque_state)?;
+    match serde_json::from_str(&opaque_msg) {
+        Ok(Msg::Chat { from, text }) if from == player_id => state.chat(player_id, &text)?,
+        _ => return Err(PyGameError::MessageNotChat),
+    }
+    Ok(serde_json::to_string(&state).unwrap())
+}
+
+/// Finish a hand that nobody can act in any more, e.g. because everyone is all in. Error if
+/// someone still has to act.
+#[pyfunction]
+fn run_out(opaque_state: OpaqueState) -> Result<OpaqueState, PyGameError> {
+    let mut state = load(&opaque_state)?;
+    state.run_out()?;
+    Ok(serde_json::to_string(&state).unwrap())
+}
+
 #[pyfunction]
 fn state_changes_since(
     opaque_state: OpaqueState,
     seq_num: SeqNum,
     player_id: PlayerId,
 ) -> Result<OpaqueMsg, PyGameError> {
-    let state: GameState =
-        serde_json::from_str(&opaque_state).expect("Unable to deserialize state");
+    let state = load(&opaque_state)?;
     let changes: Vec<(SeqNum, LogItem)> =
         state.filtered_changes_since(seq_num, player_id).collect();
     Ok(serde_json::to_string(&Msg::GameLogs(changes)).unwrap())
 }
 
+/// What a client that last saw the given sequence number needs to catch up, as a GameLogs message.
+/// If it's missed the start of this hand, that's a new base state and everything since.
+#[pyfunction]
+fn resync_payload(
+    opaque_state: OpaqueState,
+    last_seen_seq: SeqNum,
+    player_id: PlayerId,
+) -> Result<OpaqueMsg, PyGameError> {
+    let state = load(&opaque_state)?;
+    let changes = state.resync_payload(last_seen_seq, player_id);
+    Ok(serde_json::to_string(&Msg::GameLogs(changes)).unwrap())
+}
+
+#[pyfunction]
+fn is_players_turn(opaque_state: OpaqueState, player_id: PlayerId) -> Result<bool, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.is_players_turn(player_id))
+}
+
+#[pyfunction]
+fn can_check(opaque_state: OpaqueState, player_id: PlayerId) -> Result<bool, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.can_check(player_id))
+}
+
+#[pyfunction]
+fn can_call(opaque_state: OpaqueState, player_id: PlayerId) -> Result<bool, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.can_call(player_id))
+}
+
+/// Pot odds, equity, and outs for the given player as a DecisionHelp message, or None if it isn't
+/// their turn.
+#[pyfunction]
+fn decision_help(
+    opaque_state: OpaqueState,
+    player_id: PlayerId,
+) -> Result<Option<OpaqueMsg>, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state
+        .decision_help(player_id)
+        .map(|help| serde_json::to_string(&Msg::from(help)).unwrap()))
+}
+
+/// What the given player may do and for how much, as a JSON object with `can_fold`, `can_check`,
+/// `call_amount`, `can_raise`, `min_raise`, and `max_raise`, or None if it isn't their turn.
+#[pyfunction]
+fn legal_actions(
+    opaque_state: OpaqueState,
+    player_id: PlayerId,
+) -> Result<Option<String>, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state
+        .legal_actions(player_id)
+        .map(|la| serde_json::to_string(&la).unwrap()))
+}
+
+/// The given player's stack, bet status, seat, and pocket (as two-character card codes, e.g.
+/// `["As", "Td"]`, or null if not dealt) as a JSON object, or None if they aren't seated. Nothing
+/// is hidden, so this is only for trusted callers.
+#[pyfunction]
+fn player_info(
+    opaque_state: OpaqueState,
+    player_id: PlayerId,
+) -> Result<Option<String>, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.seat_of(player_id).and_then(|seat_idx| {
+        let player = state.player_at(seat_idx)?;
+        let pocket = player
+            .pocket
+            .map(|cards| cards.iter().map(|c| c.to_string()).collect::<Vec<_>>());
+        let info = serde_json::json!({
+            "stack": player.stack,
+            "bet_status": player.bet_status,
+            "seat_idx": seat_idx,
+            "pocket": pocket,
+        });
+        Some(info.to_string())
+    }))
+}
+
+/// Everything in the pot, including the current betting round's bets
+#[pyfunction]
+fn pot_total(opaque_state: OpaqueState) -> Result<Currency, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.pot_total_value())
+}
+
+/// The amount everyone has to match to stay in this betting round
+#[pyfunction]
+fn current_bet(opaque_state: OpaqueState) -> Result<Currency, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.current_bet())
+}
+
+/// The smallest total a raise this betting round may be to
+#[pyfunction]
+fn min_raise(opaque_state: OpaqueState) -> Result<Currency, PyGameError> {
+    let state = load(&opaque_state)?;
+    Ok(state.min_raise())
+}
+
 /// A Python module implemented in Rust.
 #[pymodule]
 fn poker_core_py(_py: Python, m: &PyModule) -> PyResult<()> {
     //m.add_function(wrap_pyfunction!(sum_as_string, m)?)?;
     m.add_function(wrap_pyfunction!(new_game_state, m)?)?;
     m.add_function(wrap_pyfunction!(seat_player, m)?)?;
+    m.add_function(wrap_pyfunction!(game_config, m)?)?;
+    m.add_function(wrap_pyfunction!(set_game_config, m)?)?;
     m.add_function(wrap_pyfunction!(tick_state, m)?)?;
     m.add_function(wrap_pyfunction!(player_action, m)?)?;
+    m.add_function(wrap_pyfunction!(chat, m)?)?;
+    m.add_function(wrap_pyfunction!(run_out, m)?)?;
     m.add_function(wrap_pyfunction!(state_changes_since, m)?)?;
+    m.add_function(wrap_pyfunction!(resync_payload, m)?)?;
+    m.add_function(wrap_pyfunction!(is_players_turn, m)?)?;
+    m.add_function(wrap_pyfunction!(can_check, m)?)?;
+    m.add_function(wrap_pyfunction!(can_call, m)?)?;
+    m.add_function(wrap_pyfunction!(decision_help, m)?)?;
+    m.add_function(wrap_pyfunction!(legal_actions, m)?)?;
+    m.add_function(wrap_pyfunction!(player_info, m)?)?;
+    m.add_function(wrap_pyfunction!(pot_total, m)?)?;
+    m.add_function(wrap_pyfunction!(current_bet, m)?)?;
+    m.add_function(wrap_pyfunction!(min_raise, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_core::state::{LegalActions, State};
+
+    /// The blinds are in the pot as soon as a tick starts the hand
+    #[test]
+    fn pot_reflects_blinds() {
+        let state = new_game_state();
+        let state = seat_player(state, 1, 1000).unwrap();
+        let state = seat_player(state, 2, 1000).unwrap();
+        assert_eq!(pot_total(state.clone()).unwrap(), 0);
+        let state = tick_state(state).unwrap();
+        let config: GameConfig =
+            serde_json::from_str(&game_config(state.clone()).unwrap()).unwrap();
+        assert_eq!(
+            pot_total(state.clone()).unwrap(),
+            config.small_blind + config.big_blind
+        );
+        assert_eq!(current_bet(state.clone()).unwrap(), config.big_blind);
+        assert_eq!(min_raise(state).unwrap(), config.big_blind * 2);
+    }
+
+    /// Once the hand starts, everyone dealt in has two different cards
+    #[test]
+    fn player_info_pocket() {
+        let state = new_game_state();
+        let state = seat_player(state, 1, 1000).unwrap();
+        let state = seat_player(state, 2, 1000).unwrap();
+        assert_eq!(player_info(state.clone(), 3).unwrap(), None);
+        let info: serde_json::Value =
+            serde_json::from_str(&player_info(state.clone(), 1).unwrap().unwrap()).unwrap();
+        assert!(info["pocket"].is_null());
+        let state = tick_state(state).unwrap();
+        let mut seen = vec![];
+        for player_id in [1, 2] {
+            let info: serde_json::Value =
+                serde_json::from_str(&player_info(state.clone(), player_id).unwrap().unwrap())
+                    .unwrap();
+            assert_eq!(
+                info["seat_idx"],
+                serde_json::json!(state_seat(&state, player_id))
+            );
+            let pocket = info["pocket"].as_array().unwrap();
+            assert_eq!(pocket.len(), 2);
+            for card in pocket {
+                let card = card.as_str().unwrap();
+                let mut chars = card.chars();
+                assert!("23456789TJQKA".contains(chars.next().unwrap()), "{card}");
+                assert!("cdhs".contains(chars.next().unwrap()), "{card}");
+                assert_eq!(chars.next(), None);
+                assert!(!seen.contains(&card.to_owned()));
+                seen.push(card.to_owned());
+            }
+        }
+    }
+
+    /// After both players shove preflop, the hand is over and someone has the chips
+    #[test]
+    fn run_out_shove() {
+        let state = new_game_state();
+        let state = seat_player(state, 1, 1000).unwrap();
+        let state = seat_player(state, 2, 1000).unwrap();
+        let state = tick_state(state).unwrap();
+        assert!(run_out(state.clone()).is_err());
+        let shove = serde_json::to_string(&Msg::Action(action::Msg::Raise(1000))).unwrap();
+        let call = serde_json::to_string(&Msg::Action(action::Msg::Call)).unwrap();
+        let first = if is_players_turn(state.clone(), 1).unwrap() {
+            1
+        } else {
+            2
+        };
+        let state = player_action(state, first, shove).unwrap();
+        let state = player_action(state, 3 - first, call).unwrap();
+        let state: GameState = serde_json::from_str(&run_out(state).unwrap()).unwrap();
+        assert!(matches!(state.state(), State::EndOfHand));
+        let stacks: Vec<Currency> = [1, 2]
+            .iter()
+            .map(|pid| state.players.player_by_id(*pid).unwrap().stack)
+            .collect();
+        assert!(stacks == [2000, 0] || stacks == [0, 2000] || stacks == [1000, 1000]);
+    }
+
+    fn state_seat(opaque_state: &str, player_id: PlayerId) -> usize {
+        let state: GameState = serde_json::from_str(opaque_state).unwrap();
+        state.seat_of(player_id).unwrap()
+    }
+
+    /// Heads up, the SB acts first preflop and has to call the rest of the BB or fold
+    #[test]
+    fn legal_actions_preflop() {
+        let state = new_game_state();
+        let state = seat_player(state, 1, 1000).unwrap();
+        let state = seat_player(state, 2, 1000).unwrap();
+        let state = tick_state(state).unwrap();
+        let config: GameConfig =
+            serde_json::from_str(&game_config(state.clone()).unwrap()).unwrap();
+        let (sb, bb) = if is_players_turn(state.clone(), 1).unwrap() {
+            (1, 2)
+        } else {
+            (2, 1)
+        };
+        assert_eq!(legal_actions(state.clone(), bb).unwrap(), None);
+        let la: LegalActions =
+            serde_json::from_str(&legal_actions(state, sb).unwrap().unwrap()).unwrap();
+        assert!(la.can_fold && !la.can_check && la.can_raise);
+        assert_eq!(la.call_amount, config.big_blind - config.small_blind);
+        assert_eq!(la.min_raise, config.big_blind * 2);
+        assert_eq!(la.max_raise, 1000);
+    }
+
+    /// Garbage instead of a game state is an error for the caller, not a crash. Python sees it as
+    /// a ValueError, like every PyGameError.
+    #[test]
+    fn garbage_state() {
+        let e = tick_state("{garbage".to_owned()).unwrap_err();
+        assert!(matches!(e, PyGameError::GameStateDeserializeError(_)));
+        assert!(matches!(
+            pot_total("[]".to_owned()),
+            Err(PyGameError::GameStateDeserializeError(_))
+        ));
+    }
+
+    /// Likewise garbage instead of an action, while a message that isn't an action is its own error
+    #[test]
+    fn garbage_action() {
+        let state = new_game_state();
+        let state = seat_player(state, 1, 1000).unwrap();
+        let state = seat_player(state, 2, 1000).unwrap();
+        let state = tick_state(state).unwrap();
+        assert!(matches!(
+            player_action(state.clone(), 1, "{garbage".to_owned()),
+            Err(PyGameError::MessageDeserializeError(_))
+        ));
+        let chat = serde_json::to_string(&Msg::Chat {
+            from: 1,
+            text: "gg".to_owned(),
+        })
+        .unwrap();
+        assert!(matches!(
+            player_action(state, 1, chat),
+            Err(PyGameError::MessageNotAnAction)
+        ));
+    }
+
+    /// Garbage instead of a config is an error for the caller too, and a real one goes back in
+    #[test]
+    fn garbage_config() {
+        let state = new_game_state();
+        assert!(matches!(
+            set_game_config(state.clone(), "{garbage".to_owned()),
+            Err(PyGameError::ConfigDeserializeError(_))
+        ));
+        let config = game_config(state.clone()).unwrap();
+        set_game_config(state, config).unwrap();
+    }
+
+    /// A chat message survives the trip to the server and back out to everyone in the logs
+    #[test]
+    fn chat_round_trip() {
+        let msg = Msg::Chat {
+            from: 1,
+            text: "gg".to_owned(),
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(serde_json::from_str::<Msg>(&json).unwrap(), msg);
+        let state = new_game_state();
+        let state = seat_player(state, 1, 1000).unwrap();
+        assert!(matches!(
+            chat(state.clone(), 2, json.clone()),
+            Err(PyGameError::MessageNotChat)
+        ));
+        let state = chat(state, 1, json).unwrap();
+        let logs: Msg = serde_json::from_str(&state_changes_since(state, 0, 2).unwrap()).unwrap();
+        let said: Vec<LogItem> = match logs {
+            Msg::GameLogs(items) => items
+                .into_iter()
+                .map(|(_, li)| li)
+                .filter(|li| matches!(li, LogItem::Chat(_, _)))
+                .collect(),
+            _ => panic!("not logs"),
+        };
+        assert_eq!(said, vec![LogItem::Chat(1, "gg".to_owned())]);
+    }
+}
@@ -11,10 +11,47 @@ pub enum BetAction {
     AllIn(Currency),
 }
 
+/// Bet amounts are always the player's total commitment for the betting round, not the amount
+/// they are adding to it. If the current bet is 20 and a player wants to raise 10 more, that's
+/// `Raise(30)`, and a later call of a raise to 60 by them is `Call(60)`, not `Call(30)`. The
+/// constructors here do that math so callers don't have to.
 impl BetAction {
+    /// Call the given current bet.
+    pub const fn call(current_bet: Currency) -> Self {
+        BetAction::Call(current_bet)
+    }
+
+    /// Raise to the given total, e.g. a slider showing what the player will be in for.
+    pub const fn raise_to(total: Currency) -> Self {
+        BetAction::Raise(total)
+    }
+
+    /// Raise by putting the given number of chips in on top of what the player already has in this
+    /// betting round. Already in for 10 and adding 20 is `Raise(30)`.
+    pub const fn raise_by(additional: Currency, current_in: Currency) -> Self {
+        BetAction::Raise(current_in + additional)
+    }
+
     pub const fn is_allin(&self) -> bool {
         matches!(self, &BetAction::AllIn(_))
     }
+
+    /// The total amount this action commits the player to, or `None` for actions that don't have
+    /// one (checks and folds).
+    pub const fn amount(&self) -> Option<Currency> {
+        match self {
+            BetAction::Check | BetAction::Fold => None,
+            BetAction::Call(x) | BetAction::Bet(x) | BetAction::Raise(x) | BetAction::AllIn(x) => {
+                Some(*x)
+            }
+        }
+    }
+
+    /// Whether this action puts more money in than everyone else has, forcing others to respond.
+    /// All-ins aren't considered aggressive here, as whether they are depends on the current bet.
+    pub const fn is_aggressive(&self) -> bool {
+        matches!(self, BetAction::Bet(_) | BetAction::Raise(_))
+    }
 }
 
 impl std::fmt::Display for BetAction {
@@ -30,17 +67,49 @@ impl std::fmt::Display for BetAction {
     }
 }
 
-#[derive(Debug, derive_more::Display)]
+/// How much players may bet and raise.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BettingLimit {
+    /// Any amount from the minimum up to everything in front of you
+    NoLimit,
+    /// Bets and raises are exactly `small_bet` preflop and on the flop and exactly `big_bet` on the
+    /// turn and river, and there may be at most `cap` raises each street. Going all in is for at
+    /// most one increment more than the current bet. All in for a full increment is a raise like
+    /// any other, and all in for less is always allowed.
+    FixedLimit {
+        small_bet: Currency,
+        big_bet: Currency,
+        cap: u8,
+    },
+}
+
+impl Default for BettingLimit {
+    fn default() -> Self {
+        Self::NoLimit
+    }
+}
+
+#[derive(Debug, derive_more::Display, derive_more::Error)]
 pub enum BetError {
+    #[display(fmt = "An all in has to be for the player's whole stack")]
     AllInWithoutBeingAllIn,
+    #[display(fmt = "Player has no chips to bet")]
     HasNoMoney,
+    #[display(fmt = "Bet is too small")]
     BetTooLow,
+    #[display(fmt = "Bet is more than the player has")]
     BetTooHigh,
+    #[display(fmt = "Player isn't able to bet")]
     PlayerIsNotBetting,
+    #[display(fmt = "Player isn't seated at this table")]
     PlayerNotFound,
+    #[display(fmt = "Player can't re-raise their own raise")]
     CantRaiseSelf,
+    #[display(fmt = "That action isn't allowed")]
     BadAction,
+    #[display(fmt = "It isn't that player's turn")]
     OutOfTurn,
+    #[display(fmt = "No one is expected to act right now")]
     NoBetExpected,
 }
 
@@ -52,6 +121,23 @@ pub enum BetStatus {
     AllIn(Currency),
 }
 
+impl BetStatus {
+    /// How much more a player with this status needs to put in to match the given current bet.
+    /// Players that have folded or are all in don't owe anything.
+    pub const fn amount_to_call(&self, current_bet: Currency) -> Currency {
+        let existing_in = match self {
+            BetStatus::Folded | BetStatus::AllIn(_) => return 0,
+            BetStatus::Waiting => 0,
+            BetStatus::In(x) => *x,
+        };
+        if existing_in < current_bet {
+            current_bet - existing_in
+        } else {
+            0
+        }
+    }
+}
+
 impl Default for BetStatus {
     fn default() -> Self {
         BetStatus::Waiting
@@ -68,3 +154,48 @@ impl From<BetAction> for BetStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raise_by() {
+        assert_eq!(BetAction::raise_by(20, 10), BetAction::Raise(30));
+        assert_eq!(BetAction::raise_by(0, 10), BetAction::Raise(10));
+        assert_eq!(BetAction::raise_by(60, 0), BetAction::Raise(60));
+        assert_eq!(BetAction::call(20), BetAction::Call(20));
+    }
+
+    /// Raising to a total and raising by what's added on top of what's in agree with each other
+    #[test]
+    fn raise_to() {
+        assert_eq!(BetAction::raise_to(30), BetAction::Raise(30));
+        assert_eq!(BetAction::raise_to(30), BetAction::raise_by(20, 10));
+        assert_eq!(BetAction::raise_to(30).amount(), Some(30));
+    }
+
+    #[test]
+    fn amount() {
+        assert_eq!(BetAction::Check.amount(), None);
+        assert_eq!(BetAction::Fold.amount(), None);
+        assert_eq!(BetAction::Call(5).amount(), Some(5));
+        assert_eq!(BetAction::Bet(5).amount(), Some(5));
+        assert_eq!(BetAction::Raise(10).amount(), Some(10));
+        assert_eq!(BetAction::AllIn(7).amount(), Some(7));
+    }
+
+    #[test]
+    fn is_aggressive() {
+        assert!(BetAction::Bet(5).is_aggressive());
+        assert!(BetAction::Raise(10).is_aggressive());
+        for ba in [
+            BetAction::Check,
+            BetAction::Fold,
+            BetAction::Call(5),
+            BetAction::AllIn(5),
+        ] {
+            assert!(!ba.is_aggressive());
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use crate::hand::Ruleset;
 use base64ct::{self, Base64, Encoding};
 use rand::prelude::*;
 use rand_chacha::ChaChaRng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
@@ -49,6 +51,13 @@ pub enum Suit {
     Spade,
 }
 
+impl Suit {
+    /// All four suits, in the same order as [`ALL_SUITS`]
+    pub fn all() -> impl DoubleEndedIterator<Item = Suit> {
+        ALL_SUITS.into_iter()
+    }
+}
+
 impl fmt::Display for Suit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -90,6 +99,13 @@ pub enum Rank {
     RA,
 }
 
+impl Rank {
+    /// All thirteen ranks, lowest (2) to highest (A)
+    pub fn all() -> impl DoubleEndedIterator<Item = Rank> {
+        ALL_RANKS.into_iter()
+    }
+}
+
 impl fmt::Display for Rank {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -170,6 +186,11 @@ impl Card {
         Self { rank, suit }
     }
 
+    /// All 52 cards, sorted: by rank (2 to A), then by suit within each rank.
+    pub fn all() -> impl DoubleEndedIterator<Item = Card> {
+        Rank::all().flat_map(|rank| Suit::all().map(move |suit| Card::new(rank, suit)))
+    }
+
     pub fn suit(self) -> Suit {
         self.suit
     }
@@ -213,12 +234,8 @@ pub struct Deck {
 
 impl Default for Deck {
     fn default() -> Self {
-        use itertools::Itertools;
-        let c: Vec<Card> = ALL_RANKS
-            .iter()
-            .cartesian_product(ALL_SUITS.iter())
-            .map(|x| Card::new(*x.0, *x.1))
-            .collect();
+        let mut c = Vec::with_capacity(DECK_LEN);
+        c.extend(Card::all());
         assert_eq!(c.len(), DECK_LEN);
         let mut d = Deck { cards: c };
         d.shuffle();
@@ -227,14 +244,39 @@ impl Default for Deck {
 }
 
 impl Deck {
+    /// A deck stacked with exactly the given cards, the first of which will be drawn first.
+    pub fn from_cards(mut cards: Vec<Card>) -> Self {
+        cards.reverse();
+        Self { cards }
+    }
+
     /// Generate a new single deck of cards, shuffled
     pub fn new(seed: &DeckSeed) -> Self {
+        Self::with_rng(&mut seed.rng())
+    }
+
+    /// Generate a new single deck of cards, shuffled with the given RNG. Use a CSPRNG for real
+    /// games; [`Deck::new`] is this with the seed's ChaCha RNG.
+    pub fn with_rng<R: RngCore + ?Sized>(rng: &mut R) -> Self {
         let mut d = Self::default();
+        d.shuffle_with(rng);
+        d
+    }
+
+    /// Generate a new single deck of cards for the given ruleset, shuffled. A short deck has the
+    /// 36 cards from 6 up.
+    pub fn new_for(seed: &DeckSeed, ruleset: Ruleset) -> Self {
+        let mut d = match ruleset {
+            Ruleset::Standard => Self::default(),
+            Ruleset::ShortDeck => Self {
+                cards: Card::all().filter(|c| c.rank() >= Rank::R6).collect(),
+            },
+        };
         d.seeded_shuffle(seed);
         d
     }
 
-    ///
+    /// Generate a new single deck of cards, shuffled with a new random seed. Return both.
     pub fn deck_and_seed() -> (Deck, DeckSeed) {
         let ds = DeckSeed::default();
         let d = Deck::new(&ds);
@@ -247,10 +289,15 @@ impl Deck {
     }
 
     pub fn seeded_shuffle(&mut self, seed: &DeckSeed) {
-        let mut rng = ChaChaRng::from_seed(seed.0);
-        // For determinism given the same seed, the cards need to be in a known order before shuffling.
+        self.shuffle_with(&mut seed.rng())
+    }
+
+    /// Shuffle the deck of cards in-place with the given RNG
+    pub fn shuffle_with<R: RngCore + ?Sized>(&mut self, rng: &mut R) {
+        // For determinism given the same RNG state, the cards need to be in a known order before
+        // shuffling.
         self.cards.sort_unstable();
-        self.cards.shuffle(&mut rng)
+        self.cards.shuffle(rng)
     }
 
     /// Draw the topmost card and return it, or return and error if, e.g., there are no more cards.
@@ -258,6 +305,37 @@ impl Deck {
         self.cards.pop().ok_or(DeckError::OutOfCards)
     }
 
+    /// The number of cards left in the deck
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// The number of cards still to come, e.g. for a "cards remaining" display. Burned cards are
+    /// gone just like drawn ones. The same as [`Deck::len`].
+    pub fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    /// Put every card of a full deck that isn't in this one back in, each at a random position
+    /// among the cards still here, the way a continuous shuffler takes back the last hand's cards.
+    pub(crate) fn refill_with<R: RngCore + ?Sized>(&mut self, rng: &mut R) {
+        let missing: Vec<Card> = Card::all().filter(|c| !self.cards.contains(c)).collect();
+        for card in missing {
+            let idx = rng.gen_range(0..=self.cards.len());
+            self.cards.insert(idx, card);
+        }
+    }
+
+    /// The next (up to) `n` cards, in the order [`Deck::draw`] would return them, without drawing
+    /// them.
+    pub fn peek(&self, n: usize) -> Vec<Card> {
+        self.cards.iter().rev().take(n).copied().collect()
+    }
+
     pub fn burn(&mut self) {
         self.cards.pop();
     }
@@ -280,13 +358,25 @@ impl Deck {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DeckSeed([u8; SEED_LEN]);
 
 impl DeckSeed {
-    pub fn new(b: [u8; SEED_LEN]) -> Self {
+    pub const fn new(b: [u8; SEED_LEN]) -> Self {
         Self(b)
     }
+
+    /// The sha256 of this seed. Published before a hand, it commits to the shuffle without giving
+    /// it away; once the seed is revealed after the hand, anyone can check the two match.
+    pub fn commitment(&self) -> [u8; 32] {
+        Sha256::digest(self.0).into()
+    }
+
+    /// A deterministic RNG derived from this seed, for when something other than a deck needs to
+    /// be shuffled reproducibly.
+    pub(crate) fn rng(&self) -> ChaChaRng {
+        ChaChaRng::from_seed(self.0)
+    }
 }
 
 impl Default for DeckSeed {
@@ -318,7 +408,7 @@ impl FromStr for DeckSeed {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
     const SEED1: DeckSeed = DeckSeed([1; SEED_LEN]);
     const SEED2: DeckSeed = DeckSeed([0; SEED_LEN]);
@@ -347,6 +437,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn all_cards() {
+        let all: HashSet<Card> = Card::all().collect();
+        assert_eq!(Card::all().count(), 52);
+        assert_eq!(all.len(), 52);
+        assert_eq!(Rank::all().count(), 13);
+        assert_eq!(Suit::all().count(), 4);
+    }
+
+    #[test]
+    fn with_rng() {
+        assert_eq!(Deck::with_rng(&mut SEED1.rng()), Deck::new(&SEED1));
+        assert_ne!(Deck::with_rng(&mut SEED1.rng()), Deck::new(&SEED2));
+        let d = Deck::with_rng(&mut thread_rng());
+        assert_eq!(d.len(), DECK_LEN);
+    }
+
+    #[test]
+    fn stacked_flop() {
+        let mut cards = cards_from_str("AsKsAhKh2c");
+        cards.extend(cards_from_str("QdJdTd"));
+        let mut d = Deck::from_cards(cards);
+        let pockets = d.deal_pockets(2).unwrap();
+        assert!(pockets.iter().flatten().all(|c| c.rank() >= Rank::RK));
+        d.burn();
+        let flop = [d.draw().unwrap(), d.draw().unwrap(), d.draw().unwrap()];
+        assert_eq!(flop.to_vec(), cards_from_str("QdJdTd"));
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn remaining_and_peek() {
+        let mut d = Deck::new(&SEED1);
+        d.deal_pockets(4).unwrap();
+        d.burn();
+        let next = d.peek(3);
+        assert_eq!(d.remaining(), DECK_LEN - 8 - 1);
+        let flop = [d.draw().unwrap(), d.draw().unwrap(), d.draw().unwrap()];
+        assert_eq!(flop.to_vec(), next);
+        assert_eq!(d.remaining(), DECK_LEN - 8 - 1 - 3);
+        assert_eq!(d.peek(DECK_LEN).len(), d.remaining());
+    }
+
     #[test]
     fn draw_1() {
         let mut d = Deck::default();
@@ -427,7 +560,7 @@ mod tests {
     #[test]
     fn deal_pockets_10() {
         let mut d = Deck::default();
-        let expect0 = [d.cards[51 - 0], d.cards[51 - 10]];
+        let expect0 = [d.cards[51], d.cards[51 - 10]];
         //        1              -1             -11
         //        2              -2             -12
         //              ...             ...
@@ -442,7 +575,7 @@ mod tests {
     fn deal_pockets_max() {
         let mut d = Deck::default();
         let n = MAX_PLAYERS as usize;
-        let expect0 = [d.cards[51 - 0], d.cards[51 - n]];
+        let expect0 = [d.cards[51], d.cards[51 - n]];
         let expectn = [d.cards[51 - (n - 1)], d.cards[51 - n - (n - 1)]];
         let actual = d.deal_pockets(n as u8).unwrap();
         assert_eq!(actual[0], expect0);
@@ -472,6 +605,17 @@ mod tests {
         assert_ne!(d, d2);
     }
 
+    #[test]
+    fn short_deck() {
+        let d = Deck::new_for(&SEED1, Ruleset::ShortDeck);
+        let ranks: HashSet<Rank> = d.cards.iter().map(|c| c.rank()).collect();
+        assert_eq!(d.len(), 36);
+        assert_eq!(d.cards.iter().collect::<HashSet<_>>().len(), 36);
+        assert_eq!(ranks.len(), 9);
+        assert!(!ranks.contains(&Rank::R5));
+        assert_eq!(Deck::new_for(&SEED1, Ruleset::Standard), Deck::new(&SEED1));
+    }
+
     #[test]
     fn seed_to_from_string() {
         let d = DeckSeed::default();
@@ -0,0 +1,224 @@
+use crate::deck::{Card, DeckSeed};
+use crate::hand::{best_hands, best_of_cards, WinState};
+use crate::PlayerId;
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+/// Each player's share of the pot on average, as a fraction from 0 to 1, over every possible way
+/// the rest of the community cards could come.
+///
+/// The cards that could still come are the 52 minus the given pockets and the known community
+/// cards. A pot that's split `n` ways counts `1/n` towards each of the players splitting it.
+///
+/// This is exact, so it gets slow fast. With 3 community cards known there are at most ~1000
+/// runouts, but preflop there are over a million.
+pub fn exact_equity(
+    pockets: &HashMap<PlayerId, [Card; 2]>,
+    community: &[Option<Card>; 5],
+) -> HashMap<PlayerId, f64> {
+    let mut equity: HashMap<PlayerId, f64> = pockets.keys().map(|pid| (*pid, 0.0)).collect();
+    if pockets.is_empty() {
+        return equity;
+    }
+    let known: Vec<Card> = community.iter().flatten().copied().collect();
+    let unseen: Vec<Card> = Card::all()
+        .filter(|c| !known.contains(c) && !pockets.values().any(|p| p.contains(c)))
+        .collect();
+    let mut runouts = 0;
+    for runout in unseen.into_iter().combinations(5 - known.len()) {
+        let mut runout = runout.into_iter();
+        let board = community.map(|c| c.or_else(|| runout.next()).unwrap());
+        // Unwrap is fine: there's at least one pocket, so there's at least one winner
+        let winners = best_hands(pockets, board).unwrap().swap_remove(0);
+        let share = 1.0 / winners.len() as f64;
+        for (pid, _) in winners {
+            *equity.get_mut(&pid).unwrap() += share;
+        }
+        runouts += 1;
+    }
+    for e in equity.values_mut() {
+        *e /= runouts as f64;
+    }
+    equity
+}
+
+/// How two pockets heads up do against each other over every possible way the rest of the
+/// community cards could come, as the fractions of runouts that the hero wins, that tie, and that
+/// the villain wins. Exact, and as slow, as [`exact_equity`].
+pub fn equity_exact(
+    hero: [Card; 2],
+    villain: [Card; 2],
+    community: &[Option<Card>; 5],
+) -> (f64, f64, f64) {
+    let known: Vec<Card> = community.iter().flatten().copied().collect();
+    let unseen: Vec<Card> = Card::all()
+        .filter(|c| !known.contains(c) && !hero.contains(c) && !villain.contains(c))
+        .collect();
+    let (mut wins, mut ties, mut losses) = (0, 0, 0);
+    let mut cards = Vec::with_capacity(7);
+    for runout in unseen.into_iter().combinations(5 - known.len()) {
+        let mut runout = runout.into_iter();
+        let board = community.map(|c| c.or_else(|| runout.next()).unwrap());
+        let mut best = |pocket: [Card; 2]| {
+            cards.clear();
+            cards.extend_from_slice(&pocket);
+            cards.extend_from_slice(&board);
+            best_of_cards(&cards)[0]
+        };
+        let hero_hand = best(hero);
+        match hero_hand.beats(&best(villain)) {
+            WinState::Win => wins += 1,
+            WinState::Tie => ties += 1,
+            WinState::Lose => losses += 1,
+        }
+    }
+    let runouts = (wins + ties + losses) as f64;
+    (
+        wins as f64 / runouts,
+        ties as f64 / runouts,
+        losses as f64 / runouts,
+    )
+}
+
+/// The given pocket's share of the pot on average, as a fraction from 0 to 1, against the given
+/// number of opponents holding random cards, estimated by dealing out the opponents' pockets and the
+/// rest of the board `trials` times. Ties count fractionally, as in [`exact_equity`].
+///
+/// The same seed always gives the same estimate. With no opponents, the pot is always won; with no
+/// trials, nothing is known and 0 is returned.
+pub fn equity(
+    pocket: [Card; 2],
+    community: &[Option<Card>; 5],
+    num_opponents: usize,
+    trials: usize,
+    seed: DeckSeed,
+) -> f64 {
+    if num_opponents == 0 {
+        return 1.0;
+    }
+    if trials == 0 {
+        return 0.0;
+    }
+    let known: Vec<Card> = community.iter().flatten().copied().collect();
+    let mut unseen: Vec<Card> = Card::all()
+        .filter(|c| !known.contains(c) && !pocket.contains(c))
+        .collect();
+    let needed = num_opponents * 2 + 5 - known.len();
+    assert!(needed <= unseen.len(), "too many opponents to deal to");
+    let mut rng = seed.rng();
+    // The hero is 0 and the opponents are 1 through num_opponents
+    let mut pockets = HashMap::with_capacity(num_opponents + 1);
+    let mut won = 0.0;
+    for _ in 0..trials {
+        let (dealt, _) = unseen.partial_shuffle(&mut rng, needed);
+        let mut dealt = dealt.iter().copied();
+        pockets.clear();
+        pockets.insert(0, pocket);
+        for opp in 1..=num_opponents {
+            pockets.insert(
+                opp as PlayerId,
+                [dealt.next().unwrap(), dealt.next().unwrap()],
+            );
+        }
+        let board = community.map(|c| c.or_else(|| dealt.next()).unwrap());
+        // Unwrap is fine: there's at least one pocket, so there's at least one winner
+        let winners = best_hands(&pockets, board).unwrap().swap_remove(0);
+        if winners.iter().any(|(pid, _)| *pid == 0) {
+            won += 1.0 / winners.len() as f64;
+        }
+    }
+    won / trials as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::cards_from_str;
+
+    fn pocket(s: &'static str) -> [Card; 2] {
+        let c = cards_from_str(s);
+        [c[0], c[1]]
+    }
+
+    fn community(s: &'static str) -> [Option<Card>; 5] {
+        let mut comm = [None; 5];
+        for (i, c) in cards_from_str(s).into_iter().enumerate() {
+            comm[i] = Some(c);
+        }
+        comm
+    }
+
+    /// KK needs one of the two kings left on the river to beat AA
+    #[test]
+    fn one_card_to_come() {
+        let pockets = HashMap::from([(1, pocket("AhAd")), (2, pocket("KsKc"))]);
+        let eq = exact_equity(&pockets, &community("2c7d9hJs"));
+        assert!((eq[&1] - 42.0 / 44.0).abs() < 1e-9);
+        assert!((eq[&2] - 2.0 / 44.0).abs() < 1e-9);
+    }
+
+    /// The estimate only depends on the seed, and AA on this board is a big favorite
+    #[test]
+    fn monte_carlo_deterministic() {
+        let seed = DeckSeed::new([7; 32]);
+        let comm = community("2c7d9hJs");
+        let a = equity(pocket("AhAd"), &comm, 1, 300, seed);
+        let b = equity(pocket("AhAd"), &comm, 1, 300, seed);
+        assert_eq!(a, b);
+        assert!(a > 0.8);
+        assert_eq!(equity(pocket("AhAd"), &comm, 0, 300, seed), 1.0);
+    }
+
+    /// Aces are better than an 80% favorite preflop against one random hand
+    #[test]
+    fn aces_preflop() {
+        let seed = DeckSeed::new([3; 32]);
+        let eq = equity(pocket("AhAd"), &[None; 5], 1, 500, seed);
+        assert!(eq > 0.8, "{eq}");
+        assert!(eq < 0.9, "{eq}");
+    }
+
+    /// Two cards to come: 22 against a flopped set of aces only wins with both of the last two 2s,
+    /// which beats the full house the aces make with that board. Nothing else gets there: the 2s
+    /// can't make a straight or a flush in two cards, and the aces can't be tied.
+    #[test]
+    fn exact_two_to_come() {
+        let (win, tie, lose) = equity_exact(pocket("2c2d"), pocket("AhAd"), &community("Ac7s9h"));
+        // 45 unseen cards, so C(45, 2) = 990 runouts
+        assert!((win - 1.0 / 990.0).abs() < 1e-12);
+        assert_eq!(tie, 0.0);
+        assert!((lose - 989.0 / 990.0).abs() < 1e-12);
+    }
+
+    /// A pair against two overcards, suited, is close to a coin flip preflop. Run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore = "enumerates 1.7M boards, a few minutes even with --release"]
+    fn coinflip_preflop() {
+        let (win, tie, lose) = equity_exact(pocket("AsKs"), pocket("QhQd"), &[None; 5]);
+        assert!((win + tie + lose - 1.0).abs() < 1e-9);
+        assert!((0.45..0.48).contains(&win), "{win}");
+        assert!((0.52..0.55).contains(&lose), "{lose}");
+        assert!(tie < 0.01, "{tie}");
+    }
+
+    /// Against a made flush, a set needs the board to pair
+    #[test]
+    fn exact_heads_up() {
+        let (win, tie, lose) = equity_exact(pocket("9c9d"), pocket("AhKh"), &community("9h5h2hJs"));
+        // 44 rivers: 10 of them pair the board (three 5s, three 2s, three jacks, the last 9)
+        assert!((win - 10.0 / 44.0).abs() < 1e-9);
+        assert_eq!(tie, 0.0);
+        assert!((lose - 34.0 / 44.0).abs() < 1e-9);
+    }
+
+    /// Two players holding the same ranks split every pot they don't lose
+    #[test]
+    fn chopped() {
+        let pockets = HashMap::from([(1, pocket("AhKd")), (2, pocket("AsKc"))]);
+        let eq = exact_equity(&pockets, &community("2c7d9hJsQd"));
+        assert_eq!(eq[&1], 0.5);
+        assert_eq!(eq[&2], 0.5);
+    }
+}
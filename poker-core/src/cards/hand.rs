@@ -1,6 +1,7 @@
 use crate::deck::{Card, Rank};
 use crate::PlayerId;
 use itertools::{zip, Itertools};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::error::Error;
@@ -33,13 +34,46 @@ impl From<WinState> for Ordering {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Hand {
     cards: [Card; 5],
     class: HandClass,
+    #[serde(default)]
+    ruleset: Ruleset,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// Which hand rankings to use when classifying and comparing hands.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ruleset {
+    /// Regular 52-card rankings
+    Standard,
+    /// Short-deck (6+) rankings, for a 36-card deck without 2s through 5s. A flush beats a full
+    /// house, and A6789 is the lowest straight.
+    ShortDeck,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+impl Ruleset {
+    /// Compare two hand classes under this ruleset.
+    pub fn cmp_class(self, left: HandClass, right: HandClass) -> Ordering {
+        self.class_strength(left).cmp(&self.class_strength(right))
+    }
+
+    fn class_strength(self, hc: HandClass) -> u8 {
+        match (self, hc) {
+            (Self::ShortDeck, HandClass::Flush) => HandClass::FullHouse as u8,
+            (Self::ShortDeck, HandClass::FullHouse) => HandClass::Flush as u8,
+            _ => hc as u8,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum HandClass {
     HighCard,
     Pair,
@@ -50,13 +84,32 @@ pub enum HandClass {
     FullHouse,
     FourOfAKind,
     StraightFlush,
+    RoyalFlush,
+}
+
+impl fmt::Display for HandClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::HighCard => "High Card",
+            Self::Pair => "Pair",
+            Self::TwoPair => "Two Pair",
+            Self::ThreeOfAKind => "Three of a Kind",
+            Self::Straight => "Straight",
+            Self::Flush => "Flush",
+            Self::FullHouse => "Full House",
+            Self::FourOfAKind => "Four of a Kind",
+            Self::StraightFlush => "Straight Flush",
+            Self::RoyalFlush => "Royal Flush",
+        };
+        write!(f, "{}", s)
+    }
 }
 
 impl HandClass {
-    fn beats(c1: &[Card], c2: &[Card]) -> WinState {
-        let hc1 = HandClass::which(c1);
-        let hc2 = HandClass::which(c2);
-        match hc1.cmp(&hc2) {
+    fn beats(c1: &[Card], c2: &[Card], ruleset: Ruleset) -> WinState {
+        let hc1 = HandClass::which(c1, ruleset);
+        let hc2 = HandClass::which(c2, ruleset);
+        match ruleset.cmp_class(hc1, hc2) {
             Ordering::Equal => {}
             o => return o.into(),
         };
@@ -80,7 +133,9 @@ impl HandClass {
         right.sort_unstable();
         right.reverse();
         match hc1 {
-            HandClass::StraightFlush => HandClass::beats_straight_flush(left, right),
+            HandClass::RoyalFlush | HandClass::StraightFlush => {
+                HandClass::beats_straight_flush(left, right)
+            }
             HandClass::FourOfAKind => HandClass::beats_quads(left, right),
             HandClass::FullHouse => HandClass::beats_full_house(left, right),
             HandClass::Flush => HandClass::beats_flush(left, right),
@@ -127,13 +182,15 @@ impl HandClass {
 
     fn beats_straight(left: [Rank; 5], right: [Rank; 5]) -> Ordering {
         // have to look special at 5432A straight, as it will be A5432 since cards are sorted by
-        // rank.
+        // rank. Same for the short-deck 9876A straight.
         let l = match (left[0], left[1]) {
             (Rank::RA, Rank::R5) => Rank::R5,
+            (Rank::RA, Rank::R9) => Rank::R9,
             (first, _) => first,
         };
         let r = match (right[0], right[1]) {
             (Rank::RA, Rank::R5) => Rank::R5,
+            (Rank::RA, Rank::R9) => Rank::R9,
             (first, _) => first,
         };
         l.cmp(&r)
@@ -235,7 +292,7 @@ impl HandClass {
         Ordering::Equal
     }
 
-    fn which(c: &[Card]) -> HandClass {
+    fn which(c: &[Card], ruleset: Ruleset) -> HandClass {
         // sort a copy, in case the order of the main copy of cards is important (and also because
         // we aren't mutably borrowing the hand)
         //
@@ -248,15 +305,20 @@ impl HandClass {
         let mut cards: [Card; 5] = [c[0], c[1], c[2], c[3], c[4]];
         cards.sort_unstable();
         cards.reverse();
-        if Self::is_straight_flush(&cards) {
-            Self::StraightFlush
+        if Self::is_straight_flush(&cards, ruleset) {
+            // sorted, so an ace-high straight is AKQJT and the wheel is A5432 (or A9876)
+            if cards[0].rank() == Rank::RA && cards[4].rank() == Rank::RT {
+                Self::RoyalFlush
+            } else {
+                Self::StraightFlush
+            }
         } else if Self::is_quads(&cards) {
             Self::FourOfAKind
         } else if Self::is_full_house(&cards) {
             Self::FullHouse
         } else if Self::is_flush(&cards) {
             Self::Flush
-        } else if Self::is_straight(&cards) {
+        } else if Self::is_straight(&cards, ruleset) {
             Self::Straight
         } else if Self::is_set(&cards) {
             Self::ThreeOfAKind
@@ -269,9 +331,9 @@ impl HandClass {
         }
     }
 
-    fn is_straight_flush(cards: &[Card; 5]) -> bool {
+    fn is_straight_flush(cards: &[Card; 5], ruleset: Ruleset) -> bool {
         // This function requires the given cards are sorted
-        Self::is_straight(cards) && Self::is_flush(cards)
+        Self::is_straight(cards, ruleset) && Self::is_flush(cards)
     }
 
     fn is_quads(cards: &[Card; 5]) -> bool {
@@ -293,7 +355,7 @@ impl HandClass {
         !Self::is_quads(cards)
     }
 
-    fn is_straight(cards: &[Card; 5]) -> bool {
+    fn is_straight(cards: &[Card; 5], ruleset: Ruleset) -> bool {
         // This function requires the given cards are sorted
         //
         // Convert ranks to ints that we can do basic math on. Rank 2 -> 0, Rank 3 -> 1, etc.
@@ -321,6 +383,10 @@ impl HandClass {
         if ints == [12, 3, 2, 1, 0] {
             return true;
         }
+        // Likewise for the short-deck A6789 straight, which appears as A9876
+        if ruleset == Ruleset::ShortDeck && ints == [12, 7, 6, 5, 4] {
+            return true;
+        }
         // Now make sure each successive int is one less than the previous one. This is why we
         // needed the cards sorted.
         for n in 0..4 {
@@ -398,6 +464,22 @@ impl fmt::Display for Hand {
     }
 }
 
+/// The hand a player showed down with: their best five cards and the class of hand they make.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FinalHandResult {
+    pub hand: Hand,
+    pub class: HandClass,
+}
+
+impl From<Hand> for FinalHandResult {
+    fn from(hand: Hand) -> Self {
+        Self {
+            hand,
+            class: hand.class,
+        }
+    }
+}
+
 impl Hand {
     pub fn new(cards: &[Card]) -> Result<Self, HandError> {
         match cards.len() {
@@ -407,9 +489,15 @@ impl Hand {
     }
 
     pub fn new_unchecked(c: &[Card]) -> Self {
+        Self::new_unchecked_with(c, Ruleset::Standard)
+    }
+
+    /// Like [`Hand::new_unchecked`], but classified and compared under the given ruleset.
+    pub fn new_unchecked_with(c: &[Card], ruleset: Ruleset) -> Self {
         Self {
             cards: [c[0], c[1], c[2], c[3], c[4]],
-            class: HandClass::which(c),
+            class: HandClass::which(c, ruleset),
+            ruleset,
         }
     }
 
@@ -417,13 +505,29 @@ impl Hand {
         self.cards
     }
 
+    pub fn class(&self) -> HandClass {
+        self.class
+    }
+
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+
+    /// Whether this hand beats the other, under this hand's ruleset.
     pub fn beats(&self, other: &Self) -> WinState {
-        match self.class.cmp(&other.class) {
-            Ordering::Equal => HandClass::beats(&self.cards, &other.cards),
+        match self.ruleset.cmp_class(self.class, other.class) {
+            Ordering::Equal => HandClass::beats(&self.cards, &other.cards, self.ruleset),
             o => o.into(),
         }
     }
 
+    /// Whether this hand is no better than the given community cards on their own, i.e. the
+    /// player holding it is playing the board and can at best chop with everyone still in.
+    pub fn playing_board(&self, community: &[Card; 5]) -> bool {
+        let board = Self::new_unchecked_with(community, self.ruleset);
+        matches!(self.beats(&board), WinState::Tie)
+    }
+
     /// Return the first Rank that we see more than once in the given slice of cards.
     ///
     /// Used as a helper for describe function.
@@ -484,6 +588,7 @@ impl Hand {
             Rank::RA => match cards[1].rank() {
                 Rank::RK => Rank::RA,
                 Rank::R5 => Rank::R5,
+                Rank::R9 => Rank::R9,
                 _ => unreachable!(),
             },
             _ => cards[0].rank(),
@@ -528,6 +633,7 @@ impl Hand {
             HandClass::StraightFlush => {
                 format!("{} high straight flush", Self::straight_high(&self.cards))
             }
+            HandClass::RoyalFlush => "Royal flush".to_string(),
         }
     }
 }
@@ -559,6 +665,11 @@ impl PartialOrd for Hand {
 ///
 /// The original use case was best 5 card hand given 7 cards.
 pub fn best_of_cards(cards: &[Card]) -> Vec<Hand> {
+    best_of_cards_with(cards, Ruleset::Standard)
+}
+
+/// Like [`best_of_cards`], but under the given ruleset.
+pub fn best_of_cards_with(cards: &[Card], ruleset: Ruleset) -> Vec<Hand> {
     if cards.len() < 5 {
         return vec![];
     }
@@ -569,7 +680,7 @@ pub fn best_of_cards(cards: &[Card]) -> Vec<Hand> {
             // .combinations() gives us a Vec<&Card>, but we want Vec<Card>
             combo.iter().map(|&c| *c).collect::<Vec<Card>>()
         })
-        .map(|combo| Hand::new_unchecked(&combo))
+        .map(|combo| Hand::new_unchecked_with(&combo, ruleset))
         .collect();
     // do r.beats(l) instead of l.beats(r) because we want the first items in the list to be better
     // than the ones that follow. Otherwise we'd have to sort and then reverse afterward.
@@ -602,6 +713,15 @@ pub fn best_of_cards(cards: &[Card]) -> Vec<Hand> {
 pub fn best_hands(
     pockets: &HashMap<PlayerId, [Card; 2]>,
     community: [Card; 5],
+) -> Result<Vec<Vec<(PlayerId, Hand)>>, HandError> {
+    best_hands_with(pockets, community, Ruleset::Standard)
+}
+
+/// Like [`best_hands`], but under the given ruleset.
+pub fn best_hands_with(
+    pockets: &HashMap<PlayerId, [Card; 2]>,
+    community: [Card; 5],
+    ruleset: Ruleset,
 ) -> Result<Vec<Vec<(PlayerId, Hand)>>, HandError> {
     if pockets.is_empty() {
         // This check is important, as later we pull out the best hand before iterating over the
@@ -621,7 +741,7 @@ pub fn best_hands(
         cards.extend_from_slice(pocket);
         cards.extend_from_slice(&community);
         assert_eq!(cards.len(), 7);
-        let hand = best_of_cards(&cards)[0];
+        let hand = best_of_cards_with(&cards, ruleset)[0];
         hands.push((account_id, hand));
     }
     // Do left beats right, as in this function we want the best to be at the end of the list,
@@ -654,6 +774,173 @@ pub fn best_hands(
     Ok(ret)
 }
 
+/// Like [`best_hands`], but with each player's [`FinalHandResult`] attached, for when the hand
+/// class matters as well as the ordering. The grouping is exactly that of [`best_hands`].
+pub fn best_hands_detailed(
+    pockets: &HashMap<PlayerId, [Card; 2]>,
+    community: [Card; 5],
+) -> Result<Vec<Vec<(PlayerId, FinalHandResult)>>, HandError> {
+    Ok(best_hands(pockets, community)?
+        .into_iter()
+        .map(|tier| {
+            tier.into_iter()
+                .map(|(pid, hand)| (pid, hand.into()))
+                .collect()
+        })
+        .collect())
+}
+
+/// A qualifying "8 or better" low hand for hi-lo games: five cards of different ranks, all 8 or
+/// below, with aces low. Straights and flushes don't count against a low.
+///
+/// Ordering is like [`Hand`]'s: the better low is the greater one, so the best of several lows is
+/// their `max()`. The best possible low is A2345 and the worst is 87654.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LowHand {
+    cards: [Card; 5],
+}
+
+impl LowHand {
+    /// The low the given five cards make, or None if they don't qualify.
+    pub fn new(c: &[Card]) -> Option<Self> {
+        if c.len() != 5 {
+            return None;
+        }
+        let ranks = c.iter().map(|c| Self::low_value(c.rank())).unique().count();
+        if ranks != 5 || c.iter().any(|c| Self::low_value(c.rank()) > 8) {
+            return None;
+        }
+        Some(Self {
+            cards: [c[0], c[1], c[2], c[3], c[4]],
+        })
+    }
+
+    pub fn cards(&self) -> [Card; 5] {
+        self.cards
+    }
+
+    /// Aces are 1, and everything else is its face value (with T=10, J=11, etc.)
+    fn low_value(r: Rank) -> u8 {
+        match r {
+            Rank::RA => 1,
+            Rank::R2 => 2,
+            Rank::R3 => 3,
+            Rank::R4 => 4,
+            Rank::R5 => 5,
+            Rank::R6 => 6,
+            Rank::R7 => 7,
+            Rank::R8 => 8,
+            Rank::R9 => 9,
+            Rank::RT => 10,
+            Rank::RJ => 11,
+            Rank::RQ => 12,
+            Rank::RK => 13,
+        }
+    }
+
+    /// The ranks' low values, highest first. Lows are compared by these, and the lower wins.
+    fn values(&self) -> [u8; 5] {
+        let mut v = self.cards.map(|c| Self::low_value(c.rank()));
+        v.sort_unstable();
+        v.reverse();
+        v
+    }
+
+    pub fn beats(&self, other: &Self) -> WinState {
+        other.values().cmp(&self.values()).into()
+    }
+}
+
+impl fmt::Display for LowHand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}{}",
+            self.cards[0], self.cards[1], self.cards[2], self.cards[3], self.cards[4],
+        )
+    }
+}
+
+impl Ord for LowHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.beats(other).into()
+    }
+}
+
+impl PartialOrd for LowHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The best qualifying low out of every 5-card combination of the given cards, or None if there
+/// isn't one.
+pub fn best_low_of_cards(cards: &[Card]) -> Option<LowHand> {
+    cards
+        .iter()
+        .copied()
+        .combinations(5)
+        .filter_map(|combo| LowHand::new(&combo))
+        .max()
+}
+
+/// For each hand class better than what the given pocket and board currently make, how many of the
+/// unseen cards would get there if dealt next. Best hand class first, and classes no card reaches
+/// are left out.
+///
+/// Each card is counted once, for the best class it makes, so a card that completes both a flush
+/// and a straight only counts towards the flush. If the board isn't 3 or 4 cards (i.e. there isn't
+/// exactly one more card to come before the river), returns an empty vec.
+pub fn improvement_summary(pocket: [Card; 2], board: &[Card]) -> Vec<(HandClass, usize)> {
+    if board.len() != 3 && board.len() != 4 {
+        return vec![];
+    }
+    let mut community = [None; 5];
+    for (slot, card) in community.iter_mut().zip(board) {
+        *slot = Some(*card);
+    }
+    let mut counts: HashMap<HandClass, usize> = HashMap::new();
+    for (_, class) in outs(pocket, &community, &[]) {
+        *counts.entry(class).or_insert(0) += 1;
+    }
+    let mut ret: Vec<(HandClass, usize)> = counts.into_iter().collect();
+    ret.sort_unstable_by_key(|(class, _)| std::cmp::Reverse(*class));
+    ret
+}
+
+/// The cards that would improve the given pocket's hand class if dealt next, each with the best
+/// hand class it makes, in [`Card::all`] order. The cards in the pocket, on the board, and in `dead`
+/// (e.g. other players' known pockets, see [`crate::state::GameState::dead_cards`]) can't come.
+///
+/// As with [`improvement_summary`], there must be 3 or 4 community cards out, else this returns an
+/// empty vec.
+pub fn outs(
+    pocket: [Card; 2],
+    community: &[Option<Card>; 5],
+    dead: &[Card],
+) -> Vec<(Card, HandClass)> {
+    let mut cards: Vec<Card> = Vec::with_capacity(7);
+    cards.extend_from_slice(&pocket);
+    cards.extend(community.iter().flatten());
+    if cards.len() != 5 && cards.len() != 6 {
+        return vec![];
+    }
+    let current = best_of_cards(&cards)[0].class;
+    let unseen: Vec<Card> = Card::all()
+        .filter(|c| !cards.contains(c) && !dead.contains(c))
+        .collect();
+    let mut ret = vec![];
+    for card in unseen {
+        cards.push(card);
+        let class = best_of_cards(&cards)[0].class;
+        cards.pop();
+        if class > current {
+            ret.push((card, class));
+        }
+    }
+    ret
+}
+
 #[cfg(test)]
 mod test_best_of_cards {
     use super::*;
@@ -698,6 +985,158 @@ mod test_best_of_cards {
     }
 }
 
+#[cfg(test)]
+mod test_ruleset {
+    use super::*;
+    use crate::deck::cards_from_str;
+
+    fn hand(s: &'static str, ruleset: Ruleset) -> Hand {
+        Hand::new_unchecked_with(&cards_from_str(s), ruleset)
+    }
+
+    #[test]
+    fn flush_beats_boat_only_in_short_deck() {
+        let flush = hand("Ah9h8h7hJh", Ruleset::Standard);
+        let boat = hand("6c6d6sTcTd", Ruleset::Standard);
+        assert_eq!(flush.beats(&boat), WinState::Lose);
+        assert_eq!(boat.beats(&flush), WinState::Win);
+        let flush = hand("Ah9h8h7hJh", Ruleset::ShortDeck);
+        let boat = hand("6c6d6sTcTd", Ruleset::ShortDeck);
+        assert_eq!(flush.beats(&boat), WinState::Win);
+        assert_eq!(boat.beats(&flush), WinState::Lose);
+        assert_eq!(flush.class(), HandClass::Flush);
+        assert_eq!(boat.class(), HandClass::FullHouse);
+    }
+
+    #[test]
+    fn short_deck_wheel() {
+        assert_eq!(
+            hand("Ac6d7h8s9c", Ruleset::Standard).class(),
+            HandClass::HighCard
+        );
+        let wheel = hand("Ac6d7h8s9c", Ruleset::ShortDeck);
+        assert_eq!(wheel.class(), HandClass::Straight);
+        assert_eq!(wheel.describe(), "9 high straight");
+        // The wheel is the lowest straight
+        let six_high = hand("6c7d8h9sTc", Ruleset::ShortDeck);
+        assert_eq!(wheel.beats(&six_high), WinState::Lose);
+        let best = best_of_cards_with(&cards_from_str("Ac6d7h8s9cKdKh"), Ruleset::ShortDeck);
+        assert_eq!(best[0].class(), HandClass::Straight);
+    }
+}
+
+#[cfg(test)]
+mod test_low_hand {
+    use super::*;
+    use crate::deck::cards_from_str;
+
+    fn low(s: &'static str) -> Option<LowHand> {
+        LowHand::new(&cards_from_str(s))
+    }
+
+    #[test]
+    fn qualifying() {
+        assert!(low("Ac2d3h4s5c").is_some());
+        assert!(low("8c7d6h5s4c").is_some());
+        // straight flushes still count
+        assert!(low("Ac2c3c4c5c").is_some());
+        // paired
+        assert!(low("Ac2d3h4s4c").is_none());
+        // a 9
+        assert!(low("9c2d3h4s5c").is_none());
+        assert!(low("Ac2d3h4s").is_none());
+    }
+
+    #[test]
+    fn ordering() {
+        let wheel = low("5c4d3h2sAc").unwrap();
+        let six = low("6c4d3h2sAc").unwrap();
+        let eight = low("8c4d3h2sAc").unwrap();
+        let eight_seven = low("8c7d3h2sAc").unwrap();
+        assert_eq!(wheel.beats(&six), WinState::Win);
+        assert_eq!(six.beats(&eight), WinState::Win);
+        assert_eq!(eight_seven.beats(&eight), WinState::Lose);
+        assert_eq!(wheel.beats(&low("5d4c3s2hAd").unwrap()), WinState::Tie);
+    }
+
+    #[test]
+    fn best_of_seven() {
+        let best = best_low_of_cards(&cards_from_str("KcAd2h7s8c3dQh")).unwrap();
+        assert_eq!(best.values(), [8, 7, 3, 2, 1]);
+        assert!(best_low_of_cards(&cards_from_str("KcAd2h9sTc3dQh")).is_none());
+    }
+}
+
+#[cfg(test)]
+mod test_improvement_summary {
+    use super::*;
+    use crate::deck::cards_from_str;
+
+    fn summary(pocket: &'static str, board: &'static str) -> Vec<(HandClass, usize)> {
+        let pocket = cards_from_str(pocket);
+        improvement_summary([pocket[0], pocket[1]], &cards_from_str(board))
+    }
+
+    /// A pair of nines with a flush draw: the nine hearts make a flush, the other two nines make
+    /// trips, and pairing one of the other three ranks makes two pair.
+    #[test]
+    fn pair_and_flush_draw() {
+        assert_eq!(
+            summary("Ah9h", "Kh4h9c"),
+            vec![
+                (HandClass::Flush, 9),
+                (HandClass::ThreeOfAKind, 2),
+                (HandClass::TwoPair, 9),
+            ]
+        );
+    }
+
+    /// Cards that make a straight flush aren't also counted as flushes or straights.
+    #[test]
+    fn no_double_counting() {
+        let s = summary("9h8h", "7h6h2c");
+        assert_eq!(s[0], (HandClass::StraightFlush, 2));
+        // 11 hearts left, 2 of which make the straight flush
+        assert_eq!(s[1], (HandClass::Flush, 7));
+        // the 5s and Ts that aren't hearts
+        assert_eq!(s[2], (HandClass::Straight, 6));
+        let total: usize = s.iter().map(|(_, n)| n).sum();
+        assert!(total <= 52 - 5);
+    }
+
+    #[test]
+    fn needs_cards_to_come() {
+        assert!(summary("Ah9h", "Kh4h").is_empty());
+        assert!(summary("Ah9h", "Kh4h9c2c3c").is_empty());
+    }
+
+    /// An open-ended straight draw has eight outs to the straight, the fours and nines, less any
+    /// that are dead. Pairing a card is an improvement too, just a smaller one.
+    #[test]
+    fn open_ended_outs() {
+        let pocket = cards_from_str("8h7d");
+        let pocket = [pocket[0], pocket[1]];
+        let mut community = [None; 5];
+        for (slot, c) in community.iter_mut().zip(cards_from_str("6s5cKd")) {
+            *slot = Some(c);
+        }
+        let straights = |dead: &[Card]| -> Vec<Card> {
+            outs(pocket, &community, dead)
+                .into_iter()
+                .filter(|(_, class)| *class == HandClass::Straight)
+                .map(|(card, _)| card)
+                .collect()
+        };
+        let found = straights(&[]);
+        assert_eq!(found.len(), 8);
+        assert!(found
+            .iter()
+            .all(|c| c.rank() == Rank::R4 || c.rank() == Rank::R9));
+        assert_eq!(outs(pocket, &community, &[]).len(), 8 + 5 * 3);
+        assert_eq!(straights(&cards_from_str("9s4h")).len(), 6);
+    }
+}
+
 #[cfg(test)]
 mod test_best_hands {
     use super::*;
@@ -730,6 +1169,38 @@ mod test_best_hands {
         assert_eq!(ret[1][0].1.class, HandClass::Pair);
         assert_eq!(ret[1][0].1.cards[0].rank(), Rank::RK);
     }
+
+    /// Same tiers as best_hands, with the class of each hand attached
+    #[test]
+    fn detailed() {
+        let mut map: HashMap<i32, [Card; 2]> = HashMap::new();
+        map.insert(1, [['A', 'c'].into(), ['A', 'd'].into()]);
+        map.insert(2, [['K', 'h'].into(), ['5', 's'].into()]);
+        map.insert(3, [['Q', 'h'].into(), ['Q', 's'].into()]);
+        map.insert(4, [['J', 'h'].into(), ['J', 's'].into()]);
+        let comm = crate::deck::cards_from_str("5c5d9hJcQc");
+        let comm = [comm[0], comm[1], comm[2], comm[3], comm[4]];
+        let plain = best_hands(&map, comm).unwrap();
+        let detailed = best_hands_detailed(&map, comm).unwrap();
+        assert_eq!(plain.len(), detailed.len());
+        for (p, d) in plain.iter().zip(detailed.iter()) {
+            let mut p_ids: Vec<_> = p.iter().map(|(pid, _)| *pid).collect();
+            let mut d_ids: Vec<_> = d.iter().map(|(pid, _)| *pid).collect();
+            p_ids.sort_unstable();
+            d_ids.sort_unstable();
+            assert_eq!(p_ids, d_ids);
+        }
+        let classes: Vec<_> = detailed.iter().map(|t| (t[0].0, t[0].1.class)).collect();
+        assert_eq!(
+            classes,
+            vec![
+                (3, HandClass::FullHouse),
+                (4, HandClass::FullHouse),
+                (2, HandClass::ThreeOfAKind),
+                (1, HandClass::TwoPair),
+            ]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -847,11 +1318,16 @@ mod test_hand_describe {
 
     #[test]
     fn straight_flush() {
-        is("AsKsQsJsTs", "A high straight flush");
-        is("KsAsTsJsQs", "A high straight flush");
+        is("KsQsJsTs9s", "K high straight flush");
         is("As2s4s3s5s", "5 high straight flush");
         is("8s4s6s5s7s", "8 high straight flush");
     }
+
+    #[test]
+    fn royal_flush() {
+        is("AsKsQsJsTs", "Royal flush");
+        is("KsAsTsJsQs", "Royal flush");
+    }
 }
 
 #[cfg(test)]
@@ -859,28 +1335,10 @@ mod test_hand_class {
     use super::*;
     use crate::deck::{Rank, Suit};
 
-    const ALL_RANKS: [Rank; 13] = [
-        Rank::R2,
-        Rank::R3,
-        Rank::R4,
-        Rank::R5,
-        Rank::R6,
-        Rank::R7,
-        Rank::R8,
-        Rank::R9,
-        Rank::RT,
-        Rank::RJ,
-        Rank::RQ,
-        Rank::RK,
-        Rank::RA,
-    ];
-    const ALL_SUITS: [Suit; 4] = [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade];
-
     // All the straight flushes are correctly identified as such.
     #[test]
     fn straight_flushes() {
         for ranks in [
-            [Rank::RA, Rank::RK, Rank::RQ, Rank::RJ, Rank::RT],
             [Rank::RK, Rank::RQ, Rank::RJ, Rank::RT, Rank::R9],
             [Rank::RQ, Rank::RJ, Rank::RT, Rank::R9, Rank::R8],
             [Rank::RJ, Rank::RT, Rank::R9, Rank::R8, Rank::R7],
@@ -891,7 +1349,7 @@ mod test_hand_class {
             [Rank::R6, Rank::R5, Rank::R4, Rank::R3, Rank::R2],
             [Rank::R5, Rank::R4, Rank::R3, Rank::R2, Rank::RA],
         ] {
-            for suit in ALL_SUITS {
+            for suit in Suit::all() {
                 let cards = [
                     Card::new(ranks[0], suit),
                     Card::new(ranks[1], suit),
@@ -899,15 +1357,30 @@ mod test_hand_class {
                     Card::new(ranks[3], suit),
                     Card::new(ranks[4], suit),
                 ];
-                assert_eq!(HandClass::which(&cards), HandClass::StraightFlush);
+                assert_eq!(
+                    HandClass::which(&cards, Ruleset::Standard),
+                    HandClass::StraightFlush
+                );
             }
         }
     }
 
+    #[test]
+    fn royal_flushes() {
+        for suit in Suit::all() {
+            let cards =
+                [Rank::RA, Rank::RK, Rank::RQ, Rank::RJ, Rank::RT].map(|r| Card::new(r, suit));
+            assert_eq!(
+                HandClass::which(&cards, Ruleset::Standard),
+                HandClass::RoyalFlush
+            );
+        }
+    }
+
     // Test all quads (but not with all kickers)
     #[test]
     fn quads() {
-        for rank in ALL_RANKS {
+        for rank in Rank::all() {
             let extra = Card::new(
                 match rank {
                     Rank::R2 => Rank::R3,
@@ -922,15 +1395,18 @@ mod test_hand_class {
                 Card::new(rank, Suit::Spade),
                 extra,
             ];
-            assert_eq!(HandClass::which(&cards), HandClass::FourOfAKind);
+            assert_eq!(
+                HandClass::which(&cards, Ruleset::Standard),
+                HandClass::FourOfAKind
+            );
         }
     }
 
     // All combinations of 2 ranks in a full house, but not with all combos of suit too
     #[test]
     fn boat() {
-        for rank3 in ALL_RANKS {
-            for rank2 in ALL_RANKS {
+        for rank3 in Rank::all() {
+            for rank2 in Rank::all() {
                 if rank2 == rank3 {
                     continue;
                 }
@@ -941,7 +1417,10 @@ mod test_hand_class {
                     Card::new(rank2, Suit::Club),
                     Card::new(rank2, Suit::Diamond),
                 ];
-                assert_eq!(HandClass::which(&cards), HandClass::FullHouse);
+                assert_eq!(
+                    HandClass::which(&cards, Ruleset::Standard),
+                    HandClass::FullHouse
+                );
             }
         }
     }
@@ -954,7 +1433,7 @@ mod test_hand_class {
             [Rank::RT, Rank::R8, Rank::R6, Rank::R4, Rank::R2],
             [Rank::R2, Rank::R4, Rank::R5, Rank::R6, Rank::R7],
         ] {
-            for suit in ALL_SUITS {
+            for suit in Suit::all() {
                 let cards = [
                     Card::new(ranks[0], suit),
                     Card::new(ranks[1], suit),
@@ -962,7 +1441,10 @@ mod test_hand_class {
                     Card::new(ranks[3], suit),
                     Card::new(ranks[4], suit),
                 ];
-                assert_eq!(HandClass::which(&cards), HandClass::Flush);
+                assert_eq!(
+                    HandClass::which(&cards, Ruleset::Standard),
+                    HandClass::Flush
+                );
             }
         }
     }
@@ -988,13 +1470,16 @@ mod test_hand_class {
                 Card::new(ranks[3], Suit::Club),
                 Card::new(ranks[4], Suit::Spade),
             ];
-            assert_eq!(HandClass::which(&cards), HandClass::Straight);
+            assert_eq!(
+                HandClass::which(&cards, Ruleset::Standard),
+                HandClass::Straight
+            );
         }
     }
 
     #[test]
     fn set() {
-        for rank in ALL_RANKS {
+        for rank in Rank::all() {
             let r2 = match rank {
                 Rank::R2 => Rank::R3,
                 _ => Rank::R2,
@@ -1010,14 +1495,17 @@ mod test_hand_class {
                 Card::new(r2, Suit::Club),
                 Card::new(r3, Suit::Club),
             ];
-            assert_eq!(HandClass::which(&cards), HandClass::ThreeOfAKind);
+            assert_eq!(
+                HandClass::which(&cards, Ruleset::Standard),
+                HandClass::ThreeOfAKind
+            );
         }
     }
 
     #[test]
     fn two_pair() {
-        for r1 in ALL_RANKS {
-            for r2 in ALL_RANKS {
+        for r1 in Rank::all() {
+            for r2 in Rank::all() {
                 if r1 == r2 {
                     continue;
                 }
@@ -1035,14 +1523,17 @@ mod test_hand_class {
                     Card::new(r2, Suit::Diamond),
                     Card::new(r3, Suit::Spade),
                 ];
-                assert_eq!(HandClass::which(&cards), HandClass::TwoPair);
+                assert_eq!(
+                    HandClass::which(&cards, Ruleset::Standard),
+                    HandClass::TwoPair
+                );
             }
         }
     }
 
     #[test]
     fn pair() {
-        for rank in ALL_RANKS {
+        for rank in Rank::all() {
             let r1 = match rank {
                 Rank::R2 => Rank::R3,
                 _ => Rank::R2,
@@ -1062,7 +1553,7 @@ mod test_hand_class {
                 Card::new(rank, Suit::Club),
                 Card::new(rank, Suit::Diamond),
             ];
-            assert_eq!(HandClass::which(&cards), HandClass::Pair);
+            assert_eq!(HandClass::which(&cards, Ruleset::Standard), HandClass::Pair);
         }
     }
 
@@ -1080,7 +1571,10 @@ mod test_hand_class {
                 Card::new(ranks[3], Suit::Club),
                 Card::new(ranks[4], Suit::Diamond),
             ];
-            assert_eq!(HandClass::which(&cards), HandClass::HighCard);
+            assert_eq!(
+                HandClass::which(&cards, Ruleset::Standard),
+                HandClass::HighCard
+            );
         }
     }
 }
@@ -1112,33 +1606,34 @@ mod test_hand_class_beats {
 
     #[test]
     fn straight_flush_tie() {
-        for (s1, s2) in [
-            ("AcKcQcJcTc", "AdKdQdJdTd"),
-            ("KcQcJcTc9c", "KdQdJdTd9d"),
-            ("5c4c3c2cAc", "5d4d3d2dAd"),
-        ] {
+        for (s1, s2) in [("KcQcJcTc9c", "KdQdJdTd9d"), ("5c4c3c2cAc", "5d4d3d2dAd")] {
             tie(s1, s2, HandClass::StraightFlush);
         }
     }
 
     #[test]
     fn straight_flush() {
-        for (s1, s2) in [
-            ("AcKcQcJcTc", "KdQdJdTd9d"),
-            ("6c5c4c3c2c", "5d4d3d2dAd"),
-            ("AcKcQcJcTc", "5d4d3d2dAd"),
-        ] {
+        for (s1, s2) in [("6c5c4c3c2c", "5d4d3d2dAd"), ("KcQcJcTc9c", "5d4d3d2dAd")] {
             win_lose(s1, s2, HandClass::StraightFlush);
         }
     }
 
+    #[test]
+    fn royal_flush() {
+        tie("AcKcQcJcTc", "AdKdQdJdTd", HandClass::RoyalFlush);
+        let royal = Hand::new_unchecked(&cards_from_str("AcKcQcJcTc"));
+        for s in ["KdQdJdTd9d", "5d4d3d2dAd", "AdAhAsAcKd"] {
+            let other = Hand::new_unchecked(&cards_from_str(s));
+            assert_eq!(royal.beats(&other), WinState::Win);
+            assert_eq!(other.beats(&royal), WinState::Lose);
+        }
+    }
+
     #[test]
     fn quads_tie() {
         // this should be impossible in typical single deck poker, but check for it anyway since
         // the logic doesn't care
-        for (s1, s2) in [("2c2d2h2s3c", "2c2d2h2s3d")] {
-            tie(s1, s2, HandClass::FourOfAKind);
-        }
+        tie("2c2d2h2s3c", "2c2d2h2s3d", HandClass::FourOfAKind);
     }
 
     #[test]
@@ -1150,9 +1645,7 @@ mod test_hand_class_beats {
 
     #[test]
     fn full_house_tie() {
-        for (s1, s2) in [("AcAdAhKcKd", "AdAhAsKhKs")] {
-            tie(s1, s2, HandClass::FullHouse);
-        }
+        tie("AcAdAhKcKd", "AdAhAsKhKs", HandClass::FullHouse);
     }
 
     #[test]
@@ -1164,9 +1657,7 @@ mod test_hand_class_beats {
 
     #[test]
     fn flush_tie() {
-        for (s1, s2) in [("AsKsQsJs2s", "AdKdQdJd2d")] {
-            tie(s1, s2, HandClass::Flush);
-        }
+        tie("AsKsQsJs2s", "AdKdQdJd2d", HandClass::Flush);
     }
 
     #[test]
@@ -1178,9 +1669,7 @@ mod test_hand_class_beats {
 
     #[test]
     fn straight_tie() {
-        for (s1, s2) in [("AsKsQsJsTd", "AcKcQcJcTs")] {
-            tie(s1, s2, HandClass::Straight);
-        }
+        tie("AsKsQsJsTd", "AcKcQcJcTs", HandClass::Straight);
     }
 
     #[test]
@@ -1214,9 +1703,7 @@ mod test_hand_class_beats {
 
     #[test]
     fn two_pair_tie() {
-        for (s1, s2) in [("AsAsKsKsTd", "AcAcKcKcTs")] {
-            tie(s1, s2, HandClass::TwoPair);
-        }
+        tie("AsAsKsKsTd", "AcAcKcKcTs", HandClass::TwoPair);
     }
 
     #[test]
@@ -1246,9 +1733,7 @@ mod test_hand_class_beats {
 
     #[test]
     fn high_card_tie() {
-        for (s1, s2) in [("KcQdJhTs5c", "KdQhJsTc5d")] {
-            tie(s1, s2, HandClass::HighCard);
-        }
+        tie("KcQdJhTs5c", "KdQhJsTc5d", HandClass::HighCard);
     }
 
     #[test]
@@ -0,0 +1,130 @@
+//! Reading and showing [`Currency`] as dollars and cents, e.g. "12.50" for 1250, for when an
+//! amount is typed in or read by a person rather than by code.
+use crate::Currency;
+
+#[derive(Debug, PartialEq, Eq, derive_more::Display, derive_more::Error)]
+pub enum ParseCurrencyError {
+    #[display(fmt = "No amount given")]
+    Empty,
+    #[display(fmt = "Amount isn't a number of dollars and cents")]
+    NotANumber,
+    #[display(fmt = "Amount has more than two decimal places")]
+    TooManyDecimals,
+    #[display(fmt = "Amount is too large")]
+    TooLarge,
+}
+
+/// Read an amount of dollars and cents, e.g. "12.50" or "-3", as a number of cents. There may be a
+/// sign in front, and at most two digits after the decimal point. Surrounding whitespace is
+/// ignored.
+pub fn parse(s: &str) -> Result<Currency, ParseCurrencyError> {
+    let s = s.trim();
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    if unsigned.is_empty() {
+        return Err(ParseCurrencyError::Empty);
+    }
+    let (dollars, cents) = match unsigned.split_once('.') {
+        Some((d, c)) if !c.is_empty() => (d, c),
+        Some(_) => return Err(ParseCurrencyError::NotANumber),
+        None => (unsigned, ""),
+    };
+    if dollars.is_empty()
+        || !dollars
+            .bytes()
+            .chain(cents.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(ParseCurrencyError::NotANumber);
+    }
+    if cents.len() > 2 {
+        return Err(ParseCurrencyError::TooManyDecimals);
+    }
+    // Parse as a negative number either way, since there's one more of those than positive ones
+    let mut value: Currency = 0;
+    for b in dollars.bytes().chain(format!("{:0<2}", cents).bytes()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_sub(Currency::from(b - b'0')))
+            .ok_or(ParseCurrencyError::TooLarge)?;
+    }
+    if negative {
+        Ok(value)
+    } else {
+        value.checked_neg().ok_or(ParseCurrencyError::TooLarge)
+    }
+}
+
+/// The given amount of cents as dollars and cents, e.g. "12.50". A negative amount gets one sign
+/// in front, as in "-0.05", instead of one on the dollars and another on the cents.
+pub fn format(c: Currency) -> String {
+    let sign = if c < 0 { "-" } else { "" };
+    let abs = c.unsigned_abs();
+    format!("{}{}.{:02}", sign, abs / 100, abs % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positive() {
+        assert_eq!(format(0), "0.00");
+        assert_eq!(format(5), "0.05");
+        assert_eq!(format(1250), "12.50");
+    }
+
+    #[test]
+    fn negative() {
+        assert_eq!(format(-50), "-0.50");
+        assert_eq!(format(-1250), "-12.50");
+        assert_eq!(format(-5), "-0.05");
+        assert_eq!(format(Currency::MIN), "-92233720368547758.08");
+    }
+
+    /// What's parsed shows the same way again, with the cents filled out
+    #[test]
+    fn round_trip() {
+        assert_eq!(parse("12.5").unwrap(), 1250);
+        assert_eq!(format(parse("12.5").unwrap()), "12.50");
+        for s in ["0.00", "0.05", "12.50", "-0.05", "-12.50", "1000000.00"] {
+            assert_eq!(format(parse(s).unwrap()), s);
+        }
+        for c in [Currency::MIN, Currency::MAX, -1, 0, 1] {
+            assert_eq!(parse(&format(c)).unwrap(), c);
+        }
+    }
+
+    #[test]
+    fn parse_forms() {
+        assert_eq!(parse("12").unwrap(), 1200);
+        assert_eq!(parse("+12").unwrap(), 1200);
+        assert_eq!(parse(" 12.01\n").unwrap(), 1201);
+        assert_eq!(parse("-3").unwrap(), -300);
+        assert_eq!(parse("007.1").unwrap(), 710);
+    }
+
+    #[test]
+    fn parse_errors() {
+        use ParseCurrencyError::*;
+        for (s, e) in [
+            ("", Empty),
+            ("-", Empty),
+            ("  ", Empty),
+            ("12.505", TooManyDecimals),
+            ("abc", NotANumber),
+            ("1,000", NotANumber),
+            ("12.", NotANumber),
+            (".5", NotANumber),
+            ("1.2.3", NotANumber),
+            ("--1", NotANumber),
+            ("1e5", NotANumber),
+            ("92233720368547758.08", TooLarge),
+            ("999999999999999999999", TooLarge),
+        ] {
+            assert_eq!(parse(s), Err(e), "{s:?}");
+        }
+    }
+}
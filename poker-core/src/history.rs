@@ -0,0 +1,368 @@
+//! Hand histories in the text format PokerStars uses, which most hand tracking software can
+//! import.
+use crate::bet::{BetAction, BettingLimit};
+use crate::deck::Card;
+use crate::hand::HandClass;
+use crate::log::LogItem;
+use crate::pot;
+use crate::state::{AnteStyle, GameConfig, Street};
+use crate::{Currency, PlayerId, SeatIdx, SeqNum};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// What we learn about a seated player over the course of a hand
+struct Seat {
+    seat: SeatIdx,
+    id: PlayerId,
+    stack: Currency,
+    dealt_in: bool,
+    folded: Option<Street>,
+    shown: Option<Vec<Card>>,
+    class: Option<HandClass>,
+    won: Currency,
+}
+
+fn name(player_id: PlayerId) -> String {
+    format!("Player {player_id}")
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards.iter().join(" ")
+}
+
+/// The most recent hand in the given log items, as a PokerStars hand history.
+///
+/// The log items can be everything the table logged or just what one player was allowed to see
+/// (see [`GameState::filtered_changes_since`](crate::state::GameState::filtered_changes_since)),
+/// in which case only the pockets that player saw are listed as dealt. The hand starts at the
+/// last [`LogItem::NewBaseState`], and should be over for the summary to make sense. Players are
+/// named "Player {id}". Core doesn't know the time, so the caller gives the time the hand started,
+/// already formatted the way PokerStars does, e.g. "2022/11/11 12:00:00 ET".
+pub fn pokerstars_hand_history(
+    config: &GameConfig,
+    logs: &[(SeqNum, LogItem)],
+    hand_id: u64,
+    table_name: &str,
+    started_at: &str,
+) -> String {
+    let start = logs
+        .iter()
+        .rposition(|(_, item)| matches!(item, LogItem::NewBaseState(_)))
+        .unwrap_or(0);
+    let mut seats: Vec<Seat> = vec![];
+    let mut tokens = (0, 0, 0);
+    let mut street = Street::PreFlop;
+    // Whether we're still in the antes and blinds
+    let mut posting = true;
+    // Who just straddled, so that their pot bet reads as the straddle it is
+    let mut straddler = None;
+    let mut antes_over = matches!(config.ante, AnteStyle::None);
+    // Everyone's total bet this street, and what it was at the end of the last street with bets
+    let mut committed: HashMap<PlayerId, Currency> = HashMap::new();
+    let mut last_committed: HashMap<PlayerId, Currency> = HashMap::new();
+    let mut current_bet = 0;
+    let mut board: Vec<Card> = vec![];
+    let mut rake = 0;
+    // A bet that went back to its bettor when everyone folded to it, which isn't in the payouts
+    let mut returned = None;
+    let mut showdown = false;
+    let mut body: Vec<String> = vec![];
+    for (_, item) in &logs[start..] {
+        match item {
+            LogItem::NewBaseState(bs) => {
+                seats = bs
+                    .seats
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(seat, p)| {
+                        p.map(|p| Seat {
+                            seat,
+                            id: p.id,
+                            stack: p.stack,
+                            dealt_in: false,
+                            folded: None,
+                            shown: None,
+                            class: None,
+                            won: 0,
+                        })
+                    })
+                    .collect();
+            }
+            LogItem::TokensSet(btn, sb, bb) => tokens = (*btn, *sb, *bb),
+            LogItem::Straddle(player_id, _) => straddler = Some(*player_id),
+            LogItem::PocketDealt(_, _) | LogItem::NextToAct(_) => {
+                if posting {
+                    posting = false;
+                    body.push("*** HOLE CARDS ***".to_owned());
+                }
+                if let LogItem::PocketDealt(player_id, pocket) = item {
+                    if let Some(seat) = seats.iter_mut().find(|s| s.id == *player_id) {
+                        seat.dealt_in = true;
+                    }
+                    if let Some(pocket) = pocket {
+                        body.push(format!("Dealt to {} [{}]", name(*player_id), cards(pocket)));
+                    }
+                }
+            }
+            LogItem::Pot(pot::LogItem::Bet(player_id, action)) => {
+                let who = name(*player_id);
+                let prev = committed.get(player_id).copied().unwrap_or(0);
+                let total = action.amount().unwrap_or(prev);
+                if posting {
+                    if total == 0 {
+                        // Players that don't owe an ante still go in the pot for nothing
+                    } else if !antes_over {
+                        body.push(format!("{who}: posts the ante {total}"));
+                    } else {
+                        // The SB might be dead, so go by seat rather than by who posted first
+                        let seat = seats.iter().find(|s| s.id == *player_id).map(|s| s.seat);
+                        let blind = if seat == Some(tokens.1) {
+                            "small"
+                        } else {
+                            "big"
+                        };
+                        body.push(format!("{who}: posts {blind} blind {total}"));
+                    }
+                } else if straddler.take() == Some(*player_id) {
+                    body.push(format!("{who}: straddles {total}"));
+                } else {
+                    let all_in = if matches!(action, BetAction::AllIn(_)) {
+                        " and is all-in"
+                    } else {
+                        ""
+                    };
+                    let line = match action {
+                        BetAction::Fold => {
+                            if let Some(seat) = seats.iter_mut().find(|s| s.id == *player_id) {
+                                seat.folded = Some(street);
+                            }
+                            "folds".to_owned()
+                        }
+                        // The big blind checking preflop is logged as a bet of what they're in for
+                        BetAction::Check | BetAction::Bet(_) if total == prev => {
+                            "checks".to_owned()
+                        }
+                        _ if total <= current_bet => format!("calls {}{all_in}", total - prev),
+                        _ if current_bet == 0 => format!("bets {total}{all_in}"),
+                        _ => format!("raises {} to {total}{all_in}", total - current_bet),
+                    };
+                    body.push(format!("{who}: {line}"));
+                }
+                if !matches!(action, BetAction::Fold) {
+                    committed.insert(*player_id, total);
+                    current_bet = current_bet.max(total);
+                }
+            }
+            LogItem::Pot(pot::LogItem::RoundEnd(_)) => {
+                if posting {
+                    antes_over = true;
+                }
+                if !committed.is_empty() {
+                    last_committed = std::mem::take(&mut committed);
+                }
+                current_bet = 0;
+            }
+            LogItem::Pot(pot::LogItem::Payouts(None, payouts)) => {
+                for seat in seats.iter_mut() {
+                    seat.won = payouts.get(&seat.id).copied().unwrap_or(0);
+                }
+            }
+            LogItem::Pot(pot::LogItem::Rake(amount)) => rake = *amount,
+            LogItem::Pot(pot::LogItem::UncalledBetReturned(player_id, amount)) => {
+                returned = Some((*player_id, *amount));
+            }
+            LogItem::Flop(c1, c2, c3) => {
+                street = Street::Flop;
+                board = vec![*c1, *c2, *c3];
+                body.push(format!("*** FLOP *** [{}]", cards(&board)));
+            }
+            LogItem::Turn(c) => {
+                street = Street::Turn;
+                body.push(format!("*** TURN *** [{}] [{c}]", cards(&board)));
+                board.push(*c);
+            }
+            LogItem::River(c) => {
+                street = Street::River;
+                body.push(format!("*** RIVER *** [{}] [{c}]", cards(&board)));
+                board.push(*c);
+            }
+            LogItem::HandReveal(_, _) | LogItem::Muck(_) => {
+                if !showdown {
+                    showdown = true;
+                    body.push("*** SHOW DOWN ***".to_owned());
+                }
+                match item {
+                    LogItem::HandReveal(player_id, pocket) => {
+                        let pocket: Vec<Card> = pocket.iter().flatten().copied().collect();
+                        body.push(format!("{}: shows [{}]", name(*player_id), cards(&pocket)));
+                        if let Some(seat) = seats.iter_mut().find(|s| s.id == *player_id) {
+                            seat.shown = Some(pocket);
+                        }
+                    }
+                    LogItem::Muck(player_id) => {
+                        body.push(format!("{}: mucks hand", name(*player_id)));
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            LogItem::HandResult(player_id, class, _, _) => {
+                if let Some(seat) = seats.iter_mut().find(|s| s.id == *player_id) {
+                    seat.class = Some(*class);
+                }
+            }
+            _ => {}
+        }
+    }
+    // Whatever the biggest bet on the last street with bets had that nobody matched goes back.
+    // When everyone folded to it, it's already been taken out of the pot. Otherwise it's in the
+    // winnings, as a pot nobody else could win.
+    let final_bets = if committed.is_empty() {
+        &last_committed
+    } else {
+        &committed
+    };
+    let mut uncalled = None;
+    if let Some((player_id, amount)) = returned {
+        body.push(format!(
+            "Uncalled bet ({amount}) returned to {}",
+            name(player_id)
+        ));
+    } else if let Some((&top_id, &top)) = final_bets.iter().max_by_key(|(_, amount)| **amount) {
+        let second = final_bets
+            .iter()
+            .filter(|(id, _)| **id != top_id)
+            .map(|(_, amount)| *amount)
+            .max()
+            .unwrap_or(0);
+        if top > second {
+            uncalled = Some((top_id, top - second));
+            body.push(format!(
+                "Uncalled bet ({}) returned to {}",
+                top - second,
+                name(top_id)
+            ));
+        }
+    }
+    let collected = |seat: &Seat| match uncalled {
+        Some((id, amount)) if id == seat.id => seat.won - amount,
+        _ => seat.won,
+    };
+    for seat in seats.iter().filter(|s| collected(s) > 0) {
+        body.push(format!(
+            "{} collected {} from pot",
+            name(seat.id),
+            collected(seat)
+        ));
+        if !showdown {
+            body.push(format!("{}: doesn't show hand", name(seat.id)));
+        }
+    }
+
+    let game = match config.betting_limit {
+        BettingLimit::NoLimit => "No Limit",
+        BettingLimit::FixedLimit { .. } => "Limit",
+    };
+    let mut lines = vec![
+        format!(
+            "PokerStars Hand #{hand_id}:  Hold'em {game} ({}/{}) - {started_at}",
+            config.small_blind, config.big_blind
+        ),
+        format!(
+            "Table '{table_name}' {}-max Seat #{} is the button",
+            config.max_players,
+            tokens.0 + 1
+        ),
+    ];
+    for seat in &seats {
+        let sitting_out = if seat.dealt_in { "" } else { " is sitting out" };
+        lines.push(format!(
+            "Seat {}: {} ({} in chips){sitting_out}",
+            seat.seat + 1,
+            name(seat.id),
+            seat.stack
+        ));
+    }
+    lines.append(&mut body);
+    lines.push("*** SUMMARY ***".to_owned());
+    let total: Currency = seats.iter().map(collected).sum::<Currency>() + rake;
+    lines.push(format!("Total pot {total} | Rake {rake}"));
+    if !board.is_empty() {
+        lines.push(format!("Board [{}]", cards(&board)));
+    }
+    for seat in seats.iter().filter(|s| s.dealt_in) {
+        let mut roles = String::new();
+        if seat.seat == tokens.0 {
+            roles.push_str(" (button)");
+        }
+        if seat.seat == tokens.1 {
+            roles.push_str(" (small blind)");
+        } else if seat.seat == tokens.2 {
+            roles.push_str(" (big blind)");
+        }
+        let with = seat
+            .class
+            .map_or_else(String::new, |class| format!(" with a {class}"));
+        let result = match (seat.folded, &seat.shown, collected(seat)) {
+            (Some(Street::PreFlop), _, _) => "folded before Flop".to_owned(),
+            (Some(street), _, _) => format!("folded on the {street}"),
+            (None, Some(pocket), 0) => format!("showed [{}] and lost", cards(pocket)),
+            (None, Some(pocket), won) => {
+                format!("showed [{}] and won ({won}){with}", cards(pocket))
+            }
+            (None, None, 0) => "mucked".to_owned(),
+            (None, None, won) => format!("collected ({won})"),
+        };
+        lines.push(format!(
+            "Seat {}: {}{roles} {result}",
+            seat.seat + 1,
+            name(seat.id)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{GameState, State};
+
+    /// Everyone folds to the big blind, who gets the small blind back as an uncalled bet
+    #[test]
+    fn fold_to_blinds() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        let pocket = gs.players.player_by_id(1).unwrap().pocket.unwrap();
+        let logs: Vec<_> = gs.filtered_changes_since(0, 1).collect();
+        let hh = pokerstars_hand_history(gs.config(), &logs, 7, "Test", "2022/11/11 12:00:00 ET");
+        let expected = format!(
+            "PokerStars Hand #7:  Hold'em No Limit (5/10) - 2022/11/11 12:00:00 ET
+Table 'Test' 12-max Seat #2 is the button
+Seat 1: Player 1 (1000 in chips)
+Seat 2: Player 2 (1000 in chips)
+Seat 3: Player 3 (1000 in chips)
+Player 3: posts small blind 5
+Player 1: posts big blind 10
+*** HOLE CARDS ***
+Dealt to Player 1 [{} {}]
+Player 2: folds
+Player 3: folds
+Uncalled bet (5) returned to Player 1
+Player 1 collected 10 from pot
+Player 1: doesn't show hand
+*** SUMMARY ***
+Total pot 10 | Rake 0
+Seat 1: Player 1 (big blind) collected (10)
+Seat 2: Player 2 (button) folded before Flop
+Seat 3: Player 3 (small blind) folded before Flop",
+            pocket[0], pocket[1]
+        );
+        assert_eq!(hh, expected);
+    }
+}
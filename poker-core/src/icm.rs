@@ -0,0 +1,92 @@
+//! The Independent Chip Model, for turning tournament chip counts into shares of the prize pool,
+//! e.g. to work out a fair deal at a final table.
+use crate::Currency;
+use std::collections::HashMap;
+
+/// Each player's expected winnings given their chip counts and the prizes for first place, second,
+/// and so on.
+///
+/// A player's chance of finishing first is their share of all the chips. Given who finished first,
+/// the chance of finishing second is the share of the chips that are left, and so on down the
+/// places that pay. Players with no chips are already out and get nothing; a lone player with
+/// chips gets first prize.
+///
+/// This is meant for final tables. Who places next only depends on who's already placed, not in
+/// what order, so the work grows with the number of ways to pick the players that fill the paid
+/// places: instant for a table of a dozen or so, but 50 players with 10 places paid is billions
+/// of ways and won't finish. There's no limit on the number of players as such: a field of
+/// hundreds is fine as long as only a few places are paid.
+pub fn icm(stacks: &[Currency], payouts: &[Currency]) -> Vec<f64> {
+    let mut equity = vec![0.0; stacks.len()];
+    let players: Vec<usize> = (0..stacks.len()).filter(|i| stacks[*i] > 0).collect();
+    // The chance that exactly these players, as a bitset of indexes into players, took the places
+    // paid out so far
+    let words = (players.len() + 63) / 64;
+    let mut placed: HashMap<Vec<u64>, f64> = HashMap::from([(vec![0; words], 1.0)]);
+    for prize in payouts.iter().take(players.len()) {
+        let mut next: HashMap<Vec<u64>, f64> = HashMap::new();
+        for (mask, prob) in placed {
+            let left: Vec<usize> = (0..players.len())
+                .filter(|bit| mask[bit / 64] & (1 << (bit % 64)) == 0)
+                .collect();
+            let total: Currency = left.iter().map(|bit| stacks[players[*bit]]).sum();
+            for bit in left {
+                let p = prob * stacks[players[bit]] as f64 / total as f64;
+                equity[players[bit]] += p * *prize as f64;
+                let mut mask = mask.clone();
+                mask[bit / 64] |= 1 << (bit % 64);
+                *next.entry(mask).or_default() += p;
+            }
+        }
+        placed = next;
+    }
+    equity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    /// The textbook example: 5000/3000/2000 chips playing for 50/30/20
+    #[test]
+    fn three_players() {
+        let eq = icm(&[5000, 3000, 2000], &[50, 30, 20]);
+        assert_close(&eq, &[38.392857142857, 32.75, 28.857142857143]);
+        assert!((eq.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        // fewer places paid than players left
+        let eq = icm(&[5000, 3000, 2000], &[70, 30]);
+        assert_close(&eq, &[45.178571428571, 32.25, 22.571428571429]);
+    }
+
+    /// A full table paying everyone, which would be 12! orderings one at a time
+    #[test]
+    fn full_table() {
+        let stacks: Vec<Currency> = (1..=12).map(|i| i * 1000).collect();
+        let payouts: Vec<Currency> = (1..=12).rev().collect();
+        let eq = icm(&stacks, &payouts);
+        assert!((eq.iter().sum::<f64>() - payouts.iter().sum::<Currency>() as f64).abs() < 1e-6);
+        assert!(eq.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_close(&icm(&[0, 1234, 0], &[70, 30]), &[0.0, 70.0, 0.0]);
+        assert_close(&icm(&[0, 100, 100], &[70, 30, 10]), &[0.0, 50.0, 50.0]);
+        assert_close(&icm(&[0, 0], &[70, 30]), &[0.0, 0.0]);
+        assert_close(&icm(&[10, 10], &[]), &[0.0, 0.0]);
+    }
+
+    /// More players with chips than fit in one machine word, with only the top two paid
+    #[test]
+    fn big_field() {
+        let eq = icm(&[100; 200], &[70, 30]);
+        assert_close(&eq, &[0.5; 200]);
+    }
+}
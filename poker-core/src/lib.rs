@@ -1,33 +1,98 @@
 pub mod bet;
 pub mod cards;
+pub mod currency;
+pub mod history;
+pub mod icm;
 pub mod log;
 pub mod player;
 pub mod pot;
 pub mod state;
 mod util;
 
-pub use cards::{deck, hand};
+pub use cards::{deck, equity, hand};
 
 pub const MAX_PLAYERS: usize = 12;
 pub type PlayerId = i32;
-pub type Currency = i32;
+pub type Currency = i64;
 pub type SeqNum = usize;
 pub type SeatIdx = usize;
+/// Milliseconds, either as a length of time or as a point in time measured from whatever fixed
+/// point the caller likes. Core never reads a clock itself; the time is always passed in.
+pub type Millis = u64;
 
 #[derive(Debug, derive_more::Display, derive_more::Error)]
 pub enum GameError {
+    #[display(fmt = "Player is already seated at this table")]
     PlayerAlreadySeated,
+    #[display(fmt = "The table is full")]
     TableFull,
+    #[display(fmt = "Not enough players to start a hand")]
     NotEnoughPlayers,
+    #[display(fmt = "Not enough players with chips to start a hand")]
+    NotEnoughFundedPlayers,
+    #[display(fmt = "Players still have to act on this street")]
     StreetNotComplete,
+    #[display(fmt = "Player isn't seated at this table")]
     PlayerNotFound,
+    #[display(fmt = "Player can't bet, having folded, gone all in, or not been dealt in")]
     PlayerIsNotBetting,
+    #[display(fmt = "No one is expected to act right now")]
     NoBetExpected,
+    #[display(fmt = "It isn't that player's turn")]
     OutOfTurn,
+    #[display(fmt = "Player doesn't have enough chips for that")]
     PlayerStackTooShort,
+    #[display(fmt = "That bet isn't allowed")]
     InvalidBet,
+    #[display(fmt = "The game has already started")]
+    GameAlreadyStarted,
+    #[display(fmt = "Can't do that while a hand is in progress")]
+    HandInProgress,
+    #[display(
+        fmt = "Every player must get a share, and the shares must add up to the chips in play"
+    )]
+    InvalidDeal,
+    #[display(fmt = "The min chip must be positive")]
+    InvalidMinChip,
+    #[display(fmt = "Not enough cards left in the deck to deal a hand")]
+    DeckExhausted,
+    #[display(fmt = "Invalid table settings")]
+    InvalidConfig,
+    #[display(fmt = "Stack is outside the table's buy-in limits")]
+    InvalidBuyIn,
+    #[display(fmt = "That seat is taken")]
+    SeatTaken,
+    #[display(fmt = "No such seat at this table")]
+    InvalidSeat,
+    #[display(fmt = "Player can't leave while still in the hand")]
+    BettingPlayerCantStand,
+    #[display(fmt = "The betting round isn't over")]
+    RoundNotOver,
+    #[display(fmt = "Unknown player")]
+    UnknownPlayer,
+    #[display(fmt = "Player is out of the tournament")]
+    PlayerEliminated,
+    #[display(fmt = "Player can't re-raise their own raise")]
+    CantRaiseSelf,
+    #[display(fmt = "Players still in the hand aren't in for the same amount")]
+    UnevenBets,
+    #[display(fmt = "Unable to read game state: {}", _0)]
+    InvalidState(#[error(not(source))] String),
+    #[display(
+        fmt = "Game state is from schema version {}, but this version only understands up to {}",
+        _0,
+        "state::SCHEMA_VERSION"
+    )]
+    UnsupportedSchemaVersion(#[error(not(source))] u16),
+    #[display(fmt = "Chat message is empty or too long")]
+    InvalidChat,
+    #[display(fmt = "Can't straddle: not left of the big blind, or action has already started")]
+    InvalidStraddle,
+    #[display(fmt = "The game is paused")]
+    GamePaused,
     DeckError(deck::DeckError),
     HandError(hand::HandError),
+    BetError(bet::BetError),
 }
 
 impl From<deck::DeckError> for GameError {
@@ -41,3 +106,114 @@ impl From<hand::HandError> for GameError {
         Self::HandError(e)
     }
 }
+
+impl From<bet::BetError> for GameError {
+    fn from(e: bet::BetError) -> Self {
+        Self::BetError(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every error has something to say for itself, in words rather than its variant name. The
+    /// match makes sure this list keeps up with the enum.
+    #[test]
+    fn errors_display() {
+        let errors = [
+            GameError::PlayerAlreadySeated,
+            GameError::TableFull,
+            GameError::NotEnoughPlayers,
+            GameError::NotEnoughFundedPlayers,
+            GameError::StreetNotComplete,
+            GameError::PlayerNotFound,
+            GameError::PlayerIsNotBetting,
+            GameError::NoBetExpected,
+            GameError::OutOfTurn,
+            GameError::PlayerStackTooShort,
+            GameError::InvalidBet,
+            GameError::GameAlreadyStarted,
+            GameError::HandInProgress,
+            GameError::InvalidDeal,
+            GameError::InvalidMinChip,
+            GameError::DeckExhausted,
+            GameError::InvalidConfig,
+            GameError::InvalidBuyIn,
+            GameError::SeatTaken,
+            GameError::InvalidSeat,
+            GameError::BettingPlayerCantStand,
+            GameError::RoundNotOver,
+            GameError::UnknownPlayer,
+            GameError::PlayerEliminated,
+            GameError::CantRaiseSelf,
+            GameError::UnevenBets,
+            GameError::InvalidState("EOF".to_owned()),
+            GameError::UnsupportedSchemaVersion(u16::MAX),
+            GameError::InvalidChat,
+            GameError::InvalidStraddle,
+            GameError::GamePaused,
+            deck::DeckError::OutOfCards.into(),
+            hand::HandError::NotFiveCards(4).into(),
+            bet::BetError::BetTooLow.into(),
+        ];
+        for e in errors {
+            match e {
+                GameError::PlayerAlreadySeated
+                | GameError::TableFull
+                | GameError::NotEnoughPlayers
+                | GameError::NotEnoughFundedPlayers
+                | GameError::StreetNotComplete
+                | GameError::PlayerNotFound
+                | GameError::PlayerIsNotBetting
+                | GameError::NoBetExpected
+                | GameError::OutOfTurn
+                | GameError::PlayerStackTooShort
+                | GameError::InvalidBet
+                | GameError::GameAlreadyStarted
+                | GameError::HandInProgress
+                | GameError::InvalidDeal
+                | GameError::InvalidMinChip
+                | GameError::DeckExhausted
+                | GameError::InvalidConfig
+                | GameError::InvalidBuyIn
+                | GameError::SeatTaken
+                | GameError::InvalidSeat
+                | GameError::BettingPlayerCantStand
+                | GameError::RoundNotOver
+                | GameError::UnknownPlayer
+                | GameError::PlayerEliminated
+                | GameError::CantRaiseSelf
+                | GameError::UnevenBets
+                | GameError::InvalidState(_)
+                | GameError::UnsupportedSchemaVersion(_)
+                | GameError::InvalidChat
+                | GameError::InvalidStraddle
+                | GameError::GamePaused
+                | GameError::DeckError(_)
+                | GameError::HandError(_)
+                | GameError::BetError(_) => {
+                    let s = e.to_string();
+                    assert!(!s.is_empty(), "{e:?}");
+                    assert_ne!(s, format!("{e:?}"));
+                }
+            }
+        }
+        assert_eq!(
+            GameError::OutOfTurn.to_string(),
+            "It isn't that player's turn"
+        );
+        assert_eq!(
+            GameError::HandInProgress.to_string(),
+            "Can't do that while a hand is in progress"
+        );
+        assert_eq!(
+            GameError::InvalidState("EOF".to_owned()).to_string(),
+            "Unable to read game state: EOF"
+        );
+        assert_eq!(
+            GameError::from(hand::HandError::NotFiveCards(4)).to_string(),
+            "Five cards are requied, but 4 were given"
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use crate::bet::BetAction;
 use crate::deck::Card;
+use crate::hand::HandClass;
 use crate::pot;
 use crate::state;
-use crate::{Currency, PlayerId, SeqNum};
+use crate::{Currency, PlayerId, SeatIdx, SeqNum};
 use serde::{Deserialize, Serialize};
 
 const MAX_ARCHIVED_HANDS: usize = 3;
@@ -13,12 +15,53 @@ pub enum LogItem {
     StateChange(state::State, state::State),
     TokensSet(usize, usize, usize), // btn/sb/bb seat indexes into player array
     NextToAct(usize),               // seat index into player array
+    ActionDeadline(SeatIdx, u32),   // the player in this seat has this many millis to act
     CurrentBetSet(Currency, Currency, Currency, Currency),
     PocketDealt(PlayerId, Option<[Card; 2]>),
     HandReveal(PlayerId, [Option<Card>; 2]), // Option "on the inside" to support player revealing just one card
+    HandResult(PlayerId, HandClass, [Card; 5], bool), // the hand a revealed winner won with, and whether it's just the board
+    Muck(PlayerId),                                   // this player chose not to show at showdown
+    WonByFold(PlayerId), // everyone else folded, so this player wins without a showdown
     Flop(Card, Card, Card),
     Turn(Card),
     River(Card),
+    Deal(Vec<(PlayerId, Currency)>), // players' new stacks after agreeing to chop, sorted by id
+    ChipsAdded(PlayerId, Currency, Currency), // a player topped up by this much, to this new stack
+    DeckCommitment([u8; 32]),        // sha256 of the seed this hand's deck was shuffled with
+    BlindsChanged(Currency, Currency, Currency), // new small blind, big blind, and ante (0 for none)
+    ForcedAction(PlayerId, BetAction), // an admin made this player take this action, out of turn if need be
+    Chat(PlayerId, String),            // this player said this to the table
+    Straddle(PlayerId, Currency),      // this player straddled for this much. The pot bet follows
+    /// The system acted on this player's behalf. The resulting pot bet log item follows this one.
+    AutoAction {
+        player_id: PlayerId,
+        action: BetAction,
+        reason: AutoActionReason,
+    },
+    /// The system mucked this player's hand at showdown for them. The Muck log item follows.
+    AutoMuck {
+        player_id: PlayerId,
+        reason: AutoActionReason,
+    },
+    /// A hand is starting. Everyone dealt in, by seat, with their stack before antes and blinds.
+    HandStart {
+        hand_num: u32,
+        button: SeatIdx,
+        players: Vec<(SeatIdx, PlayerId, Currency)>,
+    },
+}
+
+/// Why the system took an action for a player instead of the player choosing it themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, derive_more::Display)]
+pub enum AutoActionReason {
+    /// The player took too long to act
+    Timeout,
+    /// The player's client is no longer connected
+    Disconnect,
+    /// The player has asked to sit out
+    SitOut,
+    /// The player chose what to do before it was their turn
+    Queued,
 }
 
 impl From<pot::LogItem> for LogItem {
@@ -34,6 +77,9 @@ impl std::fmt::Display for LogItem {
             LogItem::NewBaseState(bs) => write!(f, "{bs}"),
             LogItem::TokensSet(btn, sb, bb) => write!(f, "BTN/SB/BB set to seats {btn}/{sb}/{bb}"),
             LogItem::NextToAct(idx) => write!(f, "Next to act is seat {idx}"),
+            LogItem::ActionDeadline(idx, millis) => {
+                write!(f, "Seat {idx} has {millis} ms to act")
+            }
             LogItem::StateChange(old, new) => write!(f, "State changed from {old} to {new}"),
             LogItem::CurrentBetSet(old_cb, new_cb, old_mr, new_mr) => {
                 write!(f, "Current bet changed from {old_cb} to {new_cb}; min raise changed from {old_mr} to {new_mr}")
@@ -50,6 +96,19 @@ impl std::fmt::Display for LogItem {
                     cards[1].map_or_else(|| "".to_owned(), |c| c.to_string())
                 )
             }
+            LogItem::HandResult(player_id, class, cards, plays_board) => {
+                let how = if *plays_board {
+                    "plays the board"
+                } else {
+                    "wins"
+                };
+                write!(
+                    f,
+                    "Player {player_id} {how} with a {class} ({}{}{}{}{})",
+                    cards[0], cards[1], cards[2], cards[3], cards[4]
+                )
+            }
+            LogItem::Muck(player_id) => write!(f, "Player {player_id} mucks"),
             // LogItem::SitDown(p, seat, monies) => {
             //     write!(f, "p{} sits in seat {} with {}", p, seat, monies)
             // }
@@ -57,6 +116,65 @@ impl std::fmt::Display for LogItem {
             LogItem::Flop(c1, c2, c3) => write!(f, "Flop: {c1} {c2} {c3}"),
             LogItem::Turn(c) => write!(f, "Turn: {c}"),
             LogItem::River(c) => write!(f, "River: {c}"),
+            LogItem::AutoAction {
+                player_id,
+                action,
+                reason,
+            } => write!(
+                f,
+                "Player {player_id} automatically does {action} ({reason})"
+            ),
+            LogItem::AutoMuck { player_id, reason } => {
+                write!(f, "Player {player_id} automatically mucks ({reason})")
+            }
+            LogItem::ForcedAction(player_id, bet_action) => {
+                write!(f, "Admin forces player {player_id} to {bet_action}")
+            }
+            LogItem::Chat(player_id, text) => write!(f, "Player {player_id} says: {text}"),
+            LogItem::Straddle(player_id, amount) => {
+                write!(f, "Player {player_id} straddles for {amount}")
+            }
+            LogItem::ChipsAdded(player_id, amount, stack) => {
+                write!(f, "Player {player_id} adds {amount} chips for {stack}")
+            }
+            LogItem::WonByFold(player_id) => {
+                write!(f, "Player {player_id} wins after everyone else folds")
+            }
+            LogItem::BlindsChanged(sb, bb, 0) => write!(f, "Blinds are now {sb}/{bb}"),
+            LogItem::BlindsChanged(sb, bb, ante) => {
+                write!(f, "Blinds are now {sb}/{bb} with a {ante} ante")
+            }
+            LogItem::DeckCommitment(hash) => {
+                write!(f, "Deck commitment: ")?;
+                for b in hash {
+                    write!(f, "{b:02x}")?;
+                }
+                Ok(())
+            }
+            LogItem::HandStart {
+                hand_num,
+                button,
+                players,
+            } => {
+                let players: Vec<String> = players
+                    .iter()
+                    .map(|(seat, player_id, stack)| {
+                        format!("seat {seat} player {player_id} ({stack})")
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "Hand {hand_num} starts, button in seat {button}: {}",
+                    players.join(", ")
+                )
+            }
+            LogItem::Deal(shares) => {
+                let shares: Vec<String> = shares
+                    .iter()
+                    .map(|(player_id, stack)| format!("player {player_id} gets {stack}"))
+                    .collect();
+                write!(f, "Players agree to a deal: {}", shares.join(", "))
+            }
         }
     }
 }
@@ -88,6 +206,11 @@ impl Log {
         self.drop_oldest_archived();
     }
 
+    /// The sequence number of the first item logged this hand, if anything has been
+    pub(crate) fn first_active_seq(&self) -> Option<SeqNum> {
+        self.active.first().map(|(seq, _)| *seq)
+    }
+
     pub(crate) fn items_since(
         &self,
         oldest_seq: SeqNum,
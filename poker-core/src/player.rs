@@ -4,6 +4,8 @@ use crate::GameError;
 use crate::{Currency, PlayerId, SeatIdx, MAX_PLAYERS};
 use bitflags::bitflags;
 use core::cmp::Ordering;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -18,6 +20,10 @@ pub struct Players {
     pub token_sb: usize,
     /// loation of big blind token, index into players array
     pub token_bb: usize,
+    /// whether the tokens have been handed out for a hand yet, so that the next rotation can
+    /// follow them around the table (see [`Players::rotate_tokens`])
+    #[serde(default)]
+    pub(crate) tokens_placed: bool,
     /// players (as indexes into players array that we need bets from next, ordered in reverse
     /// (next expected better is last in this Vec, and so on)
     pub(crate) need_bets_from: Vec<usize>,
@@ -30,6 +36,7 @@ impl Default for Players {
             token_dealer: 0,
             token_sb: 0,
             token_bb: 0,
+            tokens_placed: false,
             need_bets_from: Vec::with_capacity(MAX_PLAYERS),
         }
     }
@@ -41,6 +48,10 @@ pub enum PlayStatus {
     Playing,
     WantsSitOut,
     SittingOut,
+    /// Out of a tournament, having finished in the given place
+    Eliminated {
+        place: u32,
+    },
 }
 
 bitflags! {
@@ -66,12 +77,32 @@ pub struct Player {
     pub pocket: Option<[Card; POCKET_SIZE]>,
     pub bet_status: BetStatus,
     pub play_status: PlayStatus,
+    /// Whether this player has agreed to let spectators (people not seated at the table) see
+    /// their pocket as soon as it's dealt, e.g. for a delayed stream.
+    #[serde(default)]
+    pub reveal_to_spectators: bool,
+    /// Whether this player sat down between the button and the big blind, and so owes a big blind
+    /// before they're dealt in. Otherwise they'd get to play until the blinds come back around to
+    /// them without paying any.
+    #[serde(default)]
+    pub must_post: bool,
 }
 impl Players {
     pub fn player_by_id(&self, id: PlayerId) -> Option<&Player> {
         self.player_with_index_by_id(id).map(|(_, p)| p)
     }
 
+    /// The seat the given player is sitting in, if they are at this table.
+    pub fn seat_of(&self, id: PlayerId) -> Option<SeatIdx> {
+        self.player_with_index_by_id(id).map(|(idx, _)| idx)
+    }
+
+    /// The player sitting in the given seat, if any. Seats past the end of the table are simply
+    /// empty.
+    pub fn player_at(&self, seat: SeatIdx) -> Option<&Player> {
+        self.players.get(seat).and_then(|p| p.as_ref())
+    }
+
     pub(crate) fn player_with_index_by_id(&self, id: PlayerId) -> Option<(SeatIdx, &Player)> {
         self.players_iter(PlayerFilter::ALL)
             .find(|(_, x)| x.id == id)
@@ -83,8 +114,9 @@ impl Players {
             .map(|(_, p)| p)
     }
 
-    pub(crate) fn seat_player(&mut self, player: Player) -> Result<SeatIdx, GameError> {
+    pub(crate) fn seat_player(&mut self, mut player: Player) -> Result<SeatIdx, GameError> {
         if let Some(seat_idx) = self.next_empty_seat() {
+            player.must_post = self.owes_entry_blind(seat_idx);
             self.players[seat_idx] = Some(player);
             Ok(seat_idx)
         } else {
@@ -92,36 +124,81 @@ impl Players {
         }
     }
 
+    /// Seat the given player in the given seat, which must exist and be empty.
+    pub(crate) fn seat_player_at(
+        &mut self,
+        mut player: Player,
+        seat: SeatIdx,
+    ) -> Result<(), GameError> {
+        player.must_post = self.owes_entry_blind(seat);
+        match self.players.get_mut(seat) {
+            None => Err(GameError::InvalidSeat),
+            Some(Some(_)) => Err(GameError::SeatTaken),
+            Some(s) => {
+                *s = Some(player);
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether someone sitting down in the given seat would be on the wrong side of the button:
+    /// after the BTN but before the BB, where the blinds have just gone past. Before the first
+    /// hand there's no button yet, so everyone's fine.
+    fn owes_entry_blind(&self, seat: SeatIdx) -> bool {
+        let from_btn = |s: SeatIdx| (s + MAX_PLAYERS - self.token_dealer) % MAX_PLAYERS;
+        self.tokens_placed && from_btn(seat) > 0 && from_btn(seat) < from_btn(self.token_bb)
+    }
+
+    /// Take the given player out of their seat, if they have one.
+    pub(crate) fn remove_player(&mut self, id: PlayerId) -> Option<Player> {
+        let seat = self.seat_of(id)?;
+        self.players[seat].take()
+    }
+
+    /// Shuffle every seat (empty ones included) so that players end up in random seats. Players
+    /// keep their stacks and everything else; only where they sit changes.
+    pub(crate) fn shuffle_seats<R: Rng>(&mut self, rng: &mut R) {
+        self.players.shuffle(rng);
+    }
+
+    /// Give out the given pockets, as dealt by [`Deck::deal_pockets`](crate::deck::Deck::deal_pockets),
+    /// to every player in the hand, including any already all in from posting: the first pocket
+    /// goes to the first player clockwise from the dealer, the next pocket to the next player, and
+    /// so on, with the dealer getting the last one. Since the deck deals one card to each pocket
+    /// and then a second card to each, this matches the order cards come off the deck at a real
+    /// table.
     pub(crate) fn deal_pockets(
         &mut self,
-        mut pockets: Vec<[Card; 2]>,
+        pockets: Vec<[Card; 2]>,
     ) -> HashMap<PlayerId, Option<[Card; 2]>> {
-        assert_eq!(
-            pockets.len(),
-            self.players_iter(PlayerFilter::MAY_BET).count()
-        );
-        let dt = self.token_dealer;
+        let seats = self.pot_eligible_seats_from_dealer();
+        assert_eq!(pockets.len(), seats.len());
         let mut ret = HashMap::new();
-        // Can't use a betting_players_iter_after_mut() becasue can't chain/cycle mutable iterator
-        // May be able to fix this with custom iterator
-        // Until then, iterate twice
-        for (_, player) in self
-            .players_iter_mut(PlayerFilter::MAY_BET)
-            .skip_while(|(i, _)| *i < dt)
-        {
-            player.pocket = Some(pockets.pop().unwrap());
-            ret.insert(player.id, Some(player.pocket.unwrap()));
-        }
-        for (_, player) in self
-            .players_iter_mut(PlayerFilter::MAY_BET)
-            .take_while(|(i, _)| *i < dt)
-        {
-            player.pocket = Some(pockets.pop().unwrap());
-            ret.insert(player.id, Some(player.pocket.unwrap()));
+        for (seat, pocket) in seats.into_iter().zip(pockets) {
+            let player = self.players[seat]
+                .as_mut()
+                .expect("betting player should be seated");
+            player.pocket = Some(pocket);
+            ret.insert(player.id, Some(pocket));
         }
         ret
     }
 
+    /// The seats of the players still in the hand, starting with the first one clockwise from the
+    /// dealer and ending with the dealer (if they're still in).
+    pub(crate) fn pot_eligible_seats_from_dealer(&self) -> Vec<SeatIdx> {
+        let mut seats: Vec<SeatIdx> = self
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .map(|(seat, _)| seat)
+            .collect();
+        let first = seats
+            .iter()
+            .position(|seat| *seat > self.token_dealer)
+            .unwrap_or(0);
+        seats.rotate_left(first);
+        seats
+    }
+
     fn next_empty_seat(&self) -> Option<SeatIdx> {
         self.players
             .iter()
@@ -206,36 +283,73 @@ impl Players {
 
     fn auto_sitout(&mut self) {
         for (_, p) in self.players_iter_mut(PlayerFilter::ALL) {
-            if p.stack < 1 {
+            if p.stack < 1 && !matches!(p.play_status, PlayStatus::Eliminated { .. }) {
                 p.play_status = PlayStatus::SittingOut;
             }
         }
     }
 
+    /// How many players [`Players::start_hand`] would deal in: those playing that have chips
+    pub(crate) fn num_to_deal_in(&self) -> usize {
+        self.players_iter(PlayerFilter::ALL)
+            .filter(|(_, p)| matches!(p.play_status, PlayStatus::Playing) && p.stack >= 1)
+            .count()
+    }
+
     pub(crate) fn start_hand(&mut self) -> Result<(), GameError> {
         self.auto_sitout();
-        if self.players_iter(PlayerFilter::SEATED).count() < 2 {
-            return Err(GameError::NotEnoughPlayers);
+        for (_, p) in self.players_iter_mut(PlayerFilter::ALL) {
+            if matches!(p.play_status, PlayStatus::WantsSitOut) {
+                p.play_status = PlayStatus::SittingOut;
+            }
+        }
+        let n_seated = self.players_iter(PlayerFilter::SEATED).count();
+        if n_seated < 2 {
+            // auto_sitout just sat out the broke players. Had they chips, would there be a game?
+            let n_broke = self
+                .players_iter(PlayerFilter::ALL)
+                .filter(|(_, p)| {
+                    p.stack < 1 && !matches!(p.play_status, PlayStatus::Eliminated { .. })
+                })
+                .count();
+            return Err(if n_seated + n_broke >= 2 {
+                GameError::NotEnoughFundedPlayers
+            } else {
+                GameError::NotEnoughPlayers
+            });
         }
         //self.unfold_all();
         //self.auto_fold_players();
+        // Players sitting out aren't dealt in, so as far as this hand is concerned they've folded
         for (_, p) in self.players_iter_mut(PlayerFilter::ALL) {
-            p.bet_status = BetStatus::Waiting;
+            p.bet_status = match p.play_status {
+                PlayStatus::Playing => BetStatus::Waiting,
+                PlayStatus::WantsSitOut
+                | PlayStatus::SittingOut
+                | PlayStatus::Eliminated { .. } => BetStatus::Folded,
+            };
             p.pocket = None;
         }
         self.rotate_tokens()?;
         //self.last_better = self.token_dealer;
-        // prepare need_bets_from for the blinds bets
+        // prepare need_bets_from for the blinds bets, starting with the SB. That's usually the
+        // first player after the BTN, but not when the BTN is dead and someone has sat down
+        // between it and the SB.
+        let n_betting = self.players_iter(PlayerFilter::MAY_BET).count();
+        let start_after = match n_betting {
+            2 => self.token_dealer,
+            _ => (self.token_sb + MAX_PLAYERS - 1) % MAX_PLAYERS,
+        };
         self.need_bets_from = self
-            .betting_players_iter_after(self.token_dealer)
+            .betting_players_iter_after(start_after)
             .map(|(i, _)| i)
-            .take(self.players_iter(PlayerFilter::MAY_BET).count())
+            .take(n_betting)
             .collect();
         // need_bets_from stores the next needed seat at the end of the vector. This requires
         // reversing the list in all cases except when we're heads up. When heads up, the dealer/sb
         // acts first preflop, and the dealer/sb seat should already be the 2nd (of two) items in
         // the vector.
-        if self.players_iter(PlayerFilter::MAY_BET).count() == 2 {
+        if n_betting == 2 {
             assert_eq!(self.token_dealer, self.token_sb);
             assert_eq!(self.token_bb, self.need_bets_from[0]);
             assert_eq!(self.token_sb, self.need_bets_from[1]);
@@ -283,38 +397,83 @@ impl Players {
 
     /// Rotate the BTN, SB, and BB tokens to the next seats clockwise.
     ///
-    /// It is almost definitely the case that this function does not currently handle players that
-    /// join on "the wrong side" of the button and are supposed to sit out for a bit before being
-    /// dealt in. Idk the rules for this, so it's not implemented at this time. 11/11/22 MT
+    /// Once the tokens have been placed, this follows the dead button rule: the BB moves on to the
+    /// next player after last hand's BB, and the BTN and SB move to the seats last hand's SB and
+    /// BB were in. If the player in one of those seats has left or isn't dealt in, the BTN sits
+    /// on an empty seat (a dead button) or no one posts the SB (a dead small blind). This way no
+    /// one pays the BB twice or skips it just because someone near the button left.
+    ///
+    /// Players that join on "the wrong side" of the button are dealt in like anyone else, but they
+    /// have to post a big blind to do so (see [`Player::must_post`]).
     pub(crate) fn rotate_tokens(&mut self) -> Result<(), GameError> {
         let n_players = self.players_iter(PlayerFilter::MAY_BET).count();
         if n_players < 2 {
             return Err(GameError::NotEnoughPlayers);
         }
-        let mut s: [usize; 3] = [0, 0, 0];
-        // iter borrows self, so have to work around borrowing rules
-        // This might be fixable
-        // Unwraps can't panic because iter size is at least 2 above, and `betting_players_iter_after` returns count * 2 entries, making a minimum values in the iter 4
-        {
-            let mut iter = self
-                .betting_players_iter_after(self.token_dealer)
-                .map(|(i, _)| i);
-            s[0] = iter.next().unwrap();
-            s[1] = iter.next().unwrap();
-            s[2] = iter.next().unwrap();
+        if let Some((btn, sb, bb)) = self.dead_button_tokens(n_players) {
+            self.token_dealer = btn;
+            self.token_sb = sb;
+            self.token_bb = bb;
+            return Ok(());
         }
-        // If there's two players, the dealer and SB are the same.
-        if n_players == 2 {
-            self.token_dealer = s[0];
-            self.token_sb = s[0];
-            self.token_bb = s[1];
-        } else {
-            self.token_dealer = s[0];
-            self.token_sb = s[1];
-            self.token_bb = s[2];
+        self.tokens_placed = true;
+        // The next (up to) three betting seats after the current dealer. Heads up there are only
+        // two seats to hand the three tokens to, so don't go looking for a third.
+        let s: Vec<SeatIdx> = self
+            .betting_players_iter_after(self.token_dealer)
+            .map(|(i, _)| i)
+            .take(n_players.min(3))
+            .collect();
+        match s[..] {
+            // If there's two players, the dealer and SB are the same.
+            [btn, bb] => {
+                self.token_dealer = btn;
+                self.token_sb = btn;
+                self.token_bb = bb;
+            }
+            [btn, sb, bb] => {
+                self.token_dealer = btn;
+                self.token_sb = sb;
+                self.token_bb = bb;
+            }
+            _ => unreachable!(
+                "{} betting players but {} seats after dealer",
+                n_players,
+                s.len()
+            ),
         }
         Ok(())
     }
+
+    /// Where the dead button rule puts the BTN, SB, and BB tokens next, or None if the tokens
+    /// haven't been placed yet or following them would leave the blinds out of order (e.g. the BB
+    /// wrapping around past the BTN after several players left at once). Heads up, the BTN posts
+    /// the SB, so there's nothing dead: whoever isn't the next BB gets the button.
+    fn dead_button_tokens(&self, n_players: usize) -> Option<(SeatIdx, SeatIdx, SeatIdx)> {
+        if !self.tokens_placed {
+            return None;
+        }
+        let (bb, _) = self.betting_players_iter_after(self.token_bb).next()?;
+        if n_players == 2 {
+            let (btn, _) = self.betting_players_iter_after(bb).next()?;
+            return Some((btn, btn, bb));
+        }
+        let (btn, sb) = (self.token_sb, self.token_bb);
+        // Going clockwise from the SB, the BB must come before the BTN does
+        let from_sb = |seat: SeatIdx| (seat + MAX_PLAYERS - sb) % MAX_PLAYERS;
+        if btn == sb || from_sb(bb) == 0 || from_sb(bb) >= from_sb(btn) {
+            return None;
+        }
+        Some((btn, sb, bb))
+    }
+
+    /// Whether someone in the given seat is dealt in this hand, i.e. able to post a blind
+    pub(crate) fn is_live(&self, seat: SeatIdx) -> bool {
+        self.players
+            .get(seat)
+            .and_then(|p| p.as_ref())
+            .map_or(false, |p| !p.is_folded())
+    }
 }
 
 impl Player {
@@ -329,6 +488,8 @@ impl Player {
             } else {
                 PlayStatus::Playing
             },
+            reveal_to_spectators: false,
+            must_post: false,
         }
     }
 
@@ -371,8 +532,8 @@ impl Player {
                 }
                 let additional_in = x - existing_in;
                 match self.stack.cmp(&additional_in) {
-                    Ordering::Less => {
-                        // Only called when blinds are short stacked.
+                    // Short stacked blinds, or calling for everything left
+                    Ordering::Less | Ordering::Equal => {
                         let r = BetAction::AllIn(self.stack + existing_in);
                         self.stack = 0;
                         r
@@ -401,12 +562,58 @@ impl Player {
         self.bet_status = BetStatus::from(return_bet);
         Ok(return_bet)
     }
+
+    /// Gives back this much of the player's bet, that nobody else matched
+    pub(crate) fn return_uncalled(&mut self, amount: Currency) {
+        self.stack += amount;
+        self.bet_status = match self.bet_status {
+            BetStatus::In(x) => BetStatus::In(x - amount),
+            BetStatus::AllIn(x) => BetStatus::AllIn(x - amount),
+            status => status,
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Rotating tokens never panics, whatever seats the players are in, and always gives the
+    /// tokens to distinct seats with 3+ players.
+    #[test]
+    fn token_rotation_random_layouts() {
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(220);
+        let mut seats: Vec<SeatIdx> = (0..MAX_PLAYERS).collect();
+        for _ in 0..500 {
+            let n = rng.gen_range(2..=MAX_PLAYERS);
+            let mut players = Players::default();
+            seats.shuffle(&mut rng);
+            for (pid, seat) in seats[..n].iter().enumerate() {
+                players.players[*seat] = Some(Player::new(pid as PlayerId, 10));
+            }
+            // the last seat is an edge case worth hitting often, so sometimes move someone there
+            if players.players[MAX_PLAYERS - 1].is_none() && rng.gen_bool(0.5) {
+                let moved = seats[0];
+                players.players[MAX_PLAYERS - 1] = players.players[moved].take();
+            }
+            players.token_dealer = rng.gen_range(0..MAX_PLAYERS);
+            for _ in 0..n + 1 {
+                players.rotate_tokens().unwrap();
+                let (btn, sb, bb) = (players.token_dealer, players.token_sb, players.token_bb);
+                for seat in [btn, sb, bb] {
+                    assert!(players.players[seat].is_some());
+                }
+                if n == 2 {
+                    assert_eq!(btn, sb);
+                    assert_ne!(sb, bb);
+                } else {
+                    assert!(btn != sb && sb != bb && bb != btn);
+                }
+            }
+        }
+    }
+
     /// BTN/SB is same seat when heads up, which is a special case
     #[test]
     fn token_rotation_heads_up() {
@@ -468,6 +675,29 @@ mod tests {
         assert_eq!(players.token_bb, 7);
     }
 
+    /// A player saved before spectators could be shown pockets reads back as not showing them
+    #[test]
+    fn reveal_to_spectators_default() {
+        let json =
+            r#"{"id":1,"stack":10,"pocket":null,"bet_status":"Waiting","play_status":"Playing"}"#;
+        let p: Player = serde_json::from_str(json).unwrap();
+        assert!(!p.reveal_to_spectators);
+        assert_eq!(p, Player::new(1, 10));
+    }
+
+    #[test]
+    fn seat_accessors() {
+        let mut players = Players::default();
+        players.players[3] = Some(Player::new(7, 10));
+        assert_eq!(players.seat_of(7), Some(3));
+        assert_eq!(players.player_at(3).unwrap().id, 7);
+        // empty seat, and a seat that doesn't exist
+        assert!(players.player_at(2).is_none());
+        assert!(players.player_at(MAX_PLAYERS).is_none());
+        // unknown id
+        assert_eq!(players.seat_of(8), None);
+    }
+
     // betting_players_iter_after still returns the right number of players, regardless of the seat
     // index given to it. They're also in the right order.
     #[test]
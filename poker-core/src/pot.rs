@@ -20,6 +20,15 @@ pub enum LogItem {
     PartialStakeInPot(usize, PlayerId, Stake, Currency),
     NewPotCreated(usize, PlayerId, Stake),
     Payouts(Option<usize>, HashMap<PlayerId, Currency>),
+    Rake(Currency),
+    /// Every settled pot so far, main pot first: its index (as in Payouts), its value, and the
+    /// players that have put chips in it, sorted. Pot doesn't know about folds, so a player that
+    /// put chips in and then folded is still listed.
+    PotStructure(Vec<(usize, Currency, Vec<PlayerId>)>),
+    /// How much each player put in during the betting round that just ended, sorted by player
+    BetsCollected(Vec<(PlayerId, Currency)>),
+    /// This much of this player's bet this betting round went unmatched and went back to them
+    UncalledBetReturned(PlayerId, Currency),
 }
 
 impl std::fmt::Display for LogItem {
@@ -66,7 +75,52 @@ impl std::fmt::Display for LogItem {
                 };
                 write!(f, "{} payouts: {}", prefix, s)
             }
+            LogItem::Rake(amount) => write!(f, "The house takes {} in rake", amount),
+            LogItem::BetsCollected(bets) => {
+                let middle: String = bets
+                    .iter()
+                    .map(|(player, amount)| format!("p{}: {}", player, amount))
+                    .join(", ");
+                write!(f, "Bets collected into the pot: [{}]", middle)
+            }
+            LogItem::PotStructure(pots) => {
+                let middle: String = pots
+                    .iter()
+                    .map(|(pot_n, value, players)| {
+                        let players = players.iter().map(|p| format!("p{}", p)).join(" ");
+                        format!("pot {}: {} ({})", pot_n, value, players)
+                    })
+                    .join(", ");
+                write!(f, "Pots are now: [{}]", middle)
+            }
+            LogItem::UncalledBetReturned(player, amount) => {
+                write!(f, "Uncalled bet of {} returned to p{}", amount, player)
+            }
+        }
+    }
+}
+
+/// How much of the pot the house takes at the end of each hand: a percentage of the whole pot, up
+/// to a cap. The default takes nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RakeConfig {
+    /// Percent of the pot to take, 0 to 100
+    pub percent: u8,
+    /// The most to take from a single hand
+    pub cap: Currency,
+    /// Take nothing from hands that end before the flop
+    pub no_flop_no_drop: bool,
+}
+
+impl RakeConfig {
+    /// The rake on a pot of the given size. Rounded down to a multiple of min_chip, since the house
+    /// can't take part of a chip.
+    pub fn rake(&self, pot: Currency, saw_flop: bool, min_chip: Currency) -> Currency {
+        if self.no_flop_no_drop && !saw_flop {
+            return 0;
         }
+        let rake = (pot * Currency::from(self.percent) / 100).min(self.cap);
+        rake - rake % min_chip
     }
 }
 
@@ -98,27 +152,34 @@ impl From<(bool, Currency)> for Stake {
     }
 }
 
-/// Divide X as evenly as possible Y ways using only positive ints, and return those ints.
+/// Divide X as evenly as possible Y ways using only positive multiples of `unit`, and return those
+/// ints.
 ///
-/// Consider x=5 and y=3. 5 cannot be divided into 3 pieces evenly using ints. This function would
-/// return vec![2, 2, 1].
-/// x=8, y=5 returns 2, 2, 2, 1, 1.
-/// x=6, y=3 returns 2, 2, 2
+/// Consider x=5, y=3, and unit=1. 5 cannot be divided into 3 pieces evenly using ints. This
+/// function would return vec![2, 2, 1].
+/// x=8, y=5, unit=1 returns 2, 2, 2, 1, 1.
+/// x=6, y=3, unit=1 returns 2, 2, 2
+/// x=25, y=2, unit=5 returns 15, 10
+///
+/// If X isn't a multiple of `unit`, the part left over after dividing out `unit` goes to the first
+/// (largest) piece: x=27, y=2, unit=5 returns 17, 10.
 ///
 /// # Panics
 ///
 /// Panics if provided negative numbers. There should never be a negative payout, or a negative number of players
-fn split_x_by_y(x: i32, y: i32) -> Vec<i32> {
+fn split_x_by_y(x: Currency, y: Currency, unit: Currency) -> Vec<Currency> {
     assert!(y.is_positive());
-    assert!(x.is_positive());
+    assert!(!x.is_negative());
+    assert!(unit.is_positive());
+    let units = x / unit;
     let mut ret = Vec::with_capacity(y as usize);
     let mut frac_accum = 0;
     for i in 0..y {
-        frac_accum += x % y;
+        frac_accum += units % y;
         if frac_accum >= y || i == y - 1 && frac_accum > 0 {
-            ret.push((x / y) + 1);
+            ret.push((units / y + 1) * unit);
         } else {
-            ret.push(x / y);
+            ret.push(units / y * unit);
         }
         if frac_accum >= y {
             frac_accum -= y;
@@ -126,9 +187,21 @@ fn split_x_by_y(x: i32, y: i32) -> Vec<i32> {
     }
     ret.sort_unstable();
     ret.reverse();
+    ret[0] += x % unit;
     ret
 }
 
+/// Sort the given players by where they are in seat_order, keeping the given order for players
+/// that aren't in it.
+fn sort_by_seat_order(players: &mut [&PlayerId], seat_order: &[PlayerId]) {
+    players.sort_by_key(|p| {
+        seat_order
+            .iter()
+            .position(|s| s == *p)
+            .unwrap_or(seat_order.len())
+    });
+}
+
 /// "Public" interface to a pot. Tell the pot when players bet, when betting rounds are over, and
 /// the order of winning hands when it's time to pay out, and Pot will take care of all the dirty
 /// details.
@@ -154,7 +227,7 @@ fn split_x_by_y(x: i32, y: i32) -> Vec<i32> {
 /// Call this between betting rounds so the working pot can be settled and future betting can
 /// establish a new pot. This *must* be called before `payout(...)`.
 ///
-/// ## `payout(...)`
+/// ## `payout_with_rake(...)`
 ///
 /// Immediately after calling `finalize_round()` for the last time, call this to calculate and
 /// return the payouts for each winning player. This consumes the Pot. See the function
@@ -191,8 +264,48 @@ struct InnerPot {
 impl InnerPot {
     /// For this InnerPot only, return the player(s) that won and the amount they won.
     ///
-    /// See Pot's payout function for more information on the ranked_players argument.
-    fn payout(self, ranked_players: &[Vec<PlayerId>]) -> HashMap<PlayerId, Currency> {
+    /// See Pot's payout function for more information on the ranked_players and seat_order
+    /// arguments.
+    fn payout(
+        self,
+        ranked_players: &[Vec<PlayerId>],
+        seat_order: &[PlayerId],
+        min_chip: Currency,
+    ) -> HashMap<PlayerId, Currency> {
+        self.payout_amount(self.value(), ranked_players, seat_order, min_chip)
+    }
+
+    /// Like payout, but for a hi-lo game. Half of this InnerPot goes to the best high hand and half
+    /// to the best low hand. If no player eligible for this pot has a qualifying low, the high hand
+    /// scoops the whole thing. When the pot can't be halved evenly, the odd chip goes to the high.
+    fn payout_hilo(
+        self,
+        high: &[Vec<PlayerId>],
+        low: &[Vec<PlayerId>],
+        seat_order: &[PlayerId],
+        min_chip: Currency,
+    ) -> HashMap<PlayerId, Currency> {
+        let has_low = low.iter().flatten().any(|p| self.players.contains_key(p));
+        if !has_low || self.value() <= 0 {
+            return self.payout(high, seat_order, min_chip);
+        }
+        let halves = split_x_by_y(self.value(), 2, min_chip);
+        let mut hm = self.payout_amount(halves[0], high, seat_order, min_chip);
+        let hm_low = self.payout_amount(halves[1], low, seat_order, min_chip);
+        crate::util::merge_hashmap(&mut hm, hm_low);
+        hm
+    }
+
+    /// Pay the given amount out of this InnerPot to the best ranked player(s) eligible for it.
+    /// When it doesn't split evenly, the odd chip goes to whichever winner comes first in
+    /// seat_order.
+    fn payout_amount(
+        &self,
+        amount: Currency,
+        ranked_players: &[Vec<PlayerId>],
+        seat_order: &[PlayerId],
+        min_chip: Currency,
+    ) -> HashMap<PlayerId, Currency> {
         let mut hm: HashMap<PlayerId, Currency> = HashMap::new();
         // Loop over the player rank groups. The first group that contains >0 players in this pot is
         // used, and then we are done. So we generally expect to only loop once. Remember, the
@@ -200,22 +313,35 @@ impl InnerPot {
         for player_group in ranked_players {
             // See if any of the players in this group were eligible to win this pot. If not, move
             // on to the next group
-            let winning_players: Vec<_> = player_group
+            let mut winning_players: Vec<_> = player_group
                 .iter()
                 .filter(|&&p| self.players.contains_key(&p))
                 .collect();
+            sort_by_seat_order(&mut winning_players, seat_order);
             if winning_players.is_empty() {
                 continue;
             }
             assert!(!winning_players.is_empty());
             // split the payout evenly across all the winning players. It's important that we
             // avoided division by 0 by making sure there is >0 winning players.
-            let payouts = split_x_by_y(self.value(), winning_players.len().try_into().unwrap());
+            let payouts = split_x_by_y(amount, winning_players.len().try_into().unwrap(), min_chip);
             for (player, payout) in itertools::zip(winning_players, payouts) {
                 hm.insert(*player, payout);
             }
             break;
         }
+        // Nobody that put money in this pot is still in the hand, e.g. everyone folded to a player
+        // that was already all in. It's dead money, so it goes to the best hand left.
+        if hm.is_empty() && amount > 0 {
+            if let Some(best) = ranked_players.iter().find(|group| !group.is_empty()) {
+                let mut best: Vec<_> = best.iter().collect();
+                sort_by_seat_order(&mut best, seat_order);
+                let payouts = split_x_by_y(amount, best.len().try_into().unwrap(), min_chip);
+                for (player, payout) in itertools::zip(best, payouts) {
+                    hm.insert(*player, payout);
+                }
+            }
+        }
         hm
     }
 
@@ -232,7 +358,13 @@ impl Pot {
     /// stored in our settled vec of InnerPots (at which point they won't be touched). Side pots are
     /// automatically created if 1+ players have gone all in.
     pub(crate) fn finalize_round(&mut self) -> Vec<LogItem> {
-        let mut logs = vec![];
+        let mut logs = vec![LogItem::BetsCollected(
+            self.working
+                .iter()
+                .map(|(player, stake)| (*player, stake.amount))
+                .sorted_unstable()
+                .collect(),
+        )];
         // The new pot(s) we will add to our vec of settled pots
         let mut pots: Vec<InnerPot> = vec![];
         // Sort the players that are in this betting round such that:
@@ -318,10 +450,58 @@ impl Pot {
         }
         // Finally done creating all the new pots, so move them to settled.
         self.settled.append(&mut pots);
+        logs.push(LogItem::PotStructure(self.structure()));
         logs.push(LogItem::RoundEnd(self.settled.len()));
         logs
     }
 
+    /// Take back the part of the given player's bet this betting round that nobody else matched,
+    /// e.g. when everyone folds to their bet, so that it isn't won (or raked) as part of the pot.
+    /// Call before `finalize_round()`. Returns how much goes back to them.
+    pub(crate) fn return_uncalled(&mut self, player: PlayerId) -> (Currency, Vec<LogItem>) {
+        let matched = self
+            .working
+            .iter()
+            .filter(|(p, _)| **p != player)
+            .map(|(_, stake)| stake.amount)
+            .max()
+            .unwrap_or(0);
+        let stake = match self.working.get_mut(&player) {
+            Some(stake) if stake.amount > matched => stake,
+            _ => return (0, vec![]),
+        };
+        let returned = stake.amount - matched;
+        stake.amount = matched;
+        (
+            returned,
+            vec![LogItem::UncalledBetReturned(player, returned)],
+        )
+    }
+
+    /// Each settled pot's index, value, and the players that have chips in it
+    fn structure(&self) -> Vec<(usize, Currency, Vec<PlayerId>)> {
+        self.pot_summaries()
+            .into_iter()
+            .enumerate()
+            .map(|(pot_n, (value, players))| (pot_n, value, players))
+            .collect()
+    }
+
+    /// Each settled pot's value and the players eligible to win it, sorted by id, main pot first.
+    /// Bets from the current betting round aren't in here until after `finalize_round()`.
+    ///
+    /// Players that have folded since putting chips in a pot are still listed, as Pot doesn't know
+    /// about folds.
+    pub fn pot_summaries(&self) -> Vec<(Currency, Vec<PlayerId>)> {
+        self.settled
+            .iter()
+            .map(|pot| {
+                let players = pot.players.keys().copied().sorted_unstable().collect();
+                (pot.value(), players)
+            })
+            .collect()
+    }
+
     /// The value of all InnerPots that are settled and will not change. I.e. funds from previous
     /// betting rounds
     pub fn settled_value(&self) -> Currency {
@@ -376,26 +556,113 @@ impl Pot {
         self,
         ranked_players: &[Vec<PlayerId>],
     ) -> HashMap<PlayerId, Currency> {
-        let (hm, _) = self.payout(ranked_players);
+        let (hm, _) = self.payout(ranked_players, &[], 1);
         hm
     }
 
-    /// Like payout function, but also provides the log of actions we saw and took.
+    /// Like payout_with_rake, but without taking any rake.
+    #[cfg(test)]
     pub(crate) fn payout(
-        mut self,
+        self,
         ranked_players: &[Vec<PlayerId>],
+        seat_order: &[PlayerId],
+        min_chip: Currency,
     ) -> (HashMap<PlayerId, Currency>, Vec<LogItem>) {
+        let (hm, _, logs) = self.payout_with_rake(
+            &[ranked_players.to_vec()],
+            seat_order,
+            min_chip,
+            &RakeConfig::default(),
+            true,
+        );
+        (hm, logs)
+    }
+
+    /// Like payout_without_log function, but takes the house's rake first, can pay out over more
+    /// than one board, and also provides the log of actions we saw and took. Returns the payouts,
+    /// the rake, and the logs.
+    ///
+    /// boards has one ranking of the players, ordered like ranked_players in payout_without_log,
+    /// per board the hand was run out on. Each pot is divided into equal portions, one per board,
+    /// and each portion goes to the best hand(s) on its board. Any odd chip from that division
+    /// goes to the first board's portion. There must be at least one board.
+    ///
+    /// The rake is figured on the total value of the pot and taken from the main pot first, then
+    /// the side pots in order. saw_flop is whether the hand got to the flop, for
+    /// [`RakeConfig::no_flop_no_drop`].
+    ///
+    /// Split pots are only divided into multiples of min_chip, the smallest chip in play. Any
+    /// amount smaller than that, and any odd chip left over, goes to the winner that comes first
+    /// in seat_order. That should be every player still in the hand, starting with the first
+    /// player clockwise from the button. Winners missing from seat_order come after the rest, in
+    /// the order they're given in ranked_players.
+    pub(crate) fn payout_with_rake(
+        mut self,
+        boards: &[Vec<Vec<PlayerId>>],
+        seat_order: &[PlayerId],
+        min_chip: Currency,
+        rake: &RakeConfig,
+        saw_flop: bool,
+    ) -> (HashMap<PlayerId, Currency>, Currency, Vec<LogItem>) {
+        assert!(!boards.is_empty());
         // In case caller didn't call finalize_round() after the last betting round, do it for them.
         if !self.working.is_empty() {
             self.finalize_round();
         }
         assert!(self.working.is_empty());
         let mut logs = vec![];
+        let raked = rake.rake(self.total_value(), saw_flop, min_chip);
+        if raked > 0 {
+            logs.push(LogItem::Rake(raked));
+        }
+        let mut rake_left = raked;
         let mut hm: HashMap<PlayerId, Currency> = HashMap::new();
         // Ha! Made you look. All the hard work is done in each inner pot, and the results simply
         // merged together here.
         for (pot_n, pot) in self.settled.into_iter().enumerate() {
-            let hm_n = pot.payout(ranked_players);
+            let value = pot.value();
+            let pot_rake = rake_left.min(value);
+            rake_left -= pot_rake;
+            let portions = if boards.len() > 1 && value - pot_rake > 0 {
+                split_x_by_y(value - pot_rake, boards.len() as Currency, min_chip)
+            } else {
+                vec![value - pot_rake]
+            };
+            let mut hm_n = HashMap::new();
+            for (portion, ranked_players) in itertools::zip(portions, boards) {
+                let hm_b = pot.payout_amount(portion, ranked_players, seat_order, min_chip);
+                crate::util::merge_hashmap(&mut hm_n, hm_b);
+            }
+            logs.push(LogItem::Payouts(Some(pot_n), hm_n.clone()));
+            crate::util::merge_hashmap(&mut hm, hm_n);
+        }
+        logs.push(LogItem::Payouts(None, hm.clone()));
+        (hm, raked, logs)
+    }
+
+    /// Like payout function, but for a hi-lo game: each pot is split between the best high hand
+    /// and the best low hand. `high` is ordered like `ranked_players` in payout. `low` is ordered
+    /// the same way, best low first, but must only contain players with a qualifying low. A pot
+    /// none of whose players have a qualifying low goes entirely to the high.
+    ///
+    /// GameState only deals high-only games, so nothing in this crate calls this yet. It's meant
+    /// to be called from `GameState::finalize_hand` once a hi-lo variant exists, with `low`
+    /// built from [`crate::hand::best_low_of_cards`].
+    pub fn payout_hilo(
+        mut self,
+        high: &[Vec<PlayerId>],
+        low: &[Vec<PlayerId>],
+        seat_order: &[PlayerId],
+        min_chip: Currency,
+    ) -> (HashMap<PlayerId, Currency>, Vec<LogItem>) {
+        if !self.working.is_empty() {
+            self.finalize_round();
+        }
+        assert!(self.working.is_empty());
+        let mut logs = vec![];
+        let mut hm: HashMap<PlayerId, Currency> = HashMap::new();
+        for (pot_n, pot) in self.settled.into_iter().enumerate() {
+            let hm_n = pot.payout_hilo(high, low, seat_order, min_chip);
             logs.push(LogItem::Payouts(Some(pot_n), hm_n.clone()));
             crate::util::merge_hashmap(&mut hm, hm_n);
         }
@@ -437,53 +704,213 @@ impl Default for Pot {
 mod test_payout {
     use super::*;
 
+    /// A pot whose every contributor has since folded goes to the best hand left
+    #[test]
+    fn dead_money_to_best_hand() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::AllIn(10));
+        p.bet(2, BetAction::Bet(0));
+        p.finalize_round();
+        // player 2 bets into the all in player, then folds
+        p.bet(2, BetAction::Bet(5));
+        p.finalize_round();
+        let payout = p.payout_without_log(&[vec![1]]);
+        assert_eq!(payout[&1], 15);
+    }
+
+    /// Pots can hold more than fits in an i32
+    #[test]
+    fn bigger_than_i32() {
+        let big = Currency::from(i32::MAX);
+        let mut p = Pot::default();
+        p.bet(1, BetAction::AllIn(big));
+        p.bet(2, BetAction::Call(big));
+        p.bet(3, BetAction::Call(big));
+        assert_eq!(p.total_value(), 3 * big);
+        p.finalize_round();
+        assert_eq!(p.settled_value(), 3 * big);
+        let payout = p.payout_without_log(&[vec![2, 3]]);
+        assert_eq!(payout[&2] + payout[&3], 3 * big);
+    }
+
     #[test]
     fn simple_single_winner() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Call(5.into()));
-        p.bet(3, BetAction::Call(5.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Call(5));
+        p.bet(3, BetAction::Call(5));
         p.finalize_round();
-        let payout = p.payout_without_log(&vec![vec![1]]);
-        assert_eq!(payout[&1], 15.into());
+        let payout = p.payout_without_log(&[vec![1]]);
+        assert_eq!(payout[&1], 15);
+    }
+
+    /// The odd chip goes to the winner closest to the button's left, however the tie is listed
+    #[test]
+    fn odd_chip_by_position() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Call(5));
+        p.bet(3, BetAction::Call(5));
+        p.finalize_round();
+        let (payout, _) = p.clone().payout(&[vec![1, 3]], &[3, 1, 2], 1);
+        assert_eq!(payout, HashMap::from([(3, 8), (1, 7)]));
+        let (payout, _) = p.payout(&[vec![3, 1]], &[2, 1, 3], 1);
+        assert_eq!(payout, HashMap::from([(1, 8), (3, 7)]));
     }
 
     #[test]
     fn simple_multi_winner() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Call(5.into()));
-        p.bet(3, BetAction::Call(5.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Call(5));
+        p.bet(3, BetAction::Call(5));
         p.finalize_round();
-        let payout = p.payout_without_log(&vec![vec![1, 2]]);
-        assert_eq!(payout[&1], 8.into());
-        assert_eq!(payout[&2], 7.into());
+        let payout = p.payout_without_log(&[vec![1, 2]]);
+        assert_eq!(payout[&1], 8);
+        assert_eq!(payout[&2], 7);
 
         // it is not possible for the 3rd person to be in for more than the others like this, but
         // the pot does its best to function anyway. Garbage in => garbage out. It's the caller's
         // fault for not knowing how Texas Holdem works.
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Bet(5.into()));
-        p.bet(3, BetAction::Bet(6.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Bet(5));
+        p.bet(3, BetAction::Bet(6));
         p.finalize_round();
-        let payout = p.payout_without_log(&vec![vec![1, 2]]);
-        assert_eq!(payout[&1], 8.into());
-        assert_eq!(payout[&2], 8.into());
+        let payout = p.payout_without_log(&[vec![1, 2]]);
+        assert_eq!(payout[&1], 8);
+        assert_eq!(payout[&2], 8);
     }
 
     #[test]
     fn three_way_tie() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Bet(5.into()));
-        p.bet(3, BetAction::Bet(5.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Bet(5));
+        p.bet(3, BetAction::Bet(5));
         p.finalize_round();
-        let payout = p.payout_without_log(&vec![vec![1, 2, 3]]);
+        let payout = p.payout_without_log(&[vec![1, 2, 3]]);
         dbg!(&payout);
-        assert_eq!(payout[&1], 5.into());
-        assert_eq!(payout[&2], 5.into());
-        assert_eq!(payout[&3], 5.into());
+        assert_eq!(payout[&1], 5);
+        assert_eq!(payout[&2], 5);
+        assert_eq!(payout[&3], 5);
+    }
+}
+
+#[cfg(test)]
+mod test_rake {
+    use super::*;
+
+    const RAKE: RakeConfig = RakeConfig {
+        percent: 5,
+        cap: 3,
+        no_flop_no_drop: true,
+    };
+
+    fn pot(each: Currency) -> Pot {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(each));
+        p.bet(2, BetAction::Call(each));
+        p.finalize_round();
+        p
+    }
+
+    #[test]
+    fn under_cap() {
+        let (payout, raked, logs) = pot(20).payout_with_rake(&[vec![vec![1]]], &[], 1, &RAKE, true);
+        assert_eq!(raked, 2);
+        assert_eq!(payout[&1], 38);
+        assert!(logs.contains(&LogItem::Rake(2)));
+    }
+
+    #[test]
+    fn cap_hit() {
+        let (payout, raked, _) = pot(500).payout_with_rake(&[vec![vec![1]]], &[], 1, &RAKE, true);
+        assert_eq!(raked, 3);
+        assert_eq!(payout[&1], 997);
+    }
+
+    #[test]
+    fn no_flop_no_drop() {
+        let (payout, raked, logs) =
+            pot(500).payout_with_rake(&[vec![vec![1]]], &[], 1, &RAKE, false);
+        assert_eq!(raked, 0);
+        assert_eq!(payout[&1], 1000);
+        assert!(!logs.iter().any(|li| matches!(li, LogItem::Rake(_))));
+    }
+
+    /// Run twice, each board's winner gets half, and the first board gets the odd chip
+    #[test]
+    fn two_boards() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(21));
+        p.bet(2, BetAction::Call(21));
+        p.finalize_round();
+        let boards = [vec![vec![1], vec![2]], vec![vec![2], vec![1]]];
+        let (payout, raked, _) = p.payout_with_rake(&boards, &[], 1, &RakeConfig::default(), true);
+        assert_eq!(raked, 0);
+        assert_eq!(payout[&1], 21);
+        assert_eq!(payout[&2], 21);
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(15));
+        p.bet(2, BetAction::Call(15));
+        p.bet(3, BetAction::Call(15));
+        p.finalize_round();
+        let (payout, _, _) = p.payout_with_rake(&boards, &[], 1, &RakeConfig::default(), true);
+        assert_eq!(payout[&1], 23);
+        assert_eq!(payout[&2], 22);
+    }
+
+    /// The rake comes out of the main pot before any side pot
+    #[test]
+    fn main_pot_first() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::AllIn(2));
+        p.bet(2, BetAction::Bet(50));
+        p.bet(3, BetAction::Call(50));
+        p.finalize_round();
+        let rake = RakeConfig { cap: 5, ..RAKE };
+        let (payout, raked, _) = p.payout_with_rake(&[vec![vec![1], vec![2]]], &[], 1, &rake, true);
+        assert_eq!(raked, 5);
+        assert_eq!(payout[&1], 1);
+        assert_eq!(payout[&2], 96);
+    }
+}
+
+#[cfg(test)]
+mod test_payout_hilo {
+    use super::*;
+
+    fn pot() -> Pot {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Call(5));
+        p.bet(3, BetAction::Call(5));
+        p.finalize_round();
+        p
+    }
+
+    #[test]
+    fn scoop() {
+        let (payout, _) =
+            pot().payout_hilo(&[vec![1], vec![2], vec![3]], &[vec![1], vec![3]], &[], 1);
+        assert_eq!(payout, HashMap::from([(1, 15)]));
+    }
+
+    #[test]
+    fn split() {
+        // the high gets the odd chip
+        let (payout, _) = pot().payout_hilo(&[vec![1], vec![2], vec![3]], &[vec![2]], &[], 1);
+        assert_eq!(payout, HashMap::from([(1, 8), (2, 7)]));
+        // the low half is itself split between tied lows
+        let (payout, _) = pot().payout_hilo(&[vec![1], vec![2, 3]], &[vec![2, 3]], &[], 1);
+        assert_eq!(payout, HashMap::from([(1, 8), (2, 4), (3, 3)]));
+    }
+
+    #[test]
+    fn no_qualifying_low() {
+        let (payout, _) = pot().payout_hilo(&[vec![2], vec![1, 3]], &[], &[], 1);
+        assert_eq!(payout, HashMap::from([(2, 15)]));
     }
 }
 
@@ -497,82 +924,164 @@ mod tests {
     #[test]
     fn all_in_blind() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::AllIn(5.into()));
-        p.bet(2, BetAction::Bet(10.into()));
-        p.bet(3, BetAction::AllIn(8.into()));
+        p.bet(1, BetAction::AllIn(5));
+        p.bet(2, BetAction::Bet(10));
+        p.bet(3, BetAction::AllIn(8));
         p.finalize_round();
         dbg!(&p);
-        let payout = p.payout_without_log(&vec![vec![1], vec![2, 3]]);
+        let payout = p.payout_without_log(&[vec![1], vec![2, 3]]);
         dbg!(&payout);
         // 5 from each player, 8 remains (5 from p2's call and 3 from p3's allin)
-        assert_eq!(payout[&1], 15.into());
+        assert_eq!(payout[&1], 15);
         // a second side pot containing 6 (3 for p3's all in, and 3 from p2's call) exists. p2 and
         // p3 tied, so they split it.
         // p2 has 3 and p3 has 3.
         // The final pot has just p2 and their remaining 2. They get that whole pot.
         // p2 has 3+2 and p3 has 3 still.
-        assert_eq!(payout[&2], 5.into());
-        assert_eq!(payout[&3], 3.into());
+        assert_eq!(payout[&2], 5);
+        assert_eq!(payout[&3], 3);
     }
 
     #[test]
     fn side_pot_payout() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(10.into()));
-        p.bet(2, BetAction::AllIn(5.into()));
-        p.bet(3, BetAction::Bet(10.into()));
+        p.bet(1, BetAction::Bet(10));
+        p.bet(2, BetAction::AllIn(5));
+        p.bet(3, BetAction::Bet(10));
         p.finalize_round();
         dbg!(&p);
-        let payout = p.payout_without_log(&vec![vec![2], vec![1, 3]]);
-        assert_eq!(payout[&2], 15.into());
-        assert_eq!(payout[&1], 5.into());
-        assert_eq!(payout[&3], 5.into());
+        let payout = p.payout_without_log(&[vec![2], vec![1, 3]]);
+        assert_eq!(payout[&2], 15);
+        assert_eq!(payout[&1], 5);
+        assert_eq!(payout[&3], 5);
     }
 
     #[test]
     fn overflowing_side_pot() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(10.into()));
-        p.bet(2, BetAction::AllIn(5.into()));
-        p.bet(3, BetAction::AllIn(3.into()));
+        p.bet(1, BetAction::Bet(10));
+        p.bet(2, BetAction::AllIn(5));
+        p.bet(3, BetAction::AllIn(3));
         p.finalize_round();
         dbg!(&p);
-        let payout = p.payout_without_log(&vec![vec![3], vec![2], vec![1]]);
+        let payout = p.payout_without_log(&[vec![3], vec![2], vec![1]]);
         dbg!(&payout);
-        assert_eq!(payout[&3], 9.into());
-        assert_eq!(payout[&2], 4.into());
+        assert_eq!(payout[&3], 9);
+        assert_eq!(payout[&2], 4);
         // 1 overbet and was returned pot nobody else could claim
-        assert_eq!(payout[&1], 5.into());
+        assert_eq!(payout[&1], 5);
+    }
+
+    /// An all in that gets raised over splits the round into a main pot and a side pot, and both
+    /// are reported once the round is over
+    #[test]
+    fn pot_structure() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::AllIn(5));
+        p.bet(2, BetAction::Raise(20));
+        p.bet(3, BetAction::Call(20));
+        let logs = p.finalize_round();
+        let structure = logs
+            .into_iter()
+            .find_map(|li| match li {
+                LogItem::PotStructure(s) => Some(s),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(structure, vec![(0, 15, vec![1, 2, 3]), (1, 30, vec![2, 3])]);
+    }
+
+    /// A short all in is eligible for the main pot only, and the others for the side pot too
+    #[test]
+    fn pot_summaries() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(20));
+        p.bet(2, BetAction::AllIn(5));
+        p.bet(3, BetAction::Call(20));
+        assert!(p.pot_summaries().is_empty());
+        p.finalize_round();
+        assert_eq!(
+            p.pot_summaries(),
+            vec![(15, vec![1, 2, 3]), (30, vec![1, 3])]
+        );
+    }
+
+    /// Only the part of a bet that nobody matched is returned, and not to anyone else
+    #[test]
+    fn return_uncalled() {
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(40));
+        p.bet(2, BetAction::Raise(100));
+        p.bet(1, BetAction::Fold);
+        assert_eq!(p.return_uncalled(1), (0, vec![]));
+        assert_eq!(
+            p.return_uncalled(2),
+            (60, vec![LogItem::UncalledBetReturned(2, 60)])
+        );
+        assert_eq!(p.total_value(), 80);
+        // nothing more to return once it's matched
+        assert_eq!(p.return_uncalled(2), (0, vec![]));
+    }
+
+    /// Each round's collected bets are exactly what went into the pot that round
+    #[test]
+    fn bets_collected() {
+        let collected = |logs: Vec<LogItem>| {
+            logs.into_iter()
+                .find_map(|li| match li {
+                    LogItem::BetsCollected(b) => Some(b),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let mut p = Pot::default();
+        p.bet(1, BetAction::Bet(10));
+        p.bet(2, BetAction::AllIn(5));
+        p.bet(3, BetAction::Raise(30));
+        p.bet(1, BetAction::Call(30));
+        let round = p.total_value() - p.settled_value();
+        let bets = collected(p.finalize_round());
+        assert_eq!(bets, vec![(1, 30), (2, 5), (3, 30)]);
+        assert_eq!(bets.iter().map(|(_, a)| a).sum::<Currency>(), round);
+        // next round, one player checks and so isn't collected from
+        p.bet(1, BetAction::Check);
+        p.bet(3, BetAction::Bet(15));
+        p.bet(1, BetAction::Call(15));
+        let round = p.total_value() - p.settled_value();
+        let bets = collected(p.finalize_round());
+        assert_eq!(bets, vec![(1, 15), (3, 15)]);
+        assert_eq!(bets.iter().map(|(_, a)| a).sum::<Currency>(), round);
+        assert_eq!(p.settled_value(), 95);
     }
 
     #[test]
     fn multi_round_pot() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Call(5.into()));
-        p.bet(3, BetAction::Call(5.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Call(5));
+        p.bet(3, BetAction::Call(5));
         p.finalize_round();
         // 5,5,5 = 15 in pot
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Bet(10.into()));
-        p.bet(3, BetAction::AllIn(8.into()));
-        p.bet(1, BetAction::Call(10.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Bet(10));
+        p.bet(3, BetAction::AllIn(8));
+        p.bet(1, BetAction::Call(10));
         p.finalize_round();
         // 15 + 8,8,8 + 2,2 = 43 in pot
-        p.bet(1, BetAction::Bet(10.into()));
-        p.bet(2, BetAction::AllIn(6.into()));
+        p.bet(1, BetAction::Bet(10));
+        p.bet(2, BetAction::AllIn(6));
         p.finalize_round();
         // 43 + 6,6 + 4 = 59 in pot
         dbg!(&p);
-        let (payout, log) = p.payout(&vec![vec![3], vec![2], vec![1]]);
+        let (payout, log) = p.payout(&[vec![3], vec![2], vec![1]], &[], 1);
         dbg!(&payout);
         for log_item in &log {
             println!("{}", log_item);
         }
-        assert_eq!(payout[&3], 39.into());
-        assert_eq!(payout[&2], 16.into());
+        assert_eq!(payout[&3], 39);
+        assert_eq!(payout[&2], 16);
         // 1 overbet and was returned pot nobody else could claim
-        assert_eq!(payout[&1], 4.into());
+        assert_eq!(payout[&1], 4);
     }
 
     #[test]
@@ -583,30 +1092,30 @@ mod tests {
             let ip = &p.settled[0];
             assert_eq!(ip.players.len(), 3);
             for v in ip.players.values() {
-                assert_eq!(v.amount, 5.into());
+                assert_eq!(v.amount, 5);
             }
             assert_eq!(ip.max_in, None);
             dbg!(&p);
-            let payout = p.payout_without_log(&vec![vec![1]]);
-            assert_eq!(payout[&1], 15.into());
+            let payout = p.payout_without_log(&[vec![1]]);
+            assert_eq!(payout[&1], 15);
             dbg!(&payout);
         }
         let mut p1 = Pot::default();
-        p1.bet(1, BetAction::Bet(5.into()));
-        p1.bet(2, BetAction::Bet(5.into()));
-        p1.bet(3, BetAction::Bet(5.into()));
+        p1.bet(1, BetAction::Bet(5));
+        p1.bet(2, BetAction::Bet(5));
+        p1.bet(3, BetAction::Bet(5));
         p1.finalize_round();
         helper(p1);
         let mut p2 = Pot::default();
-        p2.bet(1, BetAction::Call(5.into()));
-        p2.bet(2, BetAction::Call(5.into()));
-        p2.bet(3, BetAction::Call(5.into()));
+        p2.bet(1, BetAction::Call(5));
+        p2.bet(2, BetAction::Call(5));
+        p2.bet(3, BetAction::Call(5));
         p2.finalize_round();
         helper(p2);
         let mut p3 = Pot::default();
-        p3.bet(1, BetAction::Raise(5.into()));
-        p3.bet(2, BetAction::Raise(5.into()));
-        p3.bet(3, BetAction::Raise(5.into()));
+        p3.bet(1, BetAction::Raise(5));
+        p3.bet(2, BetAction::Raise(5));
+        p3.bet(3, BetAction::Raise(5));
         p3.finalize_round();
         helper(p3);
     }
@@ -614,17 +1123,17 @@ mod tests {
     #[test]
     fn multi_round_pot2() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::Call(5.into()));
-        p.bet(3, BetAction::Raise(15.into()));
-        p.bet(1, BetAction::Call(15.into()));
-        p.bet(2, BetAction::Call(15.into()));
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::Call(5));
+        p.bet(3, BetAction::Raise(15));
+        p.bet(1, BetAction::Call(15));
+        p.bet(2, BetAction::Call(15));
         p.finalize_round();
-        assert_eq!(p.settled_value(), 45.into());
-        p.bet(1, BetAction::Bet(5.into()));
-        p.bet(2, BetAction::AllIn(50.into()));
-        p.bet(3, BetAction::Call(50.into()));
-        p.bet(1, BetAction::Raise(500.into()));
+        assert_eq!(p.settled_value(), 45);
+        p.bet(1, BetAction::Bet(5));
+        p.bet(2, BetAction::AllIn(50));
+        p.bet(3, BetAction::Call(50));
+        p.bet(1, BetAction::Raise(500));
         // 2 is all in and can't do anything
         // 3 folds, so there's nothing more to do
         p.finalize_round();
@@ -636,21 +1145,21 @@ mod tests {
         let pot = &p.settled[0];
         assert_eq!(pot.players.len(), 3);
         for v in pot.players.values() {
-            assert_eq!(v.amount, 15.into());
+            assert_eq!(v.amount, 15);
         }
         assert_eq!(pot.max_in, None);
 
         let pot = &p.settled[1];
         assert_eq!(pot.players.len(), 3);
         for v in pot.players.values() {
-            assert_eq!(v.amount, 50.into());
+            assert_eq!(v.amount, 50);
         }
-        assert_eq!(pot.max_in, Some(50.into()));
+        assert_eq!(pot.max_in, Some(50));
 
         let pot = &p.settled[2];
         assert_eq!(pot.players.len(), 1);
         for v in pot.players.values() {
-            assert_eq!(v.amount, 450.into());
+            assert_eq!(v.amount, 450);
         }
         assert_eq!(pot.max_in, None);
     }
@@ -658,40 +1167,40 @@ mod tests {
     #[test]
     fn all_all_in() {
         let mut p = Pot::default();
-        p.bet(1, BetAction::AllIn(5.into()));
-        p.bet(2, BetAction::AllIn(15.into()));
-        p.bet(3, BetAction::AllIn(45.into()));
+        p.bet(1, BetAction::AllIn(5));
+        p.bet(2, BetAction::AllIn(15));
+        p.bet(3, BetAction::AllIn(45));
         p.finalize_round();
         dbg!(&p);
         assert_eq!(p.settled.len(), 3);
 
         let pot = &p.settled[0];
         assert_eq!(pot.players.len(), 3);
-        assert_eq!(pot.max_in, Some(5.into()));
+        assert_eq!(pot.max_in, Some(5));
         let pot = &p.settled[1];
         assert_eq!(pot.players.len(), 2);
-        assert_eq!(pot.max_in, Some(10.into()));
+        assert_eq!(pot.max_in, Some(10));
         let pot = &p.settled[2];
         assert_eq!(pot.players.len(), 1);
-        assert_eq!(pot.max_in, Some(30.into()));
+        assert_eq!(pot.max_in, Some(30));
 
         let mut p = Pot::default();
-        p.bet(1, BetAction::AllIn(45.into()));
-        p.bet(2, BetAction::AllIn(15.into()));
-        p.bet(3, BetAction::AllIn(5.into()));
+        p.bet(1, BetAction::AllIn(45));
+        p.bet(2, BetAction::AllIn(15));
+        p.bet(3, BetAction::AllIn(5));
         p.finalize_round();
         dbg!(&p);
         assert_eq!(p.settled.len(), 3);
 
         let pot = &p.settled[0];
         assert_eq!(pot.players.len(), 3);
-        assert_eq!(pot.max_in, Some(5.into()));
+        assert_eq!(pot.max_in, Some(5));
         let pot = &p.settled[1];
         assert_eq!(pot.players.len(), 2);
-        assert_eq!(pot.max_in, Some(10.into()));
+        assert_eq!(pot.max_in, Some(10));
         let pot = &p.settled[2];
         assert_eq!(pot.players.len(), 1);
-        assert_eq!(pot.max_in, Some(30.into()));
+        assert_eq!(pot.max_in, Some(30));
     }
 }
 
@@ -701,16 +1210,166 @@ mod test_split_x_by_y {
 
     #[test]
     fn test1() {
-        assert_eq!(split_x_by_y(5, 3), vec![2, 2, 1]);
+        assert_eq!(split_x_by_y(5, 3, 1), vec![2, 2, 1]);
     }
 
     #[test]
     fn test2() {
-        assert_eq!(split_x_by_y(6, 2), vec![3, 3]);
+        assert_eq!(split_x_by_y(6, 2, 1), vec![3, 3]);
     }
 
     #[test]
     fn test3() {
-        assert_eq!(split_x_by_y(8, 5), vec![2, 2, 2, 1, 1]);
+        assert_eq!(split_x_by_y(8, 5, 1), vec![2, 2, 2, 1, 1]);
+    }
+
+    #[test]
+    fn chip_units() {
+        assert_eq!(split_x_by_y(25, 2, 5), vec![15, 10]);
+        assert_eq!(split_x_by_y(27, 2, 5), vec![17, 10]);
+        assert_eq!(split_x_by_y(3, 2, 5), vec![3, 0]);
+        assert_eq!(split_x_by_y(0, 2, 5), vec![0, 0]);
+    }
+}
+
+/// Property tests: whatever the betting and whoever wins, paying out a pot hands back exactly what
+/// went into it, less the rake.
+#[cfg(test)]
+mod test_conservation {
+    use super::*;
+    use crate::MAX_PLAYERS;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaChaRng;
+
+    /// Plausible random betting for a whole hand: each round the players still in check, call,
+    /// raise, shove, or fold a few times around the table, and then whoever's left calls so the
+    /// round can end. Returns the pot and the players that didn't fold.
+    fn random_hand(rng: &mut ChaChaRng) -> (Pot, Vec<PlayerId>) {
+        let n = rng.gen_range(2..=MAX_PLAYERS);
+        let mut stacks: Vec<Currency> = (0..n).map(|_| rng.gen_range(1..=500)).collect();
+        let mut folded = vec![false; n];
+        let mut all_in = vec![false; n];
+        let mut pot = Pot::default();
+        for _ in 0..rng.gen_range(1..=4) {
+            let mut current_bet = 0;
+            let mut round_in: Vec<Currency> = vec![0; n];
+            for pass in 0..3 {
+                for p in 0..n {
+                    if folded[p] || all_in[p] {
+                        continue;
+                    }
+                    let still_in = folded.iter().filter(|f| !**f).count();
+                    let owed = current_bet - round_in[p];
+                    let roll = rng.gen_range(0..100);
+                    let target = if pass == 2 || roll < 40 {
+                        current_bet
+                    } else if roll < 55 && owed > 0 && still_in > 1 {
+                        folded[p] = true;
+                        pot.bet(p as PlayerId, BetAction::Fold);
+                        continue;
+                    } else if roll < 70 {
+                        Currency::MAX
+                    } else {
+                        current_bet + rng.gen_range(1..=100)
+                    };
+                    if target == round_in[p] {
+                        pot.bet(p as PlayerId, BetAction::Check);
+                        continue;
+                    }
+                    let total = target.min(round_in[p] + stacks[p]);
+                    stacks[p] -= total - round_in[p];
+                    round_in[p] = total;
+                    current_bet = current_bet.max(total);
+                    let action = if stacks[p] == 0 {
+                        all_in[p] = true;
+                        BetAction::AllIn(total)
+                    } else {
+                        BetAction::Bet(total)
+                    };
+                    pot.bet(p as PlayerId, action);
+                }
+            }
+            pot.finalize_round();
+        }
+        let still_in = (0..n)
+            .filter(|p| !folded[*p])
+            .map(|p| p as PlayerId)
+            .collect();
+        (pot, still_in)
+    }
+
+    /// The given players in a random order, randomly grouped into ties
+    fn random_ranking(rng: &mut ChaChaRng, players: &[PlayerId]) -> Vec<Vec<PlayerId>> {
+        let mut players = players.to_vec();
+        players.shuffle(rng);
+        let mut ranking: Vec<Vec<PlayerId>> = vec![];
+        for p in players {
+            match ranking.last_mut() {
+                Some(tier) if rng.gen_bool(0.3) => tier.push(p),
+                _ => ranking.push(vec![p]),
+            }
+        }
+        ranking
+    }
+
+    fn check_seed(seed: u64) {
+        let mut rng = ChaChaRng::seed_from_u64(seed);
+        let (pot, still_in) = random_hand(&mut rng);
+        let total = pot.total_value();
+        for _ in 0..5 {
+            let boards: Vec<_> = (0..rng.gen_range(1..=2))
+                .map(|_| random_ranking(&mut rng, &still_in))
+                .collect();
+            let rake = RakeConfig {
+                percent: rng.gen_range(0..=10),
+                cap: rng.gen_range(0..=50),
+                no_flop_no_drop: false,
+            };
+            let min_chip = *[1, 5].choose(&mut rng).unwrap();
+            let (payouts, raked, _) = pot
+                .clone()
+                .payout_with_rake(&boards, &still_in, min_chip, &rake, true);
+            assert_eq!(
+                payouts.values().sum::<Currency>() + raked,
+                total,
+                "seed {seed}: {payouts:?} + {raked} rake from {pot:?}"
+            );
+            for (player, amount) in payouts {
+                assert!(amount >= 0, "seed {seed}: {player} paid {amount}");
+                assert!(
+                    amount == 0 || still_in.contains(&player),
+                    "seed {seed}: {player} folded but won {amount}"
+                );
+            }
+        }
+    }
+
+    /// The rake can swallow the whole main pot when it's just a short all in, leaving nothing
+    /// there to pay out
+    #[test]
+    fn rake_takes_main_pot() {
+        let mut pot = Pot::default();
+        pot.bet(1, BetAction::AllIn(5));
+        pot.bet(2, BetAction::Bet(100));
+        pot.bet(3, BetAction::Bet(100));
+        pot.finalize_round();
+        let rake = RakeConfig {
+            percent: 10,
+            cap: 50,
+            no_flop_no_drop: false,
+        };
+        let (payouts, raked, _) =
+            pot.payout_with_rake(&[vec![vec![1], vec![2, 3]]], &[1, 2, 3], 1, &rake, true);
+        assert_eq!(raked, 20);
+        assert_eq!(payouts.get(&1).copied().unwrap_or(0), 0);
+        assert_eq!(payouts[&2] + payouts[&3], 185);
+    }
+
+    #[test]
+    fn random_hands() {
+        for seed in 0..2000 {
+            check_seed(seed);
+        }
     }
 }
@@ -1,22 +1,29 @@
-use crate::bet::BetAction;
+use crate::bet::{BetAction, BetStatus, BettingLimit};
 use crate::deck::{Card, Deck, DeckSeed};
-use crate::hand::best_hands;
-use crate::log::{Log, LogItem};
-use crate::player::{Player, PlayerFilter, Players};
-use crate::pot::Pot;
-use crate::{Currency, GameError, PlayerId, SeatIdx, SeqNum, MAX_PLAYERS};
+use crate::equity::{equity, exact_equity};
+use crate::hand::{best_hands, improvement_summary, FinalHandResult, HandClass};
+use crate::log::{AutoActionReason, Log, LogItem};
+use crate::player::{PlayStatus, Player, PlayerFilter, Players};
+use crate::pot::{self, Pot, RakeConfig};
+use crate::{Currency, GameError, Millis, PlayerId, SeatIdx, SeqNum, MAX_PLAYERS};
 use core::cmp::Ordering;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 const COMMUNITY_SIZE: usize = 5;
+/// One card is burned before each of the flop, turn, and river
+const NUM_BURNS: usize = 3;
 const DEF_SB: Currency = 5;
 const DEF_BB: Currency = 10;
+const DEF_MIN_CHIP: Currency = 1;
 
 type PidBA = (PlayerId, BetAction);
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum TableType {
     Cash,
+    /// Players that run out of chips are out, and finish in the order they went out
+    Tournament,
 }
 
 impl Default for TableType {
@@ -25,6 +32,125 @@ impl Default for TableType {
     }
 }
 
+/// Who, if anybody, puts in an ante before the blinds each hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnteStyle {
+    None,
+    /// Every player antes the given amount
+    PerPlayer(Currency),
+    /// The big blind antes one big blind on behalf of the whole table
+    BigBlind,
+}
+
+impl Default for AnteStyle {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Where each hand's deck comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeckMode {
+    /// A new deck every hand, shuffled with that hand's seed
+    PerHand,
+    /// The same deck every hand: a full deck shuffled with the given seed. For testing.
+    Fixed(DeckSeed),
+    /// Like a continuous shuffling machine: the cards left in the deck stay in the same order from
+    /// one hand to the next. Before each hand, every card that was dealt or burned last hand is put
+    /// back in at a random position, chosen with the hand's seed. So the next hand's cards depend
+    /// on the last hand's too, and the seed alone doesn't determine the deck. That's why no
+    /// [`LogItem::DeckCommitment`] is logged or seed revealed in this mode.
+    ContinuousShuffle,
+}
+
+impl Default for DeckMode {
+    fn default() -> Self {
+        Self::PerHand
+    }
+}
+
+/// Blinds that go up as a tournament goes on. Every `hands_per_level` hands the next level's small
+/// blind, big blind, and per-player ante (0 for none) take effect, and once past the last level it
+/// stays in effect.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindSchedule {
+    pub levels: Vec<(Currency, Currency, Currency)>,
+    pub hands_per_level: u32,
+}
+
+impl BlindSchedule {
+    /// The (small blind, big blind, ante) for the given hand, counting from 1
+    pub fn level_for(&self, hand_num: u32) -> (Currency, Currency, Currency) {
+        let level = (hand_num.saturating_sub(1) / self.hands_per_level) as usize;
+        self.levels[level.min(self.levels.len() - 1)]
+    }
+}
+
+/// A table's settings, as opposed to the state of the game being played at it. These can only be
+/// changed between hands; see [`GameState::set_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// Cash or tournament
+    pub table_type: TableType,
+    /// The small blind, obviously.
+    pub small_blind: Currency,
+    /// The big blind, obviously.
+    pub big_blind: Currency,
+    /// The smallest chip in play. Bets and raises are rounded down to a multiple of it, and split
+    /// pots are divided in multiples of it.
+    pub min_chip: Currency,
+    /// How many players may be seated at once, at most [`MAX_PLAYERS`]
+    pub max_players: usize,
+    /// How many players must be seated and not sitting out before [`GameState::tick`] deals a
+    /// hand, at least 2. E.g. 3 for a table that doesn't play heads up.
+    #[serde(default = "default_min_players_to_start")]
+    pub min_players_to_start: usize,
+    /// No limit, fixed limit, etc.
+    pub betting_limit: BettingLimit,
+    /// Who antes before the blinds each hand, and how much
+    pub ante: AnteStyle,
+    /// What the house takes from each pot
+    pub rake: RakeConfig,
+    /// The fewest chips a player may sit down with, if there's a minimum
+    pub min_buyin: Option<Currency>,
+    /// The most chips a player may sit down with, if there's a maximum
+    pub max_buyin: Option<Currency>,
+    /// How long a player has to act before [`GameState::tick_with_now`] checks or folds for them.
+    /// None for no limit.
+    pub action_timeout: Option<Millis>,
+    /// If the blinds go up over time, when and to what. Each hand's blinds and ante are taken
+    /// from here instead of from the other settings, which are updated to match.
+    pub blind_schedule: Option<BlindSchedule>,
+    /// A new deck each hand, or something else. See [`DeckMode`].
+    #[serde(default)]
+    pub deck_mode: DeckMode,
+}
+
+const fn default_min_players_to_start() -> usize {
+    2
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            table_type: Default::default(),
+            small_blind: DEF_SB,
+            big_blind: DEF_BB,
+            min_chip: DEF_MIN_CHIP,
+            max_players: MAX_PLAYERS,
+            min_players_to_start: default_min_players_to_start(),
+            betting_limit: Default::default(),
+            ante: Default::default(),
+            rake: Default::default(),
+            min_buyin: None,
+            max_buyin: None,
+            action_timeout: None,
+            blind_schedule: None,
+            deck_mode: Default::default(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BaseState {
     pub table_type: TableType,
@@ -42,8 +168,8 @@ impl std::fmt::Display for BaseState {
     }
 }
 
-impl From<&mut GameState> for BaseState {
-    fn from(gs: &mut GameState) -> Self {
+impl From<&GameState> for BaseState {
+    fn from(gs: &GameState) -> Self {
         let mut seats = [None; MAX_PLAYERS];
         let seats = {
             for (idx, p) in gs.players.players_iter(PlayerFilter::ALL) {
@@ -52,7 +178,7 @@ impl From<&mut GameState> for BaseState {
             seats
         };
         Self {
-            table_type: gs.table_type,
+            table_type: gs.config.table_type,
             seats,
         }
     }
@@ -64,6 +190,8 @@ pub enum State {
     NotStarted,
     Dealing,
     Street(Street),
+    /// Betting is over. Normally passed straight through, but when players decide whether to show
+    /// (see [`GameState::set_showdown_decisions`]), the hand waits here until they have.
     Showdown,
     EndOfHand,
 }
@@ -82,13 +210,123 @@ pub enum Street {
     River,
 }
 
+/// What a side system needs to know to offer insurance to an all-in player. See
+/// [`GameState::insurance_offer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InsuranceTerms {
+    /// The player's share of the pot on average over all possible runouts, from 0 to 1
+    pub equity: f64,
+    /// The total value of the pot, including all side pots
+    pub pot: Currency,
+    /// How many community cards are still to come
+    pub cards_to_come: usize,
+}
+
+/// What a player might want to know before deciding what to do. See
+/// [`GameState::decision_help`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionHelp {
+    /// See [`GameState::pot_odds`]. None if the player may check.
+    pub pot_odds: Option<f64>,
+    /// The player's estimated share of the pot against the other players still in the hand, were
+    /// they all holding random cards
+    pub equity: f64,
+    /// How many of the unseen cards would improve the player's hand if dealt next, per hand class.
+    /// See [`improvement_summary`].
+    pub outs: Vec<(HandClass, usize)>,
+}
+
+/// What a player can choose to do before it's their turn, for [`GameState::queue_action`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueuedAction {
+    /// Check if nobody has bet by the time it's the player's turn, else fold
+    CheckFold,
+    /// Call whatever the bet is by then, or check if there isn't one
+    CallAny,
+    /// Check. Can only be chosen while there's nothing to call, and if someone has bet by the time
+    /// it's the player's turn, they fold.
+    CheckOnly,
+}
+
+/// How a player has played at this table, over all the hands they've been dealt into. See
+/// [`GameState::player_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub hands_dealt: u32,
+    /// Hands in which the player voluntarily put money in preflop. Posting a blind or ante doesn't
+    /// count, but calling, betting, or raising does.
+    pub vpip_hands: u32,
+    /// What the player has won, less what they've put into pots. Negative if they're down.
+    pub net_won: Currency,
+}
+
+/// What the player whose turn it is may do, and for how much. See [`GameState::legal_actions`].
+///
+/// Amounts are totals for this betting round, the same as [`BetAction`]s are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegalActions {
+    /// Folding is only offered when facing a bet; otherwise the player should check
+    pub can_fold: bool,
+    pub can_check: bool,
+    /// How much more the player has to put in to call. 0 if they can check. This may be more than
+    /// their stack, in which case calling puts them all in.
+    pub call_amount: Currency,
+    /// Whether the player may bet or raise at all
+    pub can_raise: bool,
+    /// The smallest bet or raise allowed, or the player's whole stack if that's less
+    pub min_raise: Currency,
+    /// The largest bet or raise allowed: everything the player has
+    pub max_raise: Currency,
+}
+
+impl LegalActions {
+    /// The no-limit actions for a player with the given bet status and stack, given the current
+    /// bet and minimum raise. Doesn't check whose turn it is.
+    pub fn new(
+        bet_status: BetStatus,
+        stack: Currency,
+        current_bet: Currency,
+        min_raise: Currency,
+    ) -> Self {
+        let call_amount = bet_status.amount_to_call(current_bet);
+        let max_raise = stack
+            + match bet_status {
+                BetStatus::In(x) => x,
+                _ => 0,
+            };
+        Self {
+            can_fold: call_amount > 0,
+            can_check: call_amount == 0,
+            call_amount,
+            can_raise: stack > call_amount,
+            min_raise: min_raise.min(max_raise),
+            max_raise,
+        }
+    }
+}
+
+/// The layout of a serialized [`GameState`]. Bump this when a change to GameState would make
+/// state saved by an older version read back wrong, and teach [`GameState::migrate`] how to
+/// upgrade from the old version.
+pub const SCHEMA_VERSION: u16 = 1;
+
+/// How many runouts to sample when estimating equity for [`DecisionHelp`]
+const DECISION_HELP_TRIALS: usize = 500;
+
+/// The longest a chat message may be, in characters
+pub const MAX_CHAT_LEN: usize = 280;
+
 /// (Replaces GameInProgress) All the state constituting a poker game in progress
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct GameState {
+    /// The [`SCHEMA_VERSION`] this was created with. 0 for state saved before there was one.
+    #[serde(default)]
+    schema_version: u16,
     /// The state this Game is in ... as in what street or showdown or paused
     __state_dont_change_directly: State,
-    /// Cash. Maybe tourny in the future
-    pub table_type: TableType,
+    /// The table's settings
+    #[serde(default)]
+    config: GameConfig,
     /// The players seated at this table and their per-player info
     pub players: Players,
     /// The community cards
@@ -97,10 +335,13 @@ pub struct GameState {
     pot: Pot,
     /// The deck, obviously.
     deck: Deck,
-    /// The small blind, obviously.
-    small_blind: Currency,
-    /// The big blind, obviously.
-    big_blind: Currency,
+    /// The seed this hand's deck was shuffled with, if any. Secret until the hand is over.
+    #[serde(default)]
+    deck_seed: Option<DeckSeed>,
+    /// How many hands have been started at this table, including the current one. Never goes
+    /// down, so it identifies a hand at this table.
+    #[serde(default)]
+    hand_num: u32,
     /// The amount that each player is expected to match in order to make it to the end of the
     /// current betting round.
     __current_bet_dont_change_directly: Currency,
@@ -115,16 +356,155 @@ pub struct GameState {
     ///
     /// It's confusing. See <https://duckduckgo.com/?t=ffab&q=allin+raise+less+than+minraise>
     last_raiser: Option<PlayerId>,
+    /// How many times someone has raised this betting round, for capping raises in fixed limit
+    #[serde(default)]
+    raises_this_street: u8,
+    /// Whether to stop before dealing the rest of the board when players are all in, so that
+    /// insurance can be offered.
+    #[serde(default)]
+    insurance_offers: bool,
+    /// How many times to run out the rest of the board when players are all in before the river
+    #[serde(default = "default_run_count")]
+    run_count: u8,
+    /// What of the board there was when players went all in this hand, if it's to be run out more
+    /// than once
+    #[serde(default)]
+    runout_from: Option<[Option<Card>; COMMUNITY_SIZE]>,
+    /// The boards dealt for this hand after the first one, if it was run out more than once
+    #[serde(default)]
+    extra_boards: Vec<[Card; COMMUNITY_SIZE]>,
+    /// Whether players at showdown choose to show or muck, instead of winners showing and everyone
+    /// else mucking
+    #[serde(default)]
+    showdown_decisions: bool,
+    /// The players still to decide whether to show or muck at this showdown, next first
+    #[serde(default)]
+    showdown_order: Vec<PlayerId>,
+    /// The players that have shown their hand at this showdown
+    #[serde(default)]
+    shown: Vec<PlayerId>,
+    /// The players that have mucked their hand at this showdown
+    #[serde(default)]
+    mucked: Vec<PlayerId>,
+    /// The players that made it to showdown last hand, best hand first. None for those that
+    /// mucked.
+    #[serde(default)]
+    showdown_hands: Vec<(PlayerId, Option<FinalHandResult>)>,
+    /// Everything the house has raked at this table
+    #[serde(default)]
+    rake_collected: Currency,
+    /// Everyone's stats over all the hands they've been dealt into here
+    #[serde(default)]
+    player_stats: HashMap<PlayerId, PlayerStats>,
+    /// The stacks of those dealt into this hand as of the start of it, before antes and blinds
+    #[serde(default)]
+    starting_stacks: Vec<(PlayerId, Currency)>,
+    /// The players that have voluntarily put money in preflop this hand
+    #[serde(default)]
+    vpip_this_hand: Vec<PlayerId>,
+    /// What players have chosen to do when it's next their turn
+    #[serde(default)]
+    queued_actions: HashMap<PlayerId, QueuedAction>,
+    /// The latest time given to [`GameState::tick_with_now`]
+    #[serde(default)]
+    now: Option<Millis>,
+    /// When the player next to act runs out of time, if there's a time limit
+    #[serde(default)]
+    action_deadline: Option<Millis>,
+    /// Whether the game is frozen where it is, e.g. for a tournament break
+    #[serde(default)]
+    paused: bool,
     /// Logs since the the start of this hand and an archive of some previous hands
     logs: Log,
 }
 
+const fn default_run_count() -> u8 {
+    1
+}
+
+/// Change state saved before there was a [`SCHEMA_VERSION`] into the version 1 layout, which
+/// moved the table type and blinds into [`GameConfig`]. Everything added since then reads back
+/// with its default.
+fn upgrade_from_v0(value: &mut serde_json::Value) -> Result<(), GameError> {
+    let mut config = serde_json::to_value(GameConfig::default()).expect("GameConfig to JSON");
+    let state = value
+        .as_object_mut()
+        .ok_or_else(|| GameError::InvalidState("not an object".to_owned()))?;
+    for key in ["table_type", "small_blind", "big_blind"] {
+        match state.remove(key) {
+            Some(v) => config[key] = v,
+            None => return Err(GameError::InvalidState(format!("missing field `{key}`"))),
+        }
+    }
+    state.insert("config".to_owned(), config);
+    Ok(())
+}
+
 impl GameState {
+    /// Read back a game state serialized as JSON, upgrading it if it's from an older
+    /// [`SCHEMA_VERSION`]. Use this instead of deserializing directly, which can't tell that state
+    /// from a different version isn't laid out the way it expects.
+    pub fn migrate(json: &str) -> Result<Self, GameError> {
+        let invalid = |e: serde_json::Error| GameError::InvalidState(e.to_string());
+        let mut value: serde_json::Value = serde_json::from_str(json).map_err(invalid)?;
+        if !value.is_object() {
+            return Err(GameError::InvalidState("not an object".to_owned()));
+        }
+        let version = match value.get("schema_version") {
+            None => 0,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u16::try_from(v).ok())
+                .ok_or_else(|| GameError::InvalidState(format!("bad schema_version {v}")))?,
+        };
+        if version > SCHEMA_VERSION {
+            return Err(GameError::UnsupportedSchemaVersion(version));
+        }
+        if version == 0 {
+            upgrade_from_v0(&mut value)?;
+        }
+        value["schema_version"] = SCHEMA_VERSION.into();
+        serde_json::from_value(value).map_err(invalid)
+    }
+
+    /// This state in a compact binary format, for when the JSON is too big to keep sending. Read
+    /// it back with [`GameState::from_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Unable to encode GameState to bytes")
+    }
+
+    /// Read back a game state from [`GameState::to_bytes`]. Unlike JSON, state in the binary
+    /// format can't be upgraded from an older [`SCHEMA_VERSION`], since there's no telling what's
+    /// what without knowing the old layout.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GameError> {
+        let invalid = |e: bincode::Error| GameError::InvalidState(e.to_string());
+        // The version comes first, so it can be checked before trying to read the rest
+        let version: u16 = bincode::deserialize(bytes).map_err(invalid)?;
+        match version.cmp(&SCHEMA_VERSION) {
+            Ordering::Greater => Err(GameError::UnsupportedSchemaVersion(version)),
+            Ordering::Less => Err(GameError::InvalidState(format!(
+                "binary state from schema version {version} can't be upgraded"
+            ))),
+            Ordering::Equal => bincode::deserialize(bytes).map_err(invalid),
+        }
+    }
+
+    /// The [`SCHEMA_VERSION`] this state is laid out as
+    pub const fn schema_version(&self) -> u16 {
+        self.schema_version
+    }
+
+    /// Log items since the given sequence number, as the given player is allowed to see them.
+    ///
+    /// Pockets are hidden unless they belong to the given player. If the given player isn't seated
+    /// at this table, they're a spectator, and they additionally get to see the pockets of players
+    /// that have opted in with [`GameState::set_reveal_to_spectators`].
     pub fn filtered_changes_since(
         &self,
         seq: SeqNum,
         player_id: PlayerId,
     ) -> impl Iterator<Item = (SeqNum, LogItem)> + '_ {
+        let is_spectator = self.players.player_by_id(player_id).is_none();
         self.logs
             .items_since(seq)
             .map(move |(idx, item)| match item {
@@ -135,11 +515,30 @@ impl GameState {
                 | LogItem::NextToAct(_)
                 | LogItem::CurrentBetSet(_, _, _, _)
                 | LogItem::HandReveal(_, _)
+                | LogItem::HandResult(_, _, _, _)
+                | LogItem::Muck(_)
+                | LogItem::WonByFold(_)
                 | LogItem::Flop(_, _, _)
                 | LogItem::Turn(_)
-                | LogItem::River(_) => (idx, item),
+                | LogItem::River(_)
+                | LogItem::Deal(_)
+                | LogItem::ChipsAdded(_, _, _)
+                | LogItem::DeckCommitment(_)
+                | LogItem::BlindsChanged(_, _, _)
+                | LogItem::HandStart { .. }
+                | LogItem::ActionDeadline(_, _)
+                | LogItem::Chat(_, _)
+                | LogItem::Straddle(_, _)
+                | LogItem::ForcedAction(_, _)
+                | LogItem::AutoAction { .. }
+                | LogItem::AutoMuck { .. } => (idx, item),
                 LogItem::PocketDealt(pid, _pocket) => {
-                    if pid == player_id {
+                    let revealed = is_spectator
+                        && self
+                            .players
+                            .player_by_id(pid)
+                            .map_or(false, |p| p.reveal_to_spectators);
+                    if pid == player_id || revealed {
                         (idx, item)
                     } else {
                         (idx, LogItem::PocketDealt(pid, None))
@@ -148,6 +547,41 @@ impl GameState {
             })
     }
 
+    /// What a client that last saw the given sequence number needs to catch up, as the given
+    /// player is allowed to see it, like [`GameState::filtered_changes_since`].
+    ///
+    /// If it's been away since before this hand started, it gets this hand's logs from the
+    /// beginning instead, which start with a [`LogItem::NewBaseState`]. That's less to get through
+    /// than every hand it missed, and it can't miss anything that's since been dropped from the
+    /// archive.
+    pub fn resync_payload(
+        &self,
+        last_seen_seq: SeqNum,
+        player_id: PlayerId,
+    ) -> Vec<(SeqNum, LogItem)> {
+        let since = match self.logs.first_active_seq() {
+            Some(first) if last_seen_seq + 1 < first => first - 1,
+            _ => last_seen_seq,
+        };
+        self.filtered_changes_since(since, player_id).collect()
+    }
+
+    /// Log items since the given sequence number, as someone with no seat at this table and no
+    /// special privileges is allowed to see them, e.g. for broadcasting a table.
+    ///
+    /// Every pocket is hidden as it's dealt, even those of players that opted in with
+    /// [`GameState::set_reveal_to_spectators`], so cards are only ever seen when they're revealed
+    /// at showdown.
+    pub fn spectator_changes_since(
+        &self,
+        seq: SeqNum,
+    ) -> impl Iterator<Item = (SeqNum, LogItem)> + '_ {
+        self.logs.items_since(seq).map(|(idx, item)| match item {
+            LogItem::PocketDealt(pid, Some(_)) => (idx, LogItem::PocketDealt(pid, None)),
+            _ => (idx, item),
+        })
+    }
+
     //#[cfg(test)]
     //pub(crate) fn changes_since(
     //    &self,
@@ -156,10 +590,463 @@ impl GameState {
     //    self.logs.items_since(seq)
     //}
 
+    /// The log items that would take a client that has seen this state to the other state, e.g.
+    /// to catch up a client after the server reloads a saved game, or to see what a bot did to a
+    /// clone.
+    ///
+    /// Compares seats, stacks, the state, tokens, community cards, the current bet, bets, and who
+    /// is next to act. If the seats changed, or a stack changed in a way that a bet doesn't
+    /// explain, a [`LogItem::NewBaseState`] with the other state's seats comes first. Pockets
+    /// aren't compared.
+    pub fn diff(&self, other: &GameState) -> Vec<LogItem> {
+        let mut items = vec![];
+        let committed = |bs: BetStatus| match bs {
+            BetStatus::In(x) | BetStatus::AllIn(x) => x,
+            BetStatus::Waiting | BetStatus::Folded => 0,
+        };
+        // Whether the other player no longer looks like they could have gotten there from the
+        // player in this state by betting
+        let rebased = |old: &Player, new: &Player| {
+            old.id != new.id
+                || match new.bet_status {
+                    BetStatus::Waiting | BetStatus::Folded => old.stack != new.stack,
+                    BetStatus::In(_) | BetStatus::AllIn(_) => {
+                        old.stack - new.stack
+                            != committed(new.bet_status) - committed(old.bet_status)
+                    }
+                }
+        };
+        let resync = self.config.table_type != other.config.table_type
+            || self
+                .players
+                .players
+                .iter()
+                .zip(other.players.players.iter())
+                .any(|(old, new)| match (old, new) {
+                    (None, None) => false,
+                    (Some(old), Some(new)) => rebased(old, new),
+                    _ => true,
+                });
+        if resync {
+            items.push(LogItem::NewBaseState(Box::new(other.into())));
+        }
+        if self.state() != other.state() {
+            items.push(LogItem::StateChange(self.state(), other.state()));
+        }
+        let tokens = |gs: &GameState| {
+            (
+                gs.players.token_dealer,
+                gs.players.token_sb,
+                gs.players.token_bb,
+            )
+        };
+        if tokens(self) != tokens(other) {
+            let (btn, sb, bb) = tokens(other);
+            items.push(LogItem::TokensSet(btn, sb, bb));
+        }
+        // After a resync, the client has forgotten the board
+        let community = if resync {
+            [None; COMMUNITY_SIZE]
+        } else {
+            self.community
+        };
+        if let (None, [Some(c1), Some(c2), Some(c3), ..]) = (community[0], other.community) {
+            items.push(LogItem::Flop(c1, c2, c3));
+        }
+        if let (None, Some(c)) = (community[3], other.community[3]) {
+            items.push(LogItem::Turn(c));
+        }
+        if let (None, Some(c)) = (community[4], other.community[4]) {
+            items.push(LogItem::River(c));
+        }
+        if (self.current_bet(), self.min_raise()) != (other.current_bet(), other.min_raise()) {
+            items.push(LogItem::CurrentBetSet(
+                self.current_bet(),
+                other.current_bet(),
+                self.min_raise(),
+                other.min_raise(),
+            ));
+        }
+        for (seat, new) in other.players.players_iter(PlayerFilter::ALL) {
+            let old_status = match (resync, self.players.player_at(seat)) {
+                (false, Some(old)) => old.bet_status,
+                _ => BetStatus::Waiting,
+            };
+            if old_status == new.bet_status {
+                continue;
+            }
+            let action = match new.bet_status {
+                BetStatus::Waiting => continue,
+                BetStatus::Folded => BetAction::Fold,
+                BetStatus::AllIn(x) => BetAction::AllIn(x),
+                BetStatus::In(0) => BetAction::Check,
+                BetStatus::In(x) if x == self.current_bet() => BetAction::Call(x),
+                BetStatus::In(x) if self.current_bet() == 0 => BetAction::Bet(x),
+                BetStatus::In(x) => BetAction::Raise(x),
+            };
+            items.push(pot::LogItem::Bet(new.id, action).into());
+        }
+        if let Some((seat, _)) = other.nta() {
+            if self.nta().map(|(s, _)| s) != Some(seat) {
+                items.push(LogItem::NextToAct(seat));
+            }
+        }
+        items
+    }
+
+    /// Rebuild a table's state from the given base state and the log items after it, up to and
+    /// including the one with the given sequence number. Meant for debugging, e.g. to see where a
+    /// client's idea of the table went wrong.
+    ///
+    /// Seats, stacks, bets, the pot, pockets that were logged, tokens, the board, the current bet,
+    /// and the state are rebuilt. Only who's next to act is known, not who else still has to.
+    /// The table's settings aren't logged, so they're the defaults. If the hand was run out more
+    /// than once, the board is the last one dealt.
+    pub fn replay(base: &BaseState, logs: &[(SeqNum, LogItem)], up_to: SeqNum) -> GameState {
+        let mut gs = GameState::default();
+        gs.replay_base_state(base);
+        for (_, item) in logs.iter().take_while(|(seq, _)| *seq <= up_to) {
+            gs.replay_one(item);
+        }
+        gs
+    }
+
+    fn replay_base_state(&mut self, base: &BaseState) {
+        self.config.table_type = base.table_type;
+        self.players.players = base.seats;
+        self.players.need_bets_from.clear();
+        self.community = [None; COMMUNITY_SIZE];
+        self.pot = Default::default();
+    }
+
+    fn replay_one(&mut self, item: &LogItem) {
+        match item {
+            LogItem::NewBaseState(bs) => self.replay_base_state(bs),
+            LogItem::StateChange(_, new) => {
+                if matches!(new, State::Showdown | State::EndOfHand) {
+                    self.players.need_bets_from.clear();
+                }
+                self.change_state(*new);
+            }
+            LogItem::TokensSet(btn, sb, bb) => {
+                self.players.token_dealer = *btn;
+                self.players.token_sb = *sb;
+                self.players.token_bb = *bb;
+            }
+            LogItem::NextToAct(seat) => self.players.need_bets_from = vec![*seat],
+            LogItem::CurrentBetSet(_, cb, _, mr) => self.set_current_bet(*cb, *mr),
+            LogItem::PocketDealt(player_id, Some(pocket)) => {
+                if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                    p.pocket = Some(*pocket);
+                }
+            }
+            LogItem::HandReveal(player_id, [Some(c1), Some(c2)]) => {
+                if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                    p.pocket = Some([*c1, *c2]);
+                }
+            }
+            LogItem::Flop(c1, c2, c3) => {
+                self.community = [Some(*c1), Some(*c2), Some(*c3), None, None];
+            }
+            LogItem::Turn(c) => self.community[3] = Some(*c),
+            LogItem::River(c) => self.community[4] = Some(*c),
+            LogItem::Deal(stacks) => {
+                for (player_id, stack) in stacks {
+                    if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                        p.stack = *stack;
+                    }
+                }
+            }
+            LogItem::ChipsAdded(player_id, _, stack) => {
+                if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                    p.stack = *stack;
+                }
+            }
+            LogItem::BlindsChanged(sb, bb, ante) => {
+                self.config.small_blind = *sb;
+                self.config.big_blind = *bb;
+                self.config.ante = match ante {
+                    0 => AnteStyle::None,
+                    ante => AnteStyle::PerPlayer(*ante),
+                };
+            }
+            LogItem::Pot(pot_item) => match pot_item {
+                pot::LogItem::Bet(player_id, action) => {
+                    if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                        let bet_status: BetStatus = (*action).into();
+                        let old_wager = match p.bet_status {
+                            BetStatus::In(x) | BetStatus::AllIn(x) => x,
+                            BetStatus::Folded | BetStatus::Waiting => 0,
+                        };
+                        if let BetStatus::In(new_wager) | BetStatus::AllIn(new_wager) = bet_status {
+                            p.stack += old_wager - new_wager;
+                        }
+                        p.bet_status = bet_status;
+                    }
+                    self.pot.bet(*player_id, *action);
+                }
+                pot::LogItem::RoundEnd(_) => {
+                    self.pot.finalize_round();
+                    for (_, p) in self.players.players_iter_mut(PlayerFilter::MAY_BET) {
+                        p.bet_status = BetStatus::Waiting;
+                    }
+                }
+                pot::LogItem::Payouts(None, winnings) => {
+                    self.pot = Default::default();
+                    for (player_id, amount) in winnings {
+                        if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                            p.stack += amount;
+                        }
+                    }
+                }
+                pot::LogItem::Rake(amount) => self.rake_collected += amount,
+                pot::LogItem::UncalledBetReturned(player_id, amount) => {
+                    self.pot.return_uncalled(*player_id);
+                    if let Some(p) = self.players.player_by_id_mut(*player_id) {
+                        p.return_uncalled(*amount);
+                    }
+                }
+                pot::LogItem::Payouts(Some(_), _)
+                | pot::LogItem::BetsSorted(_)
+                | pot::LogItem::EntireStakeInPot(_, _, _)
+                | pot::LogItem::PartialStakeInPot(_, _, _, _)
+                | pot::LogItem::NewPotCreated(_, _, _)
+                | pot::LogItem::PotStructure(_)
+                | pot::LogItem::BetsCollected(_) => {}
+            },
+            LogItem::PocketDealt(_, None)
+            | LogItem::HandReveal(_, _)
+            | LogItem::HandResult(_, _, _, _)
+            | LogItem::Muck(_)
+            | LogItem::WonByFold(_)
+            | LogItem::DeckCommitment(_)
+            | LogItem::HandStart { .. }
+            | LogItem::ActionDeadline(_, _)
+            | LogItem::Chat(_, _)
+            | LogItem::Straddle(_, _)
+            | LogItem::ForcedAction(_, _)
+            | LogItem::AutoAction { .. }
+            | LogItem::AutoMuck { .. } => {}
+        }
+    }
+
     pub fn pot_total_value(&self) -> Currency {
         self.pot.total_value()
     }
 
+    /// The main pot and any side pots from past betting rounds, each with the players that can win
+    /// it. See [`Pot::pot_summaries`]. Folded players are left out, as they can't win anything.
+    pub fn pot_summaries(&self) -> Vec<(Currency, Vec<PlayerId>)> {
+        self.pot
+            .pot_summaries()
+            .into_iter()
+            .map(|(value, players)| {
+                let eligible = players
+                    .into_iter()
+                    .filter(|pid| {
+                        self.players
+                            .player_by_id(*pid)
+                            .map_or(false, |p| !p.is_folded())
+                    })
+                    .collect();
+                (value, eligible)
+            })
+            .collect()
+    }
+
+    /// Every chip at the table: all players' stacks plus everything in the pot. Betting and
+    /// paying out only move chips between the two, so this shouldn't change during a hand.
+    pub fn total_chips_in_play(&self) -> Currency {
+        self.players
+            .players_iter(PlayerFilter::ALL)
+            .map(|(_, p)| p.stack)
+            .sum::<Currency>()
+            + self.pot_total_value()
+    }
+
+    /// The amount actually handed out to players at the end of the most recently finished hand, or
+    /// `None` if no hand has finished yet.
+    ///
+    /// This is what players won, which isn't necessarily what [`GameState::pot_total_value`] said
+    /// the pot was worth before it was paid out.
+    pub fn last_hand_net_pot(&self) -> Option<Currency> {
+        self.logs
+            .items_since(0)
+            .filter_map(|(_, item)| match item {
+                LogItem::Pot(pot::LogItem::Payouts(None, payouts)) => Some(payouts.values().sum()),
+                _ => None,
+            })
+            .last()
+    }
+
+    /// A short plain-English description of the table from the given player's point of view,
+    /// e.g. for screen readers. Lives here so that every client uses the same wording.
+    pub fn accessible_state(&self, viewer: PlayerId) -> String {
+        let player = match self.players.player_by_id(viewer) {
+            None => return "You are not seated at this table.".to_string(),
+            Some(p) => p,
+        };
+        let behind = format!("You have {} behind.", player.stack);
+        if !matches!(self.state(), State::Street(_)) {
+            return format!("Waiting for the next hand. {behind}");
+        }
+        let pot = format!("The pot is {}.", self.pot_total_value());
+        match self.nta() {
+            Some((_, nta)) if nta.id == viewer => {
+                let to_call = self.call_amount(player);
+                if to_call > 0 {
+                    format!("It's your turn. {pot} The bet to you is {to_call}. {behind}")
+                } else {
+                    format!("It's your turn. {pot} You can check. {behind}")
+                }
+            }
+            Some((seat, _)) => format!("Waiting for seat {seat} to act. {pot} {behind}"),
+            None => format!("{pot} {behind}"),
+        }
+    }
+
+    /// How much more the given player needs to put in to match the current bet.
+    fn call_amount(&self, player: &Player) -> Currency {
+        player.bet_status.amount_to_call(self.current_bet())
+    }
+
+    /// Whether it's the given player's turn to act. Nobody's turn between hands, even if
+    /// [`GameState::nta`] still remembers who was left to act when the hand ended.
+    pub fn is_players_turn(&self, player_id: PlayerId) -> bool {
+        matches!(self.state(), State::Street(_))
+            && self.nta().map_or(false, |(_, p)| p.id == player_id)
+    }
+
+    /// Whether it's the given player's turn and they may check, as nobody has bet more than them.
+    pub fn can_check(&self, player_id: PlayerId) -> bool {
+        matches!(self.state(), State::Street(_))
+            && matches!(self.nta(), Some((_, p)) if p.id == player_id && self.call_amount(&p) == 0)
+    }
+
+    /// Whether it's the given player's turn and they're facing a bet they could call.
+    pub fn can_call(&self, player_id: PlayerId) -> bool {
+        matches!(self.state(), State::Street(_))
+            && matches!(self.nta(), Some((_, p)) if p.id == player_id && self.call_amount(&p) > 0)
+    }
+
+    /// If it's the given player's turn and they have a bet to call, the fraction of the pot they'd
+    /// end up having put in for this call: what they have to call divided by the pot after they
+    /// call. This is the equity they need for calling to break even. A player that can only call
+    /// all in for less is only calling what they have. None if they may check.
+    pub fn pot_odds(&self, player_id: PlayerId) -> Option<f64> {
+        let (_, player) = self.nta().filter(|(_, p)| p.id == player_id)?;
+        let to_call = self.call_amount(&player).min(player.stack);
+        if to_call == 0 {
+            return None;
+        }
+        Some(to_call as f64 / (self.pot_total_value() + to_call) as f64)
+    }
+
+    /// Pot odds, equity, and outs for the given player, if it's their turn. Only the player's own
+    /// cards are used, so this doesn't leak anything about the other players' hands.
+    pub fn decision_help(&self, player_id: PlayerId) -> Option<DecisionHelp> {
+        self.nta().filter(|(_, p)| p.id == player_id)?;
+        let pot_odds = self.pot_odds(player_id);
+        let pocket = self.players.player_by_id(player_id)?.pocket?;
+        let num_opponents = self
+            .players
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .count()
+            - 1;
+        let equity = equity(
+            pocket,
+            &self.community,
+            num_opponents,
+            DECISION_HELP_TRIALS,
+            DeckSeed::default(),
+        );
+        let board: Vec<Card> = self.community.iter().flatten().copied().collect();
+        Some(DecisionHelp {
+            pot_odds,
+            equity,
+            outs: improvement_summary(pocket, &board),
+        })
+    }
+
+    /// What the given player may do, if it's their turn. Under fixed limit, a bet or raise can
+    /// only be for exactly one increment and not after the street's raises are capped.
+    pub fn legal_actions(&self, player_id: PlayerId) -> Option<LegalActions> {
+        if !self.is_players_turn(player_id) {
+            return None;
+        }
+        let (_, player) = self.nta()?;
+        let mut la = LegalActions::new(
+            player.bet_status,
+            player.stack,
+            self.current_bet(),
+            self.min_raise(),
+        );
+        if let BettingLimit::FixedLimit {
+            small_bet,
+            big_bet,
+            cap,
+        } = self.config.betting_limit
+        {
+            let increment = match self.state() {
+                State::Street(Street::PreFlop | Street::Flop) => small_bet,
+                _ => big_bet,
+            };
+            let to = (self.current_bet() + increment).min(la.max_raise);
+            la.min_raise = to;
+            la.max_raise = to;
+            la.can_raise = la.can_raise && self.raises_this_street < cap;
+        }
+        Some(la)
+    }
+
+    /// If it's the given player's turn and they may bet or raise, the smallest and largest totals
+    /// they may bet or raise to this betting round. The largest counts what they already have in
+    /// front of them, and the smallest is all in if they can't afford a full minimum raise.
+    pub fn bet_range(&self, player_id: PlayerId) -> Option<(Currency, Currency)> {
+        self.legal_actions(player_id)
+            .filter(|la| la.can_raise)
+            .map(|la| (la.min_raise, la.max_raise))
+    }
+
+    /// Choose whether spectators get to see this player's pocket as soon as it is dealt. Seated
+    /// opponents never do.
+    pub fn set_reveal_to_spectators(
+        &mut self,
+        player_id: PlayerId,
+        reveal: bool,
+    ) -> Result<(), GameError> {
+        self.players
+            .player_by_id_mut(player_id)
+            .ok_or(GameError::PlayerNotFound)?
+            .reveal_to_spectators = reveal;
+        Ok(())
+    }
+
+    /// Have the given seated player say something to the table. Control characters are dropped and
+    /// the ends trimmed, and what's left must be 1 to [`MAX_CHAT_LEN`] characters long.
+    pub fn chat(&mut self, player_id: PlayerId, text: &str) -> Result<(), GameError> {
+        if self.players.player_by_id(player_id).is_none() {
+            return Err(GameError::PlayerNotFound);
+        }
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        let text = text.trim();
+        if text.is_empty() || text.chars().count() > MAX_CHAT_LEN {
+            return Err(GameError::InvalidChat);
+        }
+        self.logs.push(LogItem::Chat(player_id, text.to_owned()));
+        Ok(())
+    }
+
+    /// The seat the given player is sitting in, if they are at this table.
+    pub fn seat_of(&self, player_id: PlayerId) -> Option<SeatIdx> {
+        self.players.seat_of(player_id)
+    }
+
+    /// The player sitting in the given seat, if any.
+    pub fn player_at(&self, seat: SeatIdx) -> Option<&Player> {
+        self.players.player_at(seat)
+    }
+
     pub fn nta(&self) -> Option<(SeatIdx, Player)> {
         match self.players.need_bets_from.is_empty() {
             false => {
@@ -175,17 +1062,36 @@ impl GameState {
 impl Default for GameState {
     fn default() -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
             __state_dont_change_directly: Default::default(),
-            table_type: Default::default(),
+            config: Default::default(),
             players: Default::default(),
             community: [None; COMMUNITY_SIZE],
             pot: Default::default(),
             deck: Default::default(),
-            small_blind: DEF_SB,
-            big_blind: DEF_BB,
+            deck_seed: None,
+            hand_num: 0,
             __current_bet_dont_change_directly: DEF_BB,
             __min_raise_dont_change_directly: 2 * DEF_BB,
             last_raiser: None,
+            raises_this_street: 0,
+            insurance_offers: false,
+            run_count: default_run_count(),
+            runout_from: None,
+            extra_boards: vec![],
+            showdown_decisions: false,
+            showdown_order: vec![],
+            shown: vec![],
+            mucked: vec![],
+            showdown_hands: vec![],
+            rake_collected: 0,
+            player_stats: HashMap::new(),
+            starting_stacks: vec![],
+            vpip_this_hand: vec![],
+            queued_actions: HashMap::new(),
+            now: None,
+            action_deadline: None,
+            paused: false,
             logs: Default::default(),
         }
     }
@@ -197,7 +1103,7 @@ impl GameState {
     }
 
     pub fn player_calls(&mut self, player_id: PlayerId) -> Result<(), GameError> {
-        self.player_action(player_id, BetAction::Call(self.current_bet()))
+        self.player_action(player_id, BetAction::call(self.current_bet()))
     }
 
     pub fn player_checks(&mut self, player_id: PlayerId) -> Result<(), GameError> {
@@ -212,21 +1118,144 @@ impl GameState {
         self.player_action(player_id, BetAction::Raise(val))
     }
 
+    /// Have the player left of the BB straddle: post a voluntary blind of the given amount, at
+    /// least twice the BB. This can only be done preflop before anyone has acted, else
+    /// [`GameError::InvalidStraddle`], and the amount must be less than their stack, else
+    /// [`GameError::InvalidBet`].
+    ///
+    /// The straddle becomes the bet to call and acts like a third blind: the next raise must be to
+    /// at least twice it, and the straddler acts last preflop.
+    pub fn post_straddle(
+        &mut self,
+        player_id: PlayerId,
+        amount: Currency,
+    ) -> Result<(), GameError> {
+        let seat = self
+            .players
+            .seat_of(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        let left_of_bb = self
+            .players
+            .betting_players_iter_after(self.players.token_bb)
+            .next()
+            .map(|(seat, _)| seat);
+        if self.paused {
+            return Err(GameError::GamePaused);
+        }
+        // The first to act preflop hasn't acted yet, so no one has
+        if !matches!(self.state(), State::Street(Street::PreFlop))
+            || left_of_bb != Some(seat)
+            || self.nta().map(|(nta, _)| nta) != Some(seat)
+            || self.players.players[seat].unwrap().bet_status != BetStatus::Waiting
+        {
+            return Err(GameError::InvalidStraddle);
+        }
+        // Unwrap is fine: just found them
+        let player = self.players.player_by_id_mut(player_id).unwrap();
+        if amount < self.config.big_blind * 2 || amount >= player.stack {
+            return Err(GameError::InvalidBet);
+        }
+        let bet = player.bet(BetAction::Bet(amount))?;
+        self.logs.push(LogItem::Straddle(player_id, amount));
+        let mut pot_logs = vec![];
+        pot_logs.append(&mut self.pot.bet(player_id, bet));
+        self.logs.extend(pot_logs.into_iter().map(|l| l.into()));
+        self.set_current_bet(amount, amount * 2);
+        self.players.need_bets_from = self
+            .players
+            .betting_players_iter_after(seat)
+            .map(|(i, _)| i)
+            .take(self.players.players_iter(PlayerFilter::MAY_BET).count())
+            .collect();
+        self.players.need_bets_from.reverse();
+        self.logs.push(LogItem::NextToAct(self.nta().unwrap().0));
+        self.start_action_clock();
+        self.take_queued_action()
+    }
+
+    /// Show the given player's hand at showdown. They must be the next to decide (see
+    /// [`GameState::set_showdown_decisions`]), else [`GameError::OutOfTurn`].
+    pub fn player_shows(&mut self, player_id: PlayerId) -> Result<(), GameError> {
+        self.showdown_decision(player_id, true)
+    }
+
+    /// Muck the given player's hand at showdown, giving up their claim to the pot without anyone
+    /// seeing it. They must be the next to decide, as for [`GameState::player_shows`].
+    pub fn player_mucks(&mut self, player_id: PlayerId) -> Result<(), GameError> {
+        self.showdown_decision(player_id, false)
+    }
+
+    fn showdown_decision(&mut self, player_id: PlayerId, shows: bool) -> Result<(), GameError> {
+        let player = self
+            .players
+            .player_by_id(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        if self.paused {
+            return Err(GameError::GamePaused);
+        }
+        if self.showdown_next() != Some(player_id) {
+            return Err(GameError::OutOfTurn);
+        }
+        self.showdown_order.remove(0);
+        if shows {
+            // Unwrap is fine: everyone at showdown was dealt in
+            let cards = player.pocket.unwrap();
+            self.shown.push(player_id);
+            self.logs.push(LogItem::HandReveal(
+                player_id,
+                [Some(cards[0]), Some(cards[1])],
+            ));
+        } else {
+            self.mucked.push(player_id);
+            self.logs.push(LogItem::Muck(player_id));
+        }
+        self.finish_showdown_decisions_if_done()
+    }
+
     pub fn player_action(
         &mut self,
         player_id: PlayerId,
         bet_action: BetAction,
     ) -> Result<(), GameError> {
+        // The rake leaves play, but it doesn't disappear
+        #[cfg(debug_assertions)]
+        let chips_before = self.total_chips_in_play() + self.rake_collected;
+        let already_in = self
+            .players
+            .player_by_id(player_id)
+            .map_or(0, |p| match p.bet_status {
+                BetStatus::In(x) | BetStatus::AllIn(x) => x,
+                BetStatus::Folded | BetStatus::Waiting => 0,
+            });
+        // Snap bets and raises to the smallest chip in play. Only what players choose: the blinds
+        // are what they're configured as, whatever the min chip.
+        let bet_action = match bet_action {
+            BetAction::Bet(x) => BetAction::Bet(self.round_to_min_chip(x, self.current_bet())),
+            BetAction::Raise(x) => BetAction::Raise(self.round_to_min_chip(x, self.min_raise())),
+            _ => bet_action,
+        };
         let bet = self.bet(player_id, bet_action)?;
+        self.queued_actions.remove(&player_id);
+        // The BB checking their option is recorded as a bet of what they already have in, which
+        // isn't putting money in
+        if matches!(self.state(), State::Street(Street::PreFlop))
+            && bet.amount().unwrap_or(0) > already_in
+            && !self.vpip_this_hand.contains(&player_id)
+        {
+            self.vpip_this_hand.push(player_id);
+            self.player_stats.entry(player_id).or_default().vpip_hands += 1;
+        }
         // based on the bet's value, update current_bet and min_raise if needed
-        let bet_value = match bet {
-            BetAction::Check | BetAction::Fold => 0,
-            BetAction::Call(v) | BetAction::Bet(v) | BetAction::Raise(v) | BetAction::AllIn(v) => v,
-        };
+        let bet_value = bet.amount().unwrap_or(0);
         if bet_value > self.current_bet() {
             let old_cb = self.current_bet();
             let cb = bet_value;
-            let mr = cb + (cb - old_cb);
+            // An all in for less than a full raise doesn't change what the next raise must be
+            let mr = if cb < self.min_raise() {
+                self.min_raise()
+            } else {
+                cb + (cb - old_cb)
+            };
             self.set_current_bet(cb, mr);
         }
         let mut pot_logs = vec![];
@@ -240,19 +1269,403 @@ impl GameState {
             == 1
         {
             self.finalize_hand()?;
-        } else if self.players.need_bets_from.is_empty() {
-            while self.players.need_bets_from.is_empty() && !matches!(self.state(), State::Showdown)
-            {
-                let next_state = self.advance_street()?;
-                self.change_state(next_state);
-            }
-            if matches!(self.state(), State::Showdown) {
-                self.finalize_hand()?;
-            }
+        } else if self.players.need_bets_from.is_empty()
+            && !(self.insurance_offers && self.is_all_in_before_river())
+        {
+            self.advance_until_action()?;
         }
         if !self.players.need_bets_from.is_empty() {
             self.logs.push(LogItem::NextToAct(self.nta().unwrap().0));
+            self.start_action_clock();
+            self.take_queued_action()?;
+        }
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            chips_before,
+            self.total_chips_in_play() + self.rake_collected
+        );
+        Ok(())
+    }
+
+    /// Deal the next street(s) until someone needs to act, or all the way to the end of the hand if
+    /// nobody can.
+    fn advance_until_action(&mut self) -> Result<(), GameError> {
+        // Remember the board as of the all in, as the player with chips behind (if any) is still
+        // asked to act on the later streets
+        if self.run_count > 1 && self.runout_from.is_none() && self.is_all_in_before_river() {
+            self.runout_from = Some(self.community);
+        }
+        while self.players.need_bets_from.is_empty() && !matches!(self.state(), State::Showdown) {
+            let next_state = self.advance_street()?;
+            self.change_state(next_state);
+        }
+        if matches!(self.state(), State::Showdown) {
+            if let Some(known) = self.runout_from.take() {
+                self.run_out_extra_boards(known)?;
+            }
+            if self.showdown_decisions {
+                self.start_showdown_decisions()?;
+            } else {
+                self.finalize_hand()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Starting from the given partial board, deal the rest of it again once for every extra time
+    /// the hand is to be run out, for as many times as the deck has cards for. Each street is
+    /// burned for and logged just like on the first board.
+    fn run_out_extra_boards(
+        &mut self,
+        known: [Option<Card>; COMMUNITY_SIZE],
+    ) -> Result<(), GameError> {
+        let streets: Vec<std::ops::Range<usize>> = [0..3, 3..4, 4..5]
+            .into_iter()
+            .filter(|street| known[street.start].is_none())
+            .collect();
+        let per_board = known.iter().filter(|c| c.is_none()).count() + streets.len();
+        for _ in 1..self.run_count {
+            if self.deck.len() < per_board {
+                break;
+            }
+            let mut board = known;
+            for street in streets.iter().cloned() {
+                self.deck.burn();
+                for idx in street.clone() {
+                    board[idx] = Some(self.deck.draw()?);
+                }
+                self.logs.push(match street.start {
+                    0 => LogItem::Flop(board[0].unwrap(), board[1].unwrap(), board[2].unwrap()),
+                    3 => LogItem::Turn(board[3].unwrap()),
+                    _ => LogItem::River(board[4].unwrap()),
+                });
+            }
+            self.extra_boards.push(board.map(Option::unwrap));
+        }
+        Ok(())
+    }
+
+    /// Whether betting is over for the hand because at most one player isn't all in, yet there are
+    /// still community cards to come.
+    fn is_all_in_before_river(&self) -> bool {
+        matches!(self.state(), State::Street(street) if street != Street::River)
+            && self.players.need_bets_from.is_empty()
+            && self
+                .players
+                .players_iter(PlayerFilter::POT_ELIGIBLE)
+                .count()
+                > 1
+            && self.players.players_iter(PlayerFilter::MAY_BET).count() <= 1
+    }
+
+    /// Choose whether to stop and offer insurance when players are all in before the river. When
+    /// on, the rest of the board isn't dealt right away: the hand waits in its current street so
+    /// that [`GameState::insurance_offer`] can be asked about, and the next [`GameState::tick`]
+    /// carries on dealing.
+    pub fn set_insurance_offers(&mut self, enabled: bool) {
+        self.insurance_offers = enabled;
+    }
+
+    /// Choose how many times to run out the rest of the board when players are all in before the
+    /// river, e.g. 2 to "run it twice." Each run gets its own board dealt from the same deck, and
+    /// every pot is split evenly between the boards. If the deck runs short, the hand is run out
+    /// as many times as it has cards for. Stays in effect until changed. Must be at least 1, else
+    /// [`GameError::InvalidConfig`].
+    pub fn set_run_count(&mut self, run_count: u8) -> Result<(), GameError> {
+        if run_count == 0 {
+            return Err(GameError::InvalidConfig);
+        }
+        self.run_count = run_count;
+        Ok(())
+    }
+
+    /// The boards the hand was run out on after the first one, which is in `community`. Empty
+    /// unless the hand was run out more than once (see [`GameState::set_run_count`]), or once the
+    /// next hand starts.
+    pub fn extra_boards(&self) -> &[[Card; COMMUNITY_SIZE]] {
+        &self.extra_boards
+    }
+
+    /// Choose whether players at showdown decide for themselves to show or muck. When on, the hand
+    /// waits in [`State::Showdown`] for each player still in it to call [`GameState::player_shows`]
+    /// or [`GameState::player_mucks`], in turn: the last player to bet or raise on the river
+    /// first, or the first player clockwise from the button if nobody did, and then clockwise
+    /// from them. Once only one player hasn't mucked, they win without having to decide. When off,
+    /// only the players that win something show.
+    pub fn set_showdown_decisions(&mut self, enabled: bool) {
+        self.showdown_decisions = enabled;
+    }
+
+    /// The player that needs to decide whether to show or muck next, if the hand is waiting for
+    /// showdown decisions (see [`GameState::set_showdown_decisions`]).
+    pub fn showdown_next(&self) -> Option<PlayerId> {
+        if !matches!(self.state(), State::Showdown) {
+            return None;
+        }
+        self.showdown_order.first().copied()
+    }
+
+    /// Work out the order players decide to show or muck in, then wait for them to.
+    fn start_showdown_decisions(&mut self) -> Result<(), GameError> {
+        let mut order: Vec<PlayerId> = self
+            .players
+            .pot_eligible_seats_from_dealer()
+            .into_iter()
+            .filter_map(|seat| self.players.player_at(seat).map(|p| p.id))
+            .collect();
+        if let Some(first) = self
+            .last_raiser
+            .and_then(|aggressor| order.iter().position(|pid| *pid == aggressor))
+        {
+            order.rotate_left(first);
+        }
+        self.showdown_order = order;
+        self.finish_showdown_decisions_if_done()
+    }
+
+    /// Finish the hand if every player at showdown has decided, or only one hasn't mucked.
+    fn finish_showdown_decisions_if_done(&mut self) -> Result<(), GameError> {
+        if self.showdown_order.is_empty() || self.showdown_order.len() + self.shown.len() == 1 {
+            self.showdown_order.clear();
+            self.finalize_hand()?;
+        }
+        Ok(())
+    }
+
+    /// The terms on which a side system could insure the given all-in player, if the hand is
+    /// waiting to deal the rest of the board (see [`GameState::set_insurance_offers`]). Core only
+    /// computes the player's equity; it doesn't handle any money.
+    pub fn insurance_offer(&self, player_id: PlayerId) -> Option<InsuranceTerms> {
+        if !self.insurance_offers || !self.is_all_in_before_river() {
+            return None;
+        }
+        let player = self.players.player_by_id(player_id)?;
+        if !matches!(player.bet_status, BetStatus::AllIn(_)) {
+            return None;
+        }
+        let pockets: HashMap<PlayerId, [Card; 2]> = self
+            .players
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .filter_map(|(_, p)| p.pocket.map(|pocket| (p.id, pocket)))
+            .collect();
+        let equity = exact_equity(&pockets, &self.community);
+        Some(InsuranceTerms {
+            equity: equity[&player_id],
+            pot: self.pot_total_value(),
+            cards_to_come: self.community.iter().filter(|c| c.is_none()).count(),
+        })
+    }
+
+    /// The players that went to showdown in the hand that just ended, best hand first, with the
+    /// hand they made. A player that mucked is listed with None, and a player that folded isn't
+    /// listed at all. Empty if the hand didn't get to showdown, or once the next hand starts.
+    pub fn showdown_hands(&self) -> &[(PlayerId, Option<FinalHandResult>)] {
+        &self.showdown_hands
+    }
+
+    /// The cards that can't come any more this hand: the community cards out so far and every
+    /// pocket dealt, including those of players that have since folded. This is what the server
+    /// knows, so don't send it to players.
+    pub fn dead_cards(&self) -> Vec<Card> {
+        self.community
+            .iter()
+            .flatten()
+            .copied()
+            .chain(
+                self.players
+                    .players_iter(PlayerFilter::ALL)
+                    .filter_map(|(_, p)| p.pocket)
+                    .flatten(),
+            )
+            .collect()
+    }
+
+    /// Every card in a full deck that isn't one of the [`GameState::dead_cards`], regardless of
+    /// what order the deck is in or what's been burned.
+    pub fn available_cards(&self) -> Vec<Card> {
+        let dead = self.dead_cards();
+        Card::all().filter(|c| !dead.contains(c)).collect()
+    }
+
+    /// Everything the house has raked from this table's pots, over all hands. These chips are no
+    /// longer in play.
+    pub const fn rake_collected(&self) -> Currency {
+        self.rake_collected
+    }
+
+    /// Act on behalf of the given player, who must be next to act, because of the given reason.
+    /// They check if they can, otherwise they fold. At showdown, when players decide for
+    /// themselves (see [`GameState::set_showdown_decisions`]), they muck if they're next to decide.
+    ///
+    /// The action is logged as an automatic one before it's taken, so hand histories and stats
+    /// can tell it apart from a voluntary decision.
+    pub fn auto_action(
+        &mut self,
+        player_id: PlayerId,
+        reason: AutoActionReason,
+    ) -> Result<(), GameError> {
+        if self.paused {
+            return Err(GameError::GamePaused);
+        }
+        if let Some(next) = self.showdown_next() {
+            if next != player_id {
+                return Err(GameError::OutOfTurn);
+            }
+            self.logs.push(LogItem::AutoMuck { player_id, reason });
+            return self.player_mucks(player_id);
+        }
+        let player = match self.nta() {
+            Some((_, p)) if p.id == player_id => p,
+            Some(_) => return Err(GameError::OutOfTurn),
+            None => return Err(GameError::NoBetExpected),
+        };
+        let action = if self.call_amount(&player) == 0 {
+            BetAction::Check
+        } else {
+            BetAction::Fold
+        };
+        self.logs.push(LogItem::AutoAction {
+            player_id,
+            action,
+            reason,
+        });
+        self.player_action(player_id, action)
+    }
+
+    /// Choose what the given player does when it's next their turn this hand, replacing anything
+    /// they chose before. If it's their turn now, they do it right away. Being made to act out of
+    /// turn with [`GameState::force_action`] cancels it.
+    ///
+    /// Only players still betting in a hand may queue an action, and
+    /// [`QueuedAction::CheckOnly`] only while they could check, else [`GameError::InvalidBet`].
+    pub fn queue_action(
+        &mut self,
+        player_id: PlayerId,
+        queued: QueuedAction,
+    ) -> Result<(), GameError> {
+        let player = *self
+            .players
+            .player_by_id(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        if !matches!(self.state(), State::Street(_)) {
+            return Err(GameError::NoBetExpected);
+        }
+        if !player.is_betting() {
+            return Err(GameError::PlayerIsNotBetting);
+        }
+        if matches!(queued, QueuedAction::CheckOnly) && self.call_amount(&player) > 0 {
+            return Err(GameError::InvalidBet);
+        }
+        self.queued_actions.insert(player_id, queued);
+        if self.is_players_turn(player_id) {
+            self.take_queued_action()?;
+        }
+        Ok(())
+    }
+
+    /// If the player next to act queued up an action, take it for them, as of what there is to
+    /// call now.
+    fn take_queued_action(&mut self) -> Result<(), GameError> {
+        let player = match self.nta() {
+            Some((_, p)) => p,
+            None => return Ok(()),
+        };
+        let queued = match self.queued_actions.remove(&player.id) {
+            Some(q) => q,
+            None => return Ok(()),
+        };
+        let action = match (queued, self.call_amount(&player) > 0) {
+            (_, false) => BetAction::Check,
+            (QueuedAction::CallAny, true) => BetAction::Call(self.current_bet()),
+            (QueuedAction::CheckFold | QueuedAction::CheckOnly, true) => BetAction::Fold,
+        };
+        self.logs.push(LogItem::AutoAction {
+            player_id: player.id,
+            action,
+            reason: AutoActionReason::Queued,
+        });
+        self.player_action(player.id, action)
+    }
+
+    /// Give the player that's now next to act the configured amount of time, counting from the
+    /// latest time we know of. If we've never been told the time, the clock starts at the next
+    /// [`GameState::tick_with_now`] instead. Once the clock is running, clients are told how long
+    /// the player has.
+    fn start_action_clock(&mut self) {
+        let (timeout, now) = match (self.config.action_timeout, self.now) {
+            (Some(timeout), Some(now)) => (timeout, now),
+            _ => {
+                self.action_deadline = None;
+                return;
+            }
+        };
+        self.action_deadline = Some(now + timeout);
+        if let Some((seat, _)) = self.nta() {
+            let millis = u32::try_from(timeout).unwrap_or(u32::MAX);
+            self.logs.push(LogItem::ActionDeadline(seat, millis));
+        }
+    }
+
+    /// When the player next to act runs out of time, if there's a time limit and someone needs to
+    /// act. See [`GameState::tick_with_now`].
+    pub fn action_deadline(&self) -> Option<Millis> {
+        self.nta().and(self.action_deadline)
+    }
+
+    /// Like [`GameState::tick`], but also enforce the configured action timeout as of the given
+    /// time. If the player next to act has run out of time, they're made to check if they can and
+    /// fold otherwise, just like [`GameState::auto_action`] with [`AutoActionReason::Timeout`].
+    ///
+    /// The time should never go backwards between calls.
+    pub fn tick_with_now(&mut self, now: Millis) -> Result<(), GameError> {
+        self.now = Some(now);
+        if self.paused {
+            return Ok(());
+        }
+        if let Some((_, player)) = self.nta() {
+            match self.action_deadline {
+                Some(deadline) if now >= deadline => {
+                    return self.auto_action(player.id, AutoActionReason::Timeout);
+                }
+                Some(_) => {}
+                None => self.start_action_clock(),
+            }
         }
+        self.tick()
+    }
+
+    /// Have the given player take the given action even if it isn't their turn. For server
+    /// moderation only, e.g. to unstick a game that's waiting on a misbehaving client.
+    ///
+    /// The action must still be legal for this player, and the player must still owe us an action
+    /// this street. Once they've acted, the other players that still need to act do so in their
+    /// usual order. The action is logged as forced once it has been taken, so nothing is logged for
+    /// one that's refused.
+    pub fn force_action(
+        &mut self,
+        player_id: PlayerId,
+        bet_action: BetAction,
+    ) -> Result<(), GameError> {
+        let seat = self
+            .players
+            .seat_of(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        let pos = self
+            .players
+            .need_bets_from
+            .iter()
+            .position(|s| *s == seat)
+            .ok_or(GameError::NoBetExpected)?;
+        // Pretend it's this player's turn by moving them to the end of the list, then put them
+        // back if the action turns out to be invalid.
+        let saved = self.players.need_bets_from.clone();
+        self.players.need_bets_from.remove(pos);
+        self.players.need_bets_from.push(seat);
+        if let Err(e) = self.player_action(player_id, bet_action) {
+            self.players.need_bets_from = saved;
+            return Err(e);
+        }
+        self.logs.push(LogItem::ForcedAction(player_id, bet_action));
         Ok(())
     }
 
@@ -285,6 +1698,105 @@ impl GameState {
         self.__min_raise_dont_change_directly
     }
 
+    pub const fn min_chip(&self) -> Currency {
+        self.config.min_chip
+    }
+
+    pub const fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// An empty table with the given settings, checked as [`GameState::set_config`] checks them
+    pub fn from_config(config: GameConfig) -> Result<Self, GameError> {
+        let mut gs = Self::default();
+        gs.set_config(config)?;
+        Ok(gs)
+    }
+
+    /// Replace the table's settings. They take effect starting with the next hand, so this is only
+    /// allowed between hands.
+    ///
+    /// The blinds must be positive with the small blind no bigger than the big blind, and the
+    /// players seated now must fit in the new max. A bad min chip is
+    /// [`GameError::InvalidMinChip`], anything else wrong is [`GameError::InvalidConfig`].
+    pub fn set_config(&mut self, config: GameConfig) -> Result<(), GameError> {
+        if !matches!(self.state(), State::NotStarted | State::EndOfHand) {
+            return Err(GameError::HandInProgress);
+        }
+        if config.min_chip <= 0 {
+            return Err(GameError::InvalidMinChip);
+        }
+        if config.small_blind <= 0
+            || config.small_blind > config.big_blind
+            || matches!(config.ante, AnteStyle::PerPlayer(ante) if ante <= 0)
+            || config.rake.percent > 100
+            || config.rake.cap < 0
+            || config.min_buyin.map_or(false, |min| min < 0)
+            || matches!((config.min_buyin, config.max_buyin), (Some(min), Some(max)) if min > max)
+            || config.max_players > MAX_PLAYERS
+            || config.max_players < self.players.players_iter(PlayerFilter::ALL).count()
+            || config.min_players_to_start < 2
+            || config.min_players_to_start > config.max_players
+            || matches!(config.betting_limit, BettingLimit::FixedLimit { small_bet, big_bet, cap }
+                if small_bet <= 0 || big_bet <= 0 || cap == 0)
+            || config.blind_schedule.as_ref().map_or(false, |bs| {
+                bs.hands_per_level == 0
+                    || bs.levels.is_empty()
+                    || bs
+                        .levels
+                        .iter()
+                        .any(|(sb, bb, ante)| *sb <= 0 || sb > bb || *ante < 0)
+            })
+        {
+            return Err(GameError::InvalidConfig);
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Set the smallest chip in play, e.g. after a tournament color-up removes the smaller chips.
+    /// Must be positive.
+    ///
+    /// From now on Bet and Raise amounts are rounded down to a multiple of it, but never below the
+    /// smallest legal amount (the current bet for bets, the min raise for raises), so unevenly
+    /// sized blinds can still be called and raised. Calls and all-ins are never rounded.
+    pub fn set_min_chip(&mut self, min_chip: Currency) -> Result<(), GameError> {
+        if min_chip <= 0 {
+            return Err(GameError::InvalidMinChip);
+        }
+        self.config.min_chip = min_chip;
+        Ok(())
+    }
+
+    /// Round the given bet amount down to a multiple of min_chip, but not below the given floor.
+    /// Amounts already under the floor are left alone so they can be rejected as usual.
+    fn round_to_min_chip(&self, amount: Currency, floor: Currency) -> Currency {
+        if amount < floor {
+            amount
+        } else {
+            (amount - amount % self.config.min_chip).max(floor)
+        }
+    }
+
+    /// The pot takes whatever it's given when the street is finalized, so make sure first that
+    /// everyone still in the hand has matched the current bet, or is all in. All in players keep
+    /// the status from the street they went all in on, so there's nothing to compare them to.
+    fn check_bets_even(&self) -> Result<(), GameError> {
+        let cb = self.current_bet();
+        let even = self
+            .players
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .all(|(_, p)| match p.bet_status {
+                BetStatus::Folded | BetStatus::AllIn(_) => true,
+                BetStatus::Waiting => cb == 0,
+                BetStatus::In(x) => x == cb,
+            });
+        if !even {
+            return Err(GameError::UnevenBets);
+        }
+        Ok(())
+    }
+
     fn advance_street(&mut self) -> Result<State, GameError> {
         let next = match self.state() {
             State::Street(round) => match round {
@@ -295,11 +1807,16 @@ impl GameState {
             },
             _ => unreachable!(),
         };
+        self.check_bets_even()?;
         self.players.next_street()?;
         let pot_logs = self.pot.finalize_round();
         self.logs.extend(pot_logs.into_iter().map(|l| l.into()));
-        self.set_current_bet(0, self.big_blind);
-        self.last_raiser = None;
+        self.set_current_bet(0, self.config.big_blind);
+        // The river's last aggressor shows first at showdown
+        if !matches!(next, State::Showdown) {
+            self.last_raiser = None;
+        }
+        self.raises_this_street = 0;
         if let State::Street(street) = next {
             match street {
                 Street::PreFlop => unreachable!(),
@@ -330,41 +1847,322 @@ impl GameState {
         Ok(next)
     }
 
+    /// Seat the given player in the first empty seat. Someone that sits down during a hand, e.g. in
+    /// a seat left by a player that folded and stood up, isn't in it and waits for the next one.
     pub fn try_sit(&mut self, player_id: PlayerId, stack: Currency) -> Result<(), GameError> {
+        let p = self.new_player(player_id, stack)?;
+        self.players.seat_player(p)?;
+        Ok(())
+    }
+
+    /// Like [`GameState::try_sit`], but in the given seat instead of the first empty one. Error if
+    /// there's no such seat or someone is already sitting in it.
+    pub fn try_sit_at(
+        &mut self,
+        player_id: PlayerId,
+        stack: Currency,
+        seat: SeatIdx,
+    ) -> Result<(), GameError> {
+        let p = self.new_player(player_id, stack)?;
+        self.players.seat_player_at(p, seat)
+    }
+
+    /// The player to seat for [`GameState::try_sit`], if they may sit down. They sit out the rest
+    /// of the hand if there's one going.
+    fn new_player(&self, player_id: PlayerId, stack: Currency) -> Result<Player, GameError> {
         if self.players.player_by_id(player_id).is_some() {
             return Err(GameError::PlayerAlreadySeated);
         }
-        let p = Player::new(player_id, stack);
-        self.players.seat_player(p)?;
+        if self.players.players_iter(PlayerFilter::ALL).count() >= self.config.max_players {
+            return Err(GameError::TableFull);
+        }
+        if self.config.min_buyin.map_or(false, |min| stack < min)
+            || self.config.max_buyin.map_or(false, |max| stack > max)
+        {
+            return Err(GameError::InvalidBuyIn);
+        }
+        let mut p = Player::new(player_id, stack);
+        if !matches!(self.state(), State::NotStarted | State::EndOfHand) {
+            p.bet_status = BetStatus::Folded;
+        }
+        Ok(p)
+    }
+
+    /// Take the given player away from the table, returning the stack they leave with. A player
+    /// that's still in the current hand can't leave until it's over, else
+    /// [`GameError::BettingPlayerCantStand`]; one that has folded can.
+    pub fn stand_up(&mut self, player_id: PlayerId) -> Result<Currency, GameError> {
+        let player = self
+            .players
+            .player_by_id(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        let hand_in_progress = !matches!(self.state(), State::NotStarted | State::EndOfHand);
+        if hand_in_progress && !player.is_folded() {
+            return Err(GameError::BettingPlayerCantStand);
+        }
+        // Unwrap is fine: just found them
+        Ok(self.players.remove_player(player_id).unwrap().stack)
+    }
+
+    /// Add the given amount to a seated player's stack, e.g. a rebuy or top-up at a cash table.
+    ///
+    /// Only allowed between hands, else [`GameError::HandInProgress`]. The amount must be positive
+    /// and mustn't take the stack over the table's max buy-in, else [`GameError::InvalidBuyIn`].
+    pub fn add_chips(&mut self, player_id: PlayerId, amount: Currency) -> Result<(), GameError> {
+        if !matches!(self.state(), State::NotStarted | State::EndOfHand) {
+            return Err(GameError::HandInProgress);
+        }
+        let max_buyin = self.config.max_buyin;
+        let player = self
+            .players
+            .player_by_id_mut(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        let stack = player.stack + amount;
+        if amount <= 0 || max_buyin.map_or(false, |max| stack > max) {
+            return Err(GameError::InvalidBuyIn);
+        }
+        player.stack = stack;
+        self.logs
+            .push(LogItem::ChipsAdded(player_id, amount, stack));
+        Ok(())
+    }
+
+    /// Have the given player sit out, starting with the next hand. If they're in the current hand,
+    /// they play it out as usual. While sitting out they keep their seat and stack but aren't
+    /// dealt in, until [`GameState::sit_back_in`].
+    pub fn request_sit_out(&mut self, player_id: PlayerId) -> Result<(), GameError> {
+        let between_hands = matches!(self.state(), State::NotStarted | State::EndOfHand);
+        let player = self
+            .players
+            .player_by_id_mut(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        if matches!(player.play_status, PlayStatus::Eliminated { .. }) {
+            return Err(GameError::PlayerEliminated);
+        }
+        player.play_status = if between_hands {
+            PlayStatus::SittingOut
+        } else {
+            PlayStatus::WantsSitOut
+        };
+        Ok(())
+    }
+
+    /// Have the given player be dealt in again from the next hand on, having sat out (or having
+    /// asked to) with [`GameState::request_sit_out`]. A player with no chips is still sat out
+    /// when the next hand starts.
+    pub fn sit_back_in(&mut self, player_id: PlayerId) -> Result<(), GameError> {
+        let player = self
+            .players
+            .player_by_id_mut(player_id)
+            .ok_or(GameError::PlayerNotFound)?;
+        if matches!(player.play_status, PlayStatus::Eliminated { .. }) {
+            return Err(GameError::PlayerEliminated);
+        }
+        player.play_status = PlayStatus::Playing;
+        Ok(())
+    }
+
+    /// Randomly (but reproducibly, given the seed) reassign seats to all the seated players.
+    ///
+    /// Meant for the start of a tournament, so that the order in which players joined the table
+    /// doesn't determine where they sit. Only allowed before the first hand has started.
+    pub fn randomize_seats(&mut self, seed: DeckSeed) -> Result<(), GameError> {
+        if !matches!(self.state(), State::NotStarted) {
+            return Err(GameError::GameAlreadyStarted);
+        }
+        self.players.shuffle_seats(&mut seed.rng());
+        Ok(())
+    }
+
+    /// Replace every player's stack with the given share, as agreed to by the players out of band
+    /// (e.g. an ICM or chip chop at the end of a tournament).
+    ///
+    /// Only allowed between hands. Every player at the table must be given a share, and the shares
+    /// must add up to exactly the chips currently in play.
+    pub fn chop_remaining_pot(
+        &mut self,
+        shares: &HashMap<PlayerId, Currency>,
+    ) -> Result<(), GameError> {
+        if !matches!(self.state(), State::NotStarted | State::EndOfHand) {
+            return Err(GameError::HandInProgress);
+        }
+        let mut in_play = 0;
+        let mut player_count = 0;
+        for (_, p) in self.players.players_iter(PlayerFilter::ALL) {
+            match shares.get(&p.id) {
+                Some(share) if *share >= 0 => {
+                    in_play += p.stack;
+                    player_count += 1;
+                }
+                _ => return Err(GameError::InvalidDeal),
+            }
+        }
+        if player_count != shares.len() || shares.values().sum::<Currency>() != in_play {
+            return Err(GameError::InvalidDeal);
+        }
+        let mut deal: Vec<(PlayerId, Currency)> = shares.iter().map(|(k, v)| (*k, *v)).collect();
+        deal.sort_unstable();
+        for (player_id, share) in &deal {
+            // Unwrap is fine: just checked everyone with a share is seated
+            self.players.player_by_id_mut(*player_id).unwrap().stack = *share;
+        }
+        self.logs.push(LogItem::Deal(deal));
         Ok(())
     }
 
     /// If we are able to automatically move the current game forward, do so
     pub fn tick(&mut self) -> Result<(), GameError> {
+        if self.paused {
+            return Ok(());
+        }
         // If there's no game going and there's enough people to start one, do so
-        if matches!(self.state(), State::NotStarted)
-            && self.players.players_iter(PlayerFilter::MAY_BET).count() > 1
-        {
+        let enough_players = self.players.players_iter(PlayerFilter::SEATED).count()
+            >= self.config.min_players_to_start;
+        if matches!(self.state(), State::NotStarted) && enough_players {
             return self.start_hand();
         }
-        // If it's the end of a hand, start a new one
+        // If it's the end of a hand, start a new one. If there are players, just not as many as
+        // the table wants, the hand that just ended stays up until more sit down.
         if matches!(self.state(), State::EndOfHand) {
-            return self.start_hand();
+            if self.players.players_iter(PlayerFilter::SEATED).count() < 2 || enough_players {
+                return self.start_hand();
+            }
+            return Ok(());
+        }
+        // If we stopped to offer insurance, deal the rest of the board
+        if self.is_all_in_before_river() {
+            return self.advance_until_action();
         }
         Ok(())
     }
 
+    /// Freeze the game where it is, e.g. for a tournament break or so an admin can step in. Until
+    /// [`GameState::resume`], [`GameState::tick`] does nothing and players' actions fail with
+    /// [`GameError::GamePaused`]. Players can still sit down, stand up between hands, and chat.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Pick the game back up where [`GameState::pause`] left it. Whoever was to act gets a fresh
+    /// action clock at the next [`GameState::tick_with_now`].
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.action_deadline = None;
+    }
+
+    /// Whether the game is paused, see [`GameState::pause`]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Give back to the given player whatever part of their bet this betting round nobody else
+    /// matched
+    fn return_uncalled_bet(&mut self, player_id: PlayerId) {
+        let (returned, pot_logs) = self.pot.return_uncalled(player_id);
+        self.logs.extend(pot_logs.into_iter().map(|l| l.into()));
+        if returned > 0 {
+            // Unwrap is fine: they're the one player still in the hand
+            self.players
+                .player_by_id_mut(player_id)
+                .unwrap()
+                .return_uncalled(returned);
+        }
+    }
+
+    /// Knock out of the tournament everyone that lost all their chips this hand. They finish below
+    /// everyone still in, and if more than one went out, whoever started the hand with more chips
+    /// finishes higher.
+    fn eliminate_busted_players(&mut self) {
+        let still_in = self
+            .players
+            .players_iter(PlayerFilter::ALL)
+            .filter(|(_, p)| !matches!(p.play_status, PlayStatus::Eliminated { .. }))
+            .count() as u32;
+        let mut busted: Vec<(PlayerId, Currency)> = self
+            .starting_stacks
+            .iter()
+            .filter(|(id, _)| {
+                self.players
+                    .player_by_id(*id)
+                    .map_or(false, |p| p.stack == 0)
+            })
+            .copied()
+            .collect();
+        busted.sort_by_key(|(_, starting_stack)| *starting_stack);
+        for (i, (player_id, _)) in busted.into_iter().enumerate() {
+            // Unwrap is fine: just found them
+            let player = self.players.player_by_id_mut(player_id).unwrap();
+            player.play_status = PlayStatus::Eliminated {
+                place: still_in - i as u32,
+            };
+        }
+    }
+
+    /// Everyone's finishing place in the tournament so far, best first: everyone that has been
+    /// knocked out, and the winner once there's only one player with chips left. Empty at a cash
+    /// table.
+    pub fn standings(&self) -> Vec<(PlayerId, u32)> {
+        if !matches!(self.config.table_type, TableType::Tournament) {
+            return vec![];
+        }
+        let mut standings: Vec<(PlayerId, u32)> = self
+            .players
+            .players_iter(PlayerFilter::ALL)
+            .filter_map(|(_, p)| match p.play_status {
+                PlayStatus::Eliminated { place } => Some((p.id, place)),
+                _ => None,
+            })
+            .collect();
+        let mut left = self
+            .players
+            .players_iter(PlayerFilter::ALL)
+            .filter(|(_, p)| !matches!(p.play_status, PlayStatus::Eliminated { .. }));
+        if let (Some((_, winner)), None) = (left.next(), left.next()) {
+            standings.push((winner.id, 1));
+        }
+        standings.sort_by_key(|(_, place)| *place);
+        standings
+    }
+
+    /// Finish a hand that nobody can act in any more, e.g. because everyone is all in and it
+    /// stopped to offer insurance: deal the rest of the board and pay out the pot. Nothing to do if
+    /// the hand is already over. Error if someone still has to act.
+    ///
+    /// With [`GameState::set_showdown_decisions`] on, this stops at the showdown for players to
+    /// decide whether to show.
+    pub fn run_out(&mut self) -> Result<(), GameError> {
+        match self.state() {
+            State::EndOfHand => Ok(()),
+            State::Street(_) if self.players.need_bets_from.is_empty() => {
+                self.advance_until_action()
+            }
+            _ => Err(GameError::StreetNotComplete),
+        }
+    }
+
     fn finalize_hand(&mut self) -> Result<(), GameError> {
+        // Whoever everyone folded to doesn't win the part of their bet that nobody called
+        let eligible: Vec<PlayerId> = self
+            .players
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .map(|(_, p)| p.id)
+            .collect();
+        if eligible.len() == 1 {
+            self.return_uncalled_bet(eligible[0]);
+        }
         let pot = std::mem::take(&mut self.pot);
         // players and their pockets, as a vec
         let players: Vec<(PlayerId, [Card; 2])> = self
             .players
             .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .filter(|(_, p)| !self.mucked.contains(&p.id))
             .map(|(_, p)| (p.id, p.pocket.unwrap()))
             .collect();
-        // PlayerIds, sorted in a Vec<Vec<PlayerId>>, for pot's payout function
-        let ranked_players = if players.len() == 1 {
-            vec![vec![players[0].0]]
+        // Everyone's best hand, sorted best-to-worst with ties grouped, on the first board and then
+        // on any others. Nobody shows down if only one player is left, unless they chose to show.
+        let shows_down = players.len() > 1 || !self.shown.is_empty();
+        let (ranked_hands, extra_ranked_hands) = if !shows_down {
+            (vec![], vec![])
         } else {
             assert!(self.community[4].is_some());
             let community = [
@@ -375,44 +2173,146 @@ impl GameState {
                 self.community[4].unwrap(),
             ];
             let map = players.iter().copied().collect();
-            best_hands(&map, community)?
+            let extra = self
+                .extra_boards
                 .iter()
-                .map(|inner| inner.iter().map(|item| item.0).collect())
+                .map(|board| best_hands(&map, *board))
+                .collect::<Result<Vec<_>, _>>()?;
+            (best_hands(&map, community)?, extra)
+        };
+        // PlayerIds, sorted in a Vec<Vec<PlayerId>> per board, for pot's payout function
+        let ranked_players: Vec<Vec<Vec<PlayerId>>> = if !shows_down {
+            vec![vec![vec![players[0].0]]]
+        } else {
+            std::iter::once(&ranked_hands)
+                .chain(extra_ranked_hands.iter())
+                .map(|board| {
+                    board
+                        .iter()
+                        .map(|inner| inner.iter().map(|item| item.0).collect())
+                        .collect()
+                })
                 .collect()
         };
-        let (winnings, pot_logs) = pot.payout(&ranked_players);
-        // determine who needs to reveal their hand to win, if anybody, and log the reveal. A hand
-        // needs to be revealed if there's more than 1 person that could win the pot at this time.
-        if players.len() > 1 {
-            for winning_player_id in winnings.keys() {
-                let p = self
+        // Odd chips go to the first winner clockwise from the button
+        let seat_order: Vec<PlayerId> = self
+            .players
+            .pot_eligible_seats_from_dealer()
+            .into_iter()
+            .filter_map(|seat| self.players.player_at(seat).map(|p| p.id))
+            .collect();
+        let (winnings, rake, pot_logs) = pot.payout_with_rake(
+            &ranked_players,
+            &seat_order,
+            self.config.min_chip,
+            &self.config.rake,
+            self.community[0].is_some(),
+        );
+        self.rake_collected += rake;
+        // Only the players that win something have to show their hand. Everyone else mucks, unless
+        // they chose to show.
+        self.showdown_hands = ranked_hands
+            .iter()
+            .flatten()
+            .map(|(player_id, hand)| {
+                let shows = winnings.contains_key(player_id) || self.shown.contains(player_id);
+                (*player_id, Some((*hand).into()).filter(|_| shows))
+            })
+            .chain(self.mucked.iter().map(|player_id| (*player_id, None)))
+            .collect();
+        // determine who needs to reveal their hand to win, if anybody, and log the reveal. A hand
+        // needs to be revealed if there's more than 1 person that could win the pot at this time.
+        if players.len() > 1 {
+            // Unwrap is fine: more than one player left means the board was run out
+            let community = self.community.map(|c| c.unwrap());
+            for winning_player_id in winnings.keys() {
+                let p = self
                     .players
                     .player_by_id(*winning_player_id)
                     .expect("Unable to get player that allegedly won (at least part of) the pot");
                 let cards = p
                     .pocket
                     .expect("player that won (at least part of) the pot has no cards");
-                let li = LogItem::HandReveal(*winning_player_id, [Some(cards[0]), Some(cards[1])]);
-                self.logs.push(li);
+                if !self.shown.contains(winning_player_id) {
+                    let li =
+                        LogItem::HandReveal(*winning_player_id, [Some(cards[0]), Some(cards[1])]);
+                    self.logs.push(li);
+                }
+                if !self.extra_boards.is_empty() {
+                    continue;
+                }
+                if let Some(result) = self
+                    .showdown_hands
+                    .iter()
+                    .find_map(|(pid, result)| result.filter(|_| pid == winning_player_id))
+                {
+                    let li = LogItem::HandResult(
+                        *winning_player_id,
+                        result.class,
+                        result.hand.cards(),
+                        result.hand.playing_board(&community),
+                    );
+                    self.logs.push(li);
+                }
+            }
+        }
+        // With more than one board, the best hand(s) on each board win that board's share
+        if !self.extra_boards.is_empty() {
+            let community = self.community.map(|c| c.unwrap());
+            let boards = std::iter::once(&community).chain(self.extra_boards.iter());
+            for (board, ranked) in
+                boards.zip(std::iter::once(&ranked_hands).chain(&extra_ranked_hands))
+            {
+                for (player_id, hand) in ranked.first().into_iter().flatten() {
+                    let li = LogItem::HandResult(
+                        *player_id,
+                        hand.class(),
+                        hand.cards(),
+                        hand.playing_board(board),
+                    );
+                    self.logs.push(li);
+                }
             }
         }
+        // Say outright when nobody had to show down, so it isn't mistaken for a one-player showdown
+        if eligible.len() == 1 {
+            self.logs.push(LogItem::WonByFold(eligible[0]));
+        }
         self.players.end_hand(&winnings)?;
+        for (player_id, starting_stack) in self.starting_stacks.iter() {
+            if let Some(p) = self.players.player_by_id(*player_id) {
+                self.player_stats.entry(*player_id).or_default().net_won +=
+                    p.stack - starting_stack;
+            }
+        }
+        if matches!(self.config.table_type, TableType::Tournament) {
+            self.eliminate_busted_players();
+        }
         self.change_state(State::EndOfHand);
         self.logs.extend(pot_logs.into_iter().map(|pli| pli.into()));
         Ok(())
     }
 
-    fn clean_state(&mut self, deck_seed: DeckSeed) {
+    fn clean_state(&mut self, deck: Deck) {
         self.logs.rotate();
         self.players.clean_state();
-        let bs = Box::new(self.into());
+        let bs = Box::new((&*self).into());
         self.logs.push(LogItem::NewBaseState(bs));
         self.change_state(State::NotStarted);
         self.community = [None; COMMUNITY_SIZE];
         self.pot = Default::default();
-        self.deck = Deck::new(&deck_seed);
-        self.set_current_bet(0, self.big_blind);
+        self.deck = deck;
+        self.set_current_bet(0, self.config.big_blind);
         self.last_raiser = None;
+        self.raises_this_street = 0;
+        self.showdown_hands.clear();
+        self.runout_from = None;
+        self.extra_boards.clear();
+        self.showdown_order.clear();
+        self.shown.clear();
+        self.mucked.clear();
+        self.vpip_this_hand.clear();
+        self.queued_actions.clear();
     }
 
     pub fn start_hand(&mut self) -> Result<(), GameError> {
@@ -420,35 +2320,150 @@ impl GameState {
         self.start_hand_with_seed(seed)
     }
 
+    /// Start a hand dealt from a deck shuffled with the given seed. The seed's
+    /// [`DeckSeed::commitment`] is logged before anything is dealt, and the seed itself is kept
+    /// secret until the hand is over; see [`GameState::revealed_seed`].
+    ///
+    /// That's with the default [`DeckMode::PerHand`]. The table's [`GameConfig::deck_mode`] can
+    /// have the deck come from elsewhere.
     pub fn start_hand_with_seed(&mut self, seed: DeckSeed) -> Result<(), GameError> {
-        self.clean_state(seed);
+        match self.config.deck_mode {
+            DeckMode::PerHand => self.start_hand_from(Deck::new(&seed), Some(seed)),
+            DeckMode::Fixed(fixed) => self.start_hand_from(Deck::new(&fixed), Some(fixed)),
+            DeckMode::ContinuousShuffle => {
+                let mut deck = self.deck.clone();
+                deck.refill_with(&mut seed.rng());
+                self.start_hand_from(deck, None)
+            }
+        }
+    }
+
+    /// Start a hand dealt from the given deck instead of a freshly shuffled one. Every hand gets
+    /// its own deck, so the deck must have enough cards for everyone's pocket, the board, and the
+    /// burns, else [`GameError::DeckExhausted`].
+    pub fn start_hand_with_deck(&mut self, deck: Deck) -> Result<(), GameError> {
+        self.start_hand_from(deck, None)
+    }
+
+    /// The seed the finished hand's deck was shuffled with, so players can check it against the
+    /// [`LogItem::DeckCommitment`] logged at the start of the hand. None while a hand is being
+    /// played, or if the deck didn't come from a seed.
+    pub fn revealed_seed(&self) -> Option<DeckSeed> {
+        match self.state() {
+            State::EndOfHand => self.deck_seed,
+            _ => None,
+        }
+    }
+
+    /// The given player's stats over all the hands they've been dealt into at this table, or None
+    /// if they haven't been dealt into any
+    pub fn player_stats(&self, player_id: PlayerId) -> Option<PlayerStats> {
+        self.player_stats.get(&player_id).copied()
+    }
+
+    /// The number of the current or most recent hand at this table, counting from 1. 0 if no hand
+    /// has been started yet.
+    pub fn hand_num(&self) -> u32 {
+        self.hand_num
+    }
+
+    /// Take this hand's blinds and ante from the schedule, if there is one, and log it if they
+    /// changed.
+    fn apply_blind_schedule(&mut self) {
+        let (sb, bb, ante) = match &self.config.blind_schedule {
+            Some(schedule) => schedule.level_for(self.hand_num),
+            None => return,
+        };
+        let ante_style = if ante == 0 {
+            AnteStyle::None
+        } else {
+            AnteStyle::PerPlayer(ante)
+        };
+        if (sb, bb, ante_style)
+            == (
+                self.config.small_blind,
+                self.config.big_blind,
+                self.config.ante,
+            )
+        {
+            return;
+        }
+        self.config.small_blind = sb;
+        self.config.big_blind = bb;
+        self.config.ante = ante_style;
+        self.logs.push(LogItem::BlindsChanged(sb, bb, ante));
+    }
+
+    fn start_hand_from(&mut self, deck: Deck, seed: Option<DeckSeed>) -> Result<(), GameError> {
+        // Before anything changes, or is logged for clients to see
+        if deck.len() < self.players.num_to_deal_in() * 2 + COMMUNITY_SIZE + NUM_BURNS {
+            return Err(GameError::DeckExhausted);
+        }
+        self.clean_state(deck);
+        self.deck_seed = seed;
+        if let Some(seed) = seed {
+            self.logs.push(LogItem::DeckCommitment(seed.commitment()));
+        }
+        // The rake leaves play, but it doesn't disappear
+        #[cfg(debug_assertions)]
+        let chips_before = self.total_chips_in_play() + self.rake_collected;
         self.players.start_hand()?;
+        self.hand_num += 1;
+        let dealt_in: Vec<(SeatIdx, PlayerId, Currency)> = self
+            .players
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .map(|(seat, p)| (seat, p.id, p.stack))
+            .collect();
+        self.starting_stacks = dealt_in
+            .iter()
+            .map(|(_, id, stack)| (*id, *stack))
+            .collect();
+        for (_, player_id, _) in dealt_in.iter() {
+            self.player_stats.entry(*player_id).or_default().hands_dealt += 1;
+        }
+        self.logs.push(LogItem::HandStart {
+            hand_num: self.hand_num,
+            button: self.players.token_dealer,
+            players: dealt_in,
+        });
+        self.apply_blind_schedule();
         self.change_state(State::Street(Street::PreFlop));
         self.logs.push(LogItem::TokensSet(
             self.players.token_dealer,
             self.players.token_sb,
             self.players.token_bb,
         ));
-        self.set_current_bet(0, self.big_blind);
-        let ((player_sb, bet_sb), (player_bb, bet_bb)) = self.blinds_bet()?;
+        self.set_current_bet(0, self.config.big_blind);
+        self.antes_bet()?;
+        let (sb, (player_bb, bet_bb)) = self.blinds_bet()?;
         let mut pot_logs = vec![];
-        pot_logs.append(&mut self.pot.bet(player_sb, bet_sb));
+        if let Some((player_sb, bet_sb)) = sb {
+            pot_logs.append(&mut self.pot.bet(player_sb, bet_sb));
+        }
         pot_logs.append(&mut self.pot.bet(player_bb, bet_bb));
         self.logs.extend(pot_logs.into_iter().map(|l| l.into()));
-        self.set_current_bet(self.big_blind, self.big_blind * 2);
+        self.entry_blinds_bet()?;
+        self.set_current_bet(self.config.big_blind, self.config.big_blind * 2);
         // at this point, there is no last raiser, but the bet function thinks there is (it considers
         // the BB to have taken the most recent agressive action). Thus we won't let the BB raise if
         // no one raises before him ... unless we clear the last_raiser.
         // We assert here because if logic changes, we might be able to clean this up, or we might
-        // be fucking something up.
-        assert!(self.last_raiser.is_some());
-        assert_eq!(
-            self.last_raiser.unwrap(),
-            self.players.players[self.players.token_bb].unwrap().id,
-        );
+        // be fucking something up. (Unless the BB couldn't post a full blind, e.g. because the ante
+        // put them all in.)
+        if matches!(bet_bb, BetAction::Bet(_)) {
+            assert!(self.last_raiser.is_some());
+            assert_eq!(
+                self.last_raiser.unwrap(),
+                self.players.players[self.players.token_bb].unwrap().id,
+            );
+        }
         self.last_raiser = None;
 
-        let num_p = self.players.players_iter(PlayerFilter::MAY_BET).count() as u8;
+        // deal in everyone, including those the antes or blinds put all in
+        let num_p = self
+            .players
+            .players_iter(PlayerFilter::POT_ELIGIBLE)
+            .count() as u8;
         let pockets = self.deck.deal_pockets(num_p)?;
         let deal_logs = self
             .players
@@ -457,21 +2472,42 @@ impl GameState {
             .map(|(k, v)| LogItem::PocketDealt(k, v));
         self.logs.extend(deal_logs);
         self.logs.push(LogItem::NextToAct(self.nta().unwrap().0));
+        self.start_action_clock();
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            chips_before,
+            self.total_chips_in_play() + self.rake_collected
+        );
         Ok(())
     }
 
     /// Have the SB and BB execute their obligatory preflop betting. Return their IDs and bet
-    /// amounts.
+    /// amounts. There's no SB when it's dead (see [`Players::rotate_tokens`]).
     ///
     /// Caller can't assume SB and BB are in for the full SB/BB amount: they could have been a very
     /// short stack and now be allin for less.
-    fn blinds_bet(&mut self) -> Result<(PidBA, PidBA), GameError> {
-        let player_sb =
-            self.players.players[self.players.token_sb].ok_or(GameError::PlayerNotFound)?;
+    fn blinds_bet(&mut self) -> Result<(Option<PidBA>, PidBA), GameError> {
+        let player_sb = if self.players.is_live(self.players.token_sb) {
+            self.players.players[self.players.token_sb]
+        } else {
+            None
+        };
         let player_bb =
             self.players.players[self.players.token_bb].ok_or(GameError::PlayerNotFound)?;
-        let bet_sb = self.bet(player_sb.id, BetAction::Bet(self.small_blind))?;
-        let bet_bb = self.bet(player_bb.id, BetAction::Bet(self.big_blind))?;
+        // A blind that went all in on the ante has nothing left to post
+        let sb = match player_sb {
+            Some(p) if p.is_betting() => Some((
+                p.id,
+                self.bet(p.id, BetAction::Bet(self.config.small_blind))?,
+            )),
+            Some(p) => Some((p.id, BetAction::Check)),
+            None => None,
+        };
+        let bet_bb = if player_bb.is_betting() {
+            self.bet(player_bb.id, BetAction::Bet(self.config.big_blind))?
+        } else {
+            BetAction::Check
+        };
         // the blinds have bet, and we need to make sure they have the opportunity to bet again this
         // round, so rebuild need_bets_from
         self.players.need_bets_from = self
@@ -481,16 +2517,127 @@ impl GameState {
             .take(self.players.players_iter(PlayerFilter::MAY_BET).count())
             .collect();
         self.players.need_bets_from.reverse();
-        Ok(((player_sb.id, bet_sb), (player_bb.id, bet_bb)))
+        Ok((sb, (player_bb.id, bet_bb)))
+    }
+
+    /// Have the players that sat down on the wrong side of the button (see [`Player::must_post`])
+    /// post a big blind to be dealt in. It's a live blind, just like the BB's, so they get to check
+    /// if no one raises. Someone that's in one of the blinds this hand is paying their way already.
+    fn entry_blinds_bet(&mut self) -> Result<(), GameError> {
+        let (sb_seat, bb_seat) = (self.players.token_sb, self.players.token_bb);
+        let big_blind = self.config.big_blind;
+        let mut pot_logs = vec![];
+        for (seat, player) in self.players.players_iter_mut(PlayerFilter::POT_ELIGIBLE) {
+            if !player.must_post {
+                continue;
+            }
+            player.must_post = false;
+            if seat == sb_seat || seat == bb_seat || !player.is_betting() {
+                continue;
+            }
+            let action = if player.stack <= big_blind {
+                player.bet(BetAction::AllIn(player.stack))?
+            } else {
+                player.bet(BetAction::Bet(big_blind))?
+            };
+            pot_logs.append(&mut self.pot.bet(player.id, action));
+        }
+        self.logs.extend(pot_logs.into_iter().map(|l| l.into()));
+        Ok(())
+    }
+
+    /// Have whoever owes an ante put it in, or all of their stack if that's less, and put the
+    /// antes in the pot as a betting round of their own. That way a player that's all in for part
+    /// of the ante can still win the main pot, and everyone else's antes beyond that start a side
+    /// pot.
+    fn antes_bet(&mut self) -> Result<(), GameError> {
+        let bb_seat = self.players.token_bb;
+        let ante_for = |seat: SeatIdx| match self.config.ante {
+            AnteStyle::None => 0,
+            AnteStyle::PerPlayer(ante) => ante,
+            AnteStyle::BigBlind if seat == bb_seat => self.config.big_blind,
+            AnteStyle::BigBlind => 0,
+        };
+        if matches!(self.config.ante, AnteStyle::None) {
+            return Ok(());
+        }
+        let antes: Vec<(SeatIdx, Currency)> = self
+            .players
+            .players_iter(PlayerFilter::MAY_BET)
+            .map(|(seat, _)| (seat, ante_for(seat)))
+            .collect();
+        let mut pot_logs = vec![];
+        for (seat, ante) in antes {
+            let player = self.players.players[seat].as_mut().unwrap();
+            // Players that don't owe anything still go in the pot (for nothing) so that they can
+            // win the antes
+            let action = if ante == 0 {
+                BetAction::Bet(0)
+            } else if player.stack <= ante {
+                player.bet(BetAction::AllIn(player.stack))?
+            } else {
+                player.bet(BetAction::Bet(ante))?
+            };
+            pot_logs.append(&mut self.pot.bet(player.id, action));
+        }
+        pot_logs.append(&mut self.pot.finalize_round());
+        self.logs.extend(pot_logs.into_iter().map(|l| l.into()));
+        // The antes don't count towards anyone's bet this street
+        for (_, player) in self.players.players_iter_mut(PlayerFilter::MAY_BET) {
+            player.bet_status = BetStatus::Waiting;
+        }
+        Ok(())
     }
 
     /// Check that the player can make the given bet, adjusting it if possible. Returns the
     /// (possibly adjusted) bet this player made
     fn bet(&mut self, player_id: PlayerId, bet: BetAction) -> Result<BetAction, GameError> {
         // Check for obvious errors: game not in correct state
+        if self.paused {
+            return Err(GameError::GamePaused);
+        }
         if !matches!(self.state(), State::Street(_)) {
             return Err(GameError::NoBetExpected);
         }
+        // In fixed limit, bets and raises are by exactly one increment, and only so many raises.
+        // All in is at most one increment too, and counts as a raise if it's a full one.
+        let mut full_allin_raise = false;
+        if let BettingLimit::FixedLimit {
+            small_bet,
+            big_bet,
+            cap,
+        } = self.config.betting_limit
+        {
+            let preflop = matches!(self.state(), State::Street(Street::PreFlop));
+            let increment = match self.state() {
+                State::Street(Street::PreFlop | Street::Flop) => small_bet,
+                _ => big_bet,
+            };
+            match bet {
+                // the blinds are the only bets preflop, and they're whatever they're configured as
+                BetAction::Bet(x) if !preflop && self.current_bet() == 0 && x != increment => {
+                    return Err(GameError::InvalidBet);
+                }
+                BetAction::Raise(x)
+                    if x != self.current_bet() + increment || self.raises_this_street >= cap =>
+                {
+                    return Err(GameError::InvalidBet);
+                }
+                BetAction::AllIn(x)
+                    if x > self.current_bet() + increment
+                        || (x > self.current_bet()
+                            && self.current_bet() > 0
+                            && self.raises_this_street >= cap) =>
+                {
+                    return Err(GameError::InvalidBet);
+                }
+                BetAction::AllIn(x) => {
+                    full_allin_raise =
+                        self.current_bet() > 0 && x == self.current_bet() + increment;
+                }
+                _ => {}
+            }
+        }
         // Check for obvious errors: bet too small, or this player shouldn't be betting, etc.
         match &bet {
             // nothing obvious to check for
@@ -506,6 +2653,16 @@ impl GameState {
                         if self.current_bet() != 0 {
                             return Err(GameError::InvalidBet);
                         }
+                        // Opening the betting after the flop takes at least a big blind, unless
+                        // it's everything the player has. Preflop, this is the blinds going in.
+                        let stack = self.players.player_by_id(player_id).map_or(0, |p| p.stack);
+                        if matches!(bet, BetAction::Bet(_))
+                            && !matches!(self.state(), State::Street(Street::PreFlop))
+                            && *x < self.config.big_blind
+                            && *x < stack
+                        {
+                            return Err(GameError::InvalidBet);
+                        }
                     }
                     // No errors to account for and no maintenance to do
                     Ordering::Equal => {}
@@ -517,7 +2674,7 @@ impl GameState {
                 }
                 // Cannot raise if same player was most recent player to raise
                 if self.last_raiser.is_some() && self.last_raiser.unwrap() == player_id {
-                    return Err(GameError::InvalidBet);
+                    return Err(GameError::CantRaiseSelf);
                 }
             }
         }
@@ -553,11 +2710,15 @@ impl GameState {
         // There are no more obvious issues. Assuming the player has enough in their stack, have
         // them take the bet from their stack (updates their stack size) and convert the bet to an
         // allin if needed.
+        let is_raise = matches!(bet, BetAction::Raise(_)) || full_allin_raise;
         let bet = self
             .players
             .player_by_id_mut(player_id)
             .ok_or(GameError::PlayerNotFound)?
             .bet(bet)?;
+        if is_raise {
+            self.raises_this_street += 1;
+        }
 
         // If the bet is for an amount greater than the current bet, then a full orbit is required
         // to give everyone a chance to match it. We expect it to be ...
@@ -616,7 +2777,7 @@ impl GameState {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bet::BetStatus;
+    use crate::hand::HandClass;
     use crate::player::Player;
     use crate::MAX_PLAYERS;
 
@@ -672,6 +2833,2616 @@ mod tests {
         }
     }
 
+    /// Cards come off a stacked deck one at a time to each player, starting left of the button,
+    /// then a second card to each.
+    #[test]
+    fn deal_pockets_in_order() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        // place the tokens as if starting a hand, then figure out who should be dealt to first
+        gs.players.start_hand().unwrap();
+        let order: Vec<PlayerId> = gs
+            .players
+            .betting_players_iter_after(gs.players.token_dealer)
+            .take(3)
+            .map(|(_, p)| p.id)
+            .collect();
+        let dealer = gs.player_at(gs.players.token_dealer).unwrap().id;
+        assert_eq!(order[2], dealer);
+        let cards = crate::deck::cards_from_str("AsKsQsJsTs9s");
+        let mut deck = Deck::from_cards(cards.clone());
+        let pockets = gs.players.deal_pockets(deck.deal_pockets(3).unwrap());
+        assert_eq!(pockets[&order[0]], Some([cards[0], cards[3]]));
+        assert_eq!(pockets[&order[1]], Some([cards[1], cards[4]]));
+        assert_eq!(pockets[&order[2]], Some([cards[2], cards[5]]));
+        for pid in order {
+            assert_eq!(gs.players.player_by_id(pid).unwrap().pocket, pockets[&pid]);
+        }
+    }
+
+    fn seating(gs: &GameState) -> Vec<(SeatIdx, PlayerId, Currency)> {
+        gs.players
+            .players_iter(PlayerFilter::ALL)
+            .map(|(idx, p)| (idx, p.id, p.stack))
+            .collect()
+    }
+
+    /// Seat shuffling is reproducible given the same seed, different given different seeds, and
+    /// players keep their stacks.
+    #[test]
+    fn randomize_seats() {
+        const SEED1: DeckSeed = DeckSeed::new([1; 32]);
+        const SEED2: DeckSeed = DeckSeed::new([2; 32]);
+        let mut gs = GameState::default();
+        for pid in 1..=6 {
+            gs.try_sit(pid, 1000 * pid as Currency).unwrap();
+        }
+        let mut gs1 = gs.clone();
+        let mut gs2 = gs.clone();
+        let mut gs3 = gs.clone();
+        gs1.randomize_seats(SEED1).unwrap();
+        gs2.randomize_seats(SEED1).unwrap();
+        gs3.randomize_seats(SEED2).unwrap();
+        assert_eq!(seating(&gs1), seating(&gs2));
+        assert_ne!(seating(&gs1), seating(&gs3));
+        assert_ne!(seating(&gs), seating(&gs1));
+        for (_, pid, stack) in seating(&gs1) {
+            assert_eq!(stack, 1000 * pid as Currency);
+        }
+    }
+
+    #[test]
+    fn randomize_seats_only_before_start() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 100).unwrap();
+        gs.try_sit(2, 100).unwrap();
+        gs.start_hand().unwrap();
+        assert!(matches!(
+            gs.randomize_seats(DeckSeed::default()),
+            Err(GameError::GameAlreadyStarted)
+        ));
+    }
+
+    #[test]
+    fn accessible_state() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        assert_eq!(
+            gs.accessible_state(1),
+            "Waiting for the next hand. You have 1000 behind."
+        );
+        assert_eq!(gs.accessible_state(3), "You are not seated at this table.");
+        gs.start_hand().unwrap();
+        // heads up: player 2 is BTN/SB in seat 1 and acts first, facing the BB
+        assert_eq!(
+            gs.accessible_state(2),
+            "It's your turn. The pot is 15. The bet to you is 5. You have 995 behind."
+        );
+        assert_eq!(
+            gs.accessible_state(1),
+            "Waiting for seat 1 to act. The pot is 15. You have 990 behind."
+        );
+        // SB completes, and the BB has the option
+        gs.player_calls(2).unwrap();
+        assert_eq!(
+            gs.accessible_state(1),
+            "It's your turn. The pot is 20. You can check. You have 990 behind."
+        );
+    }
+
+    #[test]
+    fn can_check_and_call() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        // heads up: player 2 is SB, facing the BB
+        assert!(gs.can_call(2));
+        assert!(!gs.can_check(2));
+        // not player 1's turn
+        assert!(!gs.can_call(1));
+        assert!(!gs.can_check(1));
+        gs.player_calls(2).unwrap();
+        // BB has the option
+        assert!(gs.can_check(1));
+        assert!(!gs.can_call(1));
+        assert!(!gs.can_check(3));
+    }
+
+    /// Nobody may check or call before a hand, while showdown decisions are pending, or after it
+    #[test]
+    fn can_check_and_call_outside_betting() {
+        let mut gs = GameState::default();
+        gs.set_showdown_decisions(true);
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        let nobody = |gs: &GameState| (1..=2).all(|pid| !gs.can_check(pid) && !gs.can_call(pid));
+        assert!(nobody(&gs));
+        gs.start_hand().unwrap();
+        while let State::Street(_) = gs.state() {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        assert!(matches!(gs.state(), State::Showdown));
+        assert!(nobody(&gs));
+        while let Some(pid) = gs.showdown_next() {
+            gs.player_shows(pid).unwrap();
+        }
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert!(nobody(&gs));
+    }
+
+    /// The BB may check their option preflop, but once raised, the SB has to call, fold, or
+    /// re-raise
+    #[test]
+    fn legal_actions() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        assert!(gs.legal_actions(1).is_none());
+        gs.player_calls(2).unwrap();
+        let la = gs.legal_actions(1).unwrap();
+        assert!(la.can_check && !la.can_fold && la.can_raise);
+        assert_eq!(la.call_amount, 0);
+        assert_eq!(la.min_raise, gs.min_raise());
+        assert_eq!(la.max_raise, 1000);
+        gs.player_raises(1, 40).unwrap();
+        assert!(gs.legal_actions(1).is_none());
+        let la = gs.legal_actions(2).unwrap();
+        assert!(!la.can_check && la.can_fold && la.can_raise);
+        assert_eq!(la.call_amount, 30);
+        assert_eq!(la.min_raise, 70);
+        assert_eq!(la.max_raise, 1000);
+    }
+
+    /// A player that can't afford a full raise may still go all in, and the chips they already
+    /// have in count towards it
+    #[test]
+    fn bet_range_short_stack() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 50).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.bet_range(1), None);
+        gs.player_calls(2).unwrap();
+        assert_eq!(gs.bet_range(1), Some((gs.min_raise(), 1000)));
+        gs.player_raises(1, 40).unwrap();
+        // 10 in and 40 behind: short of the 70 needed for a full re-raise
+        assert_eq!(gs.bet_range(2), Some((50, 50)));
+    }
+
+    /// With insurance offers on, an all-in on the turn waits for a tick before the river, and in
+    /// the meantime the all-in player is offered their exact equity.
+    #[test]
+    fn insurance_offer() {
+        let mut gs = GameState::default();
+        gs.set_insurance_offers(true);
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 500).unwrap();
+        gs.start_hand().unwrap();
+        // heads up: player 2 is SB and acts first preflop, last after that
+        gs.player_calls(2).unwrap();
+        gs.player_checks(1).unwrap();
+        gs.player_checks(1).unwrap();
+        gs.player_checks(2).unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::Turn)));
+        assert_eq!(gs.insurance_offer(2), None);
+        gs.player_checks(1).unwrap();
+        gs.player_action(2, BetAction::AllIn(490)).unwrap();
+        gs.player_calls(1).unwrap();
+        // stopped before the river
+        assert!(matches!(gs.state(), State::Street(Street::Turn)));
+        // swap in known cards so we know what the equity should be
+        let cards = crate::deck::cards_from_str("AhAdKsKc2c7d9hJs");
+        gs.players.player_by_id_mut(2).unwrap().pocket = Some([cards[0], cards[1]]);
+        gs.players.player_by_id_mut(1).unwrap().pocket = Some([cards[2], cards[3]]);
+        for (i, c) in cards[4..].iter().enumerate() {
+            gs.community[i] = Some(*c);
+        }
+        let pockets: HashMap<PlayerId, [Card; 2]> =
+            HashMap::from([(2, [cards[0], cards[1]]), (1, [cards[2], cards[3]])]);
+        let terms = gs.insurance_offer(2).unwrap();
+        assert_eq!(terms.equity, exact_equity(&pockets, &gs.community)[&2]);
+        assert!((terms.equity - 42.0 / 44.0).abs() < 1e-9);
+        assert_eq!(terms.pot, 1000);
+        assert_eq!(terms.cards_to_come, 1);
+        // player 1 isn't all in
+        assert_eq!(gs.insurance_offer(1), None);
+        gs.tick().unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::River)));
+        assert_eq!(gs.insurance_offer(2), None);
+        gs.player_checks(1).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+    }
+
+    /// A queued check-fold folds once there's a raise to face, and a queued call-any calls it
+    #[test]
+    fn queued_actions() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        assert!(matches!(
+            gs.queue_action(1, QueuedAction::CallAny),
+            Err(GameError::NoBetExpected)
+        ));
+        gs.start_hand().unwrap();
+        let order: Vec<PlayerId> = {
+            let first = gs.nta().unwrap().1.id;
+            let bb = gs.players.players[gs.players.token_bb].unwrap().id;
+            let sb = gs.players.players[gs.players.token_sb].unwrap().id;
+            vec![first, sb, bb]
+        };
+        let (first, sb, bb) = (order[0], order[1], order[2]);
+        // the SB faces half a blind, so can't just check
+        assert!(matches!(
+            gs.queue_action(sb, QueuedAction::CheckOnly),
+            Err(GameError::InvalidBet)
+        ));
+        gs.queue_action(sb, QueuedAction::CallAny).unwrap();
+        gs.queue_action(bb, QueuedAction::CheckFold).unwrap();
+        gs.player_raises(first, 40).unwrap();
+        // both queued actions were taken as soon as it was each player's turn
+        assert_eq!(gs.players.player_by_id(sb).unwrap().stack, 960);
+        assert_eq!(
+            gs.players.player_by_id(bb).unwrap().bet_status,
+            BetStatus::Folded
+        );
+        assert!(gs.logs.items_since(0).any(|(_, li)| li
+            == LogItem::AutoAction {
+                player_id: bb,
+                action: BetAction::Fold,
+                reason: AutoActionReason::Queued,
+            }));
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        // the SB acts first now, and the check-fold waits for them
+        gs.queue_action(first, QueuedAction::CheckFold).unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        gs.player_bets(sb, 20).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert!(gs.queued_actions.is_empty());
+    }
+
+    /// A hand waiting to offer insurance can be finished in one go, but not while someone can act
+    #[test]
+    fn run_out() {
+        let mut gs = GameState::default();
+        gs.set_insurance_offers(true);
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        assert!(matches!(gs.run_out(), Err(GameError::StreetNotComplete)));
+        gs.player_action(2, BetAction::AllIn(1000)).unwrap();
+        gs.player_calls(1).unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::PreFlop)));
+        gs.run_out().unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert!(gs.community.iter().all(|c| c.is_some()));
+        assert_eq!(gs.total_chips_in_play(), 2000);
+        gs.run_out().unwrap();
+    }
+
+    /// The river bettor decides first and shows, the caller mucks, and neither the mucked hand nor
+    /// the one folded on the river is ever revealed
+    #[test]
+    fn showdown_decisions() {
+        let mut gs = GameState::default();
+        gs.set_showdown_decisions(true);
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        while !matches!(gs.state(), State::Street(Street::River)) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let bettor = gs.nta().unwrap().1.id;
+        gs.player_bets(bettor, 100).unwrap();
+        let folder = gs.nta().unwrap().1.id;
+        gs.player_folds(folder).unwrap();
+        let caller = gs.nta().unwrap().1.id;
+        gs.player_calls(caller).unwrap();
+        assert!(matches!(gs.state(), State::Showdown));
+        assert_eq!(gs.showdown_next(), Some(bettor));
+        assert!(matches!(gs.player_mucks(caller), Err(GameError::OutOfTurn)));
+        gs.player_shows(bettor).unwrap();
+        assert_eq!(gs.showdown_next(), Some(caller));
+        gs.player_mucks(caller).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        let revealed: Vec<_> = gs
+            .logs
+            .items_since(0)
+            .filter_map(|(_, li)| match li {
+                LogItem::HandReveal(pid, _) => Some(pid),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(revealed, vec![bettor]);
+        assert!(gs
+            .logs
+            .items_since(0)
+            .any(|(_, li)| li == LogItem::Muck(caller)));
+        let hands = gs.showdown_hands();
+        assert_eq!(hands.len(), 2);
+        assert_eq!(hands[0].0, bettor);
+        assert!(hands[0].1.is_some());
+        assert_eq!(hands[1], (caller, None));
+        assert_eq!(gs.players.player_by_id(bettor).unwrap().stack, 1120);
+    }
+
+    /// A player that disconnects while deciding whether to show mucks, and it's logged as automatic
+    #[test]
+    fn auto_muck_at_showdown() {
+        let mut gs = GameState::default();
+        gs.set_showdown_decisions(true);
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        while !matches!(gs.state(), State::Showdown) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let first = gs.showdown_next().unwrap();
+        assert!(matches!(
+            gs.auto_action(3 - first, AutoActionReason::Disconnect),
+            Err(GameError::OutOfTurn)
+        ));
+        gs.auto_action(first, AutoActionReason::Disconnect).unwrap();
+        // with only one hand left unmucked, it wins without having to show
+        assert!(matches!(gs.state(), State::EndOfHand));
+        let logs: Vec<LogItem> = gs.logs.items_since(0).map(|(_, li)| li).collect();
+        let at = logs
+            .iter()
+            .position(|li| {
+                *li == LogItem::AutoMuck {
+                    player_id: first,
+                    reason: AutoActionReason::Disconnect,
+                }
+            })
+            .unwrap();
+        assert_eq!(logs[at + 1], LogItem::Muck(first));
+        assert_eq!(
+            gs.players.player_by_id(3 - first).unwrap().stack,
+            1000 + DEF_BB
+        );
+    }
+
+    /// Run twice, each board's half of the pot goes to that board's winner
+    #[test]
+    fn run_it_twice() {
+        let mut gs = GameState::default();
+        assert!(matches!(gs.set_run_count(0), Err(GameError::InvalidConfig)));
+        gs.set_run_count(2).unwrap();
+        gs.try_sit(1, 2000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        // pockets, then a burn before every street of each board
+        let cards = crate::deck::cards_from_str(
+            "AhAdKsKc6c2c7d9h6dJs6h3d\
+             6sKh4s8cTcQdTd5h",
+        );
+        gs.start_hand_with_deck(Deck::from_cards(cards.clone()))
+            .unwrap();
+        gs.players.player_by_id_mut(2).unwrap().pocket = Some([cards[0], cards[1]]);
+        gs.players.player_by_id_mut(1).unwrap().pocket = Some([cards[2], cards[3]]);
+        // heads up: player 2 is SB and acts first preflop
+        gs.player_action(2, BetAction::AllIn(1000)).unwrap();
+        gs.player_calls(1).unwrap();
+        // the rest of the board is still run twice even though player 1 has chips behind
+        while !matches!(gs.state(), State::EndOfHand) {
+            gs.player_checks(1).unwrap();
+        }
+        let board = |s| {
+            let c = crate::deck::cards_from_str(s);
+            [c[0], c[1], c[2], c[3], c[4]]
+        };
+        assert_eq!(gs.community.map(Option::unwrap), board("2c7d9hJs3d"));
+        assert_eq!(gs.extra_boards(), &[board("Kh4s8cQd5h")]);
+        let rivers = gs
+            .logs
+            .items_since(0)
+            .filter(|(_, li)| matches!(li, LogItem::River(_)))
+            .count();
+        assert_eq!(rivers, 2);
+        let results: Vec<_> = gs
+            .logs
+            .items_since(0)
+            .filter_map(|(_, li)| match li {
+                LogItem::HandResult(pid, class, _, _) => Some((pid, class)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![(2, HandClass::Pair), (1, HandClass::ThreeOfAKind)]
+        );
+        assert_eq!(gs.players.player_by_id(1).unwrap().stack, 2000);
+        assert_eq!(gs.players.player_by_id(2).unwrap().stack, 1000);
+        // the next hand starts with only the one board
+        gs.tick().unwrap();
+        assert!(gs.extra_boards().is_empty());
+    }
+
+    /// Both players that go to showdown show their hand when they chop the pot, and the player
+    /// that folded isn't listed.
+    #[test]
+    fn showdown_hands() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        assert!(gs.showdown_hands().is_empty());
+        while !matches!(gs.state(), State::EndOfHand) {
+            if matches!(gs.state(), State::Street(Street::River)) {
+                // a royal flush on the board, so everyone still in chops
+                let cards = crate::deck::cards_from_str("AsKsQsJsTs");
+                for (i, c) in cards.into_iter().enumerate() {
+                    gs.community[i] = Some(c);
+                }
+            }
+            let pid = gs.nta().unwrap().1.id;
+            if pid == 3 {
+                gs.player_folds(pid).unwrap();
+            } else if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let hands = gs.showdown_hands();
+        assert_eq!(hands.len(), 2);
+        assert!(hands.iter().all(|(pid, _)| *pid == 1 || *pid == 2));
+        for (_, result) in hands {
+            assert_eq!(result.unwrap().class, HandClass::RoyalFlush);
+        }
+        // the next hand starts with nothing to show
+        gs.tick().unwrap();
+        assert!(gs.showdown_hands().is_empty());
+    }
+
+    /// A showdown with one winner logs that winner's hand, and only theirs
+    #[test]
+    fn hand_result_single_winner() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let cards = crate::deck::cards_from_str("2c7d9hJsQdAhAd3s4c");
+        gs.players.player_by_id_mut(1).unwrap().pocket = Some([cards[5], cards[6]]);
+        gs.players.player_by_id_mut(2).unwrap().pocket = Some([cards[7], cards[8]]);
+        while !matches!(gs.state(), State::EndOfHand) {
+            if matches!(gs.state(), State::Street(Street::River)) {
+                for (i, c) in cards[..5].iter().enumerate() {
+                    gs.community[i] = Some(*c);
+                }
+            }
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let results: Vec<_> = gs
+            .logs
+            .items_since(0)
+            .filter_map(|(_, li)| match li {
+                LogItem::HandResult(pid, class, cards, _) => Some((pid, class, cards)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(results.len(), 1);
+        let (pid, class, hand) = results[0];
+        assert_eq!(pid, 1);
+        assert_eq!(class, HandClass::Pair);
+        assert!(hand.contains(&cards[5]) && hand.contains(&cards[6]));
+    }
+
+    /// Nothing moves while the game is paused, and it carries on from the same spot once resumed
+    #[test]
+    fn pause_resume() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let state = gs.state();
+        let nta = gs.nta().unwrap().1.id;
+        gs.pause();
+        assert!(gs.is_paused());
+        assert!(matches!(gs.player_calls(nta), Err(GameError::GamePaused)));
+        assert!(matches!(
+            gs.player_action(nta, BetAction::Fold),
+            Err(GameError::GamePaused)
+        ));
+        gs.tick().unwrap();
+        assert_eq!(gs.state(), state);
+        assert_eq!(gs.nta().unwrap().1.id, nta);
+        gs.resume();
+        assert!(!gs.is_paused());
+        gs.player_calls(nta).unwrap();
+        // and between hands, tick doesn't deal the next one
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        gs.pause();
+        gs.tick().unwrap();
+        assert_eq!(gs.state(), State::EndOfHand);
+        gs.resume();
+        gs.tick().unwrap();
+        assert_eq!(gs.state(), State::Street(Street::PreFlop));
+    }
+
+    /// A table built with 25/50 blinds and a 5 ante deals its first hand with them
+    #[test]
+    fn from_config() {
+        let mut gs = GameState::from_config(GameConfig {
+            small_blind: 25,
+            big_blind: 50,
+            ante: AnteStyle::PerPlayer(5),
+            ..Default::default()
+        })
+        .unwrap();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        assert_eq!(gs.pot_total_value(), 3 * 5 + 25 + 50);
+        assert_eq!(gs.current_bet(), 50);
+        let sb = gs.players.players[gs.players.token_sb].unwrap();
+        assert_eq!(sb.stack, 1000 - 5 - 25);
+        let bb = gs.players.players[gs.players.token_bb].unwrap();
+        assert_eq!(bb.stack, 1000 - 5 - 50);
+        // and a table with its blinds the wrong way round isn't built at all
+        let bad = GameConfig {
+            small_blind: 50,
+            big_blind: 25,
+            ..Default::default()
+        };
+        assert!(matches!(
+            GameState::from_config(bad),
+            Err(GameError::InvalidConfig)
+        ));
+    }
+
+    /// A table that wants three players doesn't deal heads up, before the first hand or after
+    #[test]
+    fn min_players_to_start() {
+        let mut gs = GameState::default();
+        gs.set_config(GameConfig {
+            min_players_to_start: 3,
+            ..Default::default()
+        })
+        .unwrap();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.tick().unwrap();
+        assert_eq!(gs.state(), State::NotStarted);
+        gs.try_sit(3, 1000).unwrap();
+        gs.tick().unwrap();
+        assert_eq!(gs.state(), State::Street(Street::PreFlop));
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        gs.stand_up(3).unwrap();
+        gs.tick().unwrap();
+        assert_eq!(gs.state(), State::EndOfHand);
+        // and it has to be a table that can hold that many
+        let bad = GameConfig {
+            min_players_to_start: 1,
+            ..Default::default()
+        };
+        assert!(matches!(gs.set_config(bad), Err(GameError::InvalidConfig)));
+        let bad = GameConfig {
+            min_players_to_start: 4,
+            max_players: 3,
+            ..Default::default()
+        };
+        assert!(matches!(gs.set_config(bad), Err(GameError::InvalidConfig)));
+    }
+
+    /// Starting a hand with one player is a different problem from starting it with two players
+    /// but only one with chips
+    #[test]
+    fn not_enough_players() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        assert!(matches!(gs.start_hand(), Err(GameError::NotEnoughPlayers)));
+        gs.try_sit(2, 1000).unwrap();
+        gs.players.player_by_id_mut(2).unwrap().stack = 0;
+        assert!(matches!(
+            gs.start_hand(),
+            Err(GameError::NotEnoughFundedPlayers)
+        ));
+        // sitting out by choice isn't about chips
+        gs.players.player_by_id_mut(2).unwrap().stack = 1000;
+        gs.players.player_by_id_mut(2).unwrap().play_status = PlayStatus::SittingOut;
+        assert!(matches!(gs.start_hand(), Err(GameError::NotEnoughPlayers)));
+    }
+
+    /// A new deck per hand deals different flops from different seeds, a fixed deck deals the same
+    /// one every time, and a continuous shuffler keeps all 52 cards in play
+    #[test]
+    fn deck_modes() {
+        let flops = |deck_mode: DeckMode| -> Vec<[Option<Card>; 3]> {
+            let mut gs = GameState::default();
+            gs.set_config(GameConfig {
+                deck_mode,
+                ..Default::default()
+            })
+            .unwrap();
+            gs.try_sit(1, 1000).unwrap();
+            gs.try_sit(2, 1000).unwrap();
+            (1..=3)
+                .map(|n| {
+                    gs.start_hand_with_seed(DeckSeed::new([n; 32])).unwrap();
+                    gs.player_calls(gs.nta().unwrap().1.id).unwrap();
+                    gs.player_checks(gs.nta().unwrap().1.id).unwrap();
+                    let flop = [gs.community[0], gs.community[1], gs.community[2]];
+                    assert_eq!(gs.deck.remaining(), 52 - 4 - 1 - 3);
+                    let pid = gs.nta().unwrap().1.id;
+                    gs.player_folds(pid).unwrap();
+                    flop
+                })
+                .collect()
+        };
+        let per_hand = flops(DeckMode::PerHand);
+        assert_ne!(per_hand[0], per_hand[1]);
+        assert_ne!(per_hand[1], per_hand[2]);
+        let fixed = flops(DeckMode::Fixed(DeckSeed::new([9; 32])));
+        assert_eq!(fixed[0], fixed[1]);
+        assert_eq!(fixed[1], fixed[2]);
+        let csm = flops(DeckMode::ContinuousShuffle);
+        assert_ne!(csm[0], csm[1]);
+    }
+
+    /// The dead cards are the board and everyone's pocket, and the available cards are the rest
+    #[test]
+    fn dead_cards() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.dead_cards().len(), 4);
+        gs.player_calls(gs.nta().unwrap().1.id).unwrap();
+        gs.player_checks(gs.nta().unwrap().1.id).unwrap();
+        assert_eq!(gs.state(), State::Street(Street::Flop));
+        let dead = gs.dead_cards();
+        assert_eq!(dead.len(), 7);
+        let available = gs.available_cards();
+        assert_eq!(available.len(), 52 - 7);
+        assert!(available.iter().all(|c| !dead.contains(c)));
+    }
+
+    /// When the board beats everyone's pocket, the chop is flagged as everyone playing the board
+    #[test]
+    fn hand_result_plays_board() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let cards = crate::deck::cards_from_str("8h8d8s5c5h2c3d7h9c");
+        gs.players.player_by_id_mut(1).unwrap().pocket = Some([cards[5], cards[6]]);
+        gs.players.player_by_id_mut(2).unwrap().pocket = Some([cards[7], cards[8]]);
+        while !matches!(gs.state(), State::EndOfHand) {
+            if matches!(gs.state(), State::Street(Street::River)) {
+                for (i, c) in cards[..5].iter().enumerate() {
+                    gs.community[i] = Some(*c);
+                }
+            }
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let mut results: Vec<_> = gs
+            .logs
+            .items_since(0)
+            .filter_map(|(_, li)| match li {
+                LogItem::HandResult(pid, class, _, plays_board) => Some((pid, class, plays_board)),
+                _ => None,
+            })
+            .collect();
+        results.sort_by_key(|(pid, _, _)| *pid);
+        assert_eq!(
+            results,
+            vec![
+                (1, HandClass::FullHouse, true),
+                (2, HandClass::FullHouse, true)
+            ]
+        );
+    }
+
+    /// Nobody shows down when everyone else folds
+    #[test]
+    fn showdown_hands_everyone_folds() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert!(gs.showdown_hands().is_empty());
+    }
+
+    /// A fold-around is logged as won by fold, with nobody revealing anything, and a hand that
+    /// goes to showdown isn't
+    #[test]
+    fn won_by_fold() {
+        let ending = |gs: &GameState| -> Vec<LogItem> {
+            gs.logs
+                .items_since(0)
+                .map(|(_, li)| li)
+                .filter(|li| matches!(li, LogItem::WonByFold(_) | LogItem::HandReveal(_, _)))
+                .collect()
+        };
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let folder = gs.nta().unwrap().1.id;
+        gs.player_folds(folder).unwrap();
+        let raiser = gs.nta().unwrap().1.id;
+        gs.player_raises(raiser, DEF_BB * 3).unwrap();
+        let folder = gs.nta().unwrap().1.id;
+        gs.player_folds(folder).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert_eq!(ending(&gs), vec![LogItem::WonByFold(raiser)]);
+
+        gs.start_hand().unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let wbf = ending(&gs)
+            .into_iter()
+            .filter(|li| matches!(li, LogItem::WonByFold(_)))
+            .count();
+        assert_eq!(wbf, 1);
+    }
+
+    /// New settings are rejected mid-hand, and otherwise used from the next hand on
+    #[test]
+    fn set_config() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        let config = GameConfig {
+            small_blind: 25,
+            big_blind: 50,
+            ..gs.config().clone()
+        };
+        gs.start_hand().unwrap();
+        assert!(matches!(
+            gs.set_config(config.clone()),
+            Err(GameError::HandInProgress)
+        ));
+        assert_eq!(gs.config().big_blind, DEF_BB);
+        let nta = gs.nta().unwrap().1.id;
+        gs.player_folds(nta).unwrap();
+        // bad settings are rejected even between hands
+        let bad = GameConfig {
+            small_blind: 100,
+            ..config.clone()
+        };
+        assert!(matches!(gs.set_config(bad), Err(GameError::InvalidConfig)));
+        let bad = GameConfig {
+            max_players: 1,
+            ..config.clone()
+        };
+        assert!(matches!(gs.set_config(bad), Err(GameError::InvalidConfig)));
+        gs.set_config(config).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.pot_total_value(), 75);
+        assert_eq!(gs.current_bet(), 50);
+    }
+
+    /// The blinds go up on schedule, and stay at the last level once past it
+    #[test]
+    fn blind_schedule() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        let schedule = BlindSchedule {
+            levels: vec![(5, 10, 0), (10, 20, 5)],
+            hands_per_level: 2,
+        };
+        let bad = GameConfig {
+            blind_schedule: Some(BlindSchedule {
+                hands_per_level: 0,
+                ..schedule.clone()
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(gs.set_config(bad), Err(GameError::InvalidConfig)));
+        let config = GameConfig {
+            blind_schedule: Some(schedule),
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        let changed = |gs: &GameState| {
+            gs.logs
+                .items_since(0)
+                .filter(|(_, li)| matches!(li, LogItem::BlindsChanged(_, _, _)))
+                .count()
+        };
+        for hand_num in 1..=4 {
+            gs.start_hand().unwrap();
+            // level 2 from hand 3: 10/20 blinds and two 5 antes
+            let (in_pot, changes) = if hand_num < 3 { (15, 0) } else { (40, 1) };
+            assert_eq!(gs.pot_total_value(), in_pot, "hand {hand_num}");
+            assert_eq!(changed(&gs), changes, "hand {hand_num}");
+            let nta = gs.nta().unwrap().1.id;
+            gs.player_folds(nta).unwrap();
+        }
+        assert_eq!(gs.config().big_blind, 20);
+        assert_eq!(gs.config().ante, AnteStyle::PerPlayer(5));
+    }
+
+    /// The big blind needn't be twice the small, but can't be less than it
+    #[test]
+    fn odd_blinds() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        let config = GameConfig {
+            small_blind: 1,
+            big_blind: 3,
+            min_chip: 1,
+            ..Default::default()
+        };
+        gs.set_config(config.clone()).unwrap();
+        gs.start_hand().unwrap();
+        let posted = |gs: &GameState, seat: SeatIdx| gs.players.players[seat].unwrap().bet_status;
+        assert_eq!(posted(&gs, gs.players.token_sb), BetStatus::In(1));
+        assert_eq!(posted(&gs, gs.players.token_bb), BetStatus::In(3));
+        assert_eq!(gs.current_bet(), 3);
+        assert_eq!(gs.min_raise(), 6);
+        let nta = gs.nta().unwrap().1.id;
+        gs.player_folds(nta).unwrap();
+        let flat = GameConfig {
+            big_blind: 1,
+            ..config.clone()
+        };
+        gs.set_config(flat).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.pot_total_value(), 2);
+        let nta = gs.nta().unwrap().1.id;
+        gs.player_folds(nta).unwrap();
+        let backwards = GameConfig {
+            small_blind: 3,
+            big_blind: 1,
+            ..config
+        };
+        assert!(matches!(
+            gs.set_config(backwards),
+            Err(GameError::InvalidConfig)
+        ));
+    }
+
+    /// Fixed limit bets and raises are by exactly the street's increment, and the fifth raise of a
+    /// street is one too many
+    #[test]
+    fn fixed_limit() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        let config = GameConfig {
+            betting_limit: BettingLimit::FixedLimit {
+                small_bet: 10,
+                big_bet: 20,
+                cap: 4,
+            },
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        gs.start_hand().unwrap();
+        // preflop, raises are by the small bet
+        let sb = gs.nta().unwrap().1.id;
+        let bb = 3 - sb;
+        assert!(matches!(
+            gs.player_raises(sb, 25),
+            Err(GameError::InvalidBet)
+        ));
+        gs.player_calls(sb).unwrap();
+        gs.player_checks(bb).unwrap();
+        // flop: bet, then four raises
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        assert!(matches!(gs.player_bets(bb, 20), Err(GameError::InvalidBet)));
+        gs.player_bets(bb, 10).unwrap();
+        let mut raiser = sb;
+        for to in [20, 30, 40, 50] {
+            gs.player_raises(raiser, to).unwrap();
+            raiser = 3 - raiser;
+        }
+        assert!(matches!(
+            gs.player_raises(raiser, 60),
+            Err(GameError::InvalidBet)
+        ));
+        gs.player_calls(raiser).unwrap();
+        // turn: the cap is reset and the increment is the big bet
+        assert!(matches!(gs.state(), State::Street(Street::Turn)));
+        assert!(matches!(gs.player_bets(bb, 10), Err(GameError::InvalidBet)));
+        gs.player_bets(bb, 20).unwrap();
+        gs.player_raises(sb, 40).unwrap();
+    }
+
+    fn fixed_limit_heads_up() -> (GameState, PlayerId, PlayerId) {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.set_config(GameConfig {
+            betting_limit: BettingLimit::FixedLimit {
+                small_bet: 10,
+                big_bet: 20,
+                cap: 4,
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        gs.start_hand().unwrap();
+        let sb = gs.nta().unwrap().1.id;
+        (gs, sb, 3 - sb)
+    }
+
+    /// Going all in at a fixed limit table is for no more than one raise
+    #[test]
+    fn fixed_limit_no_shoving() {
+        let (mut gs, sb, _) = fixed_limit_heads_up();
+        assert!(matches!(
+            gs.player_action(sb, BetAction::AllIn(1000)),
+            Err(GameError::InvalidBet)
+        ));
+        assert_eq!(gs.current_bet(), DEF_BB);
+        // with only a raise's worth left, all in is that raise
+        gs.players.player_by_id_mut(sb).unwrap().stack = 20 - DEF_SB;
+        gs.player_action(sb, BetAction::AllIn(20)).unwrap();
+        assert_eq!(gs.current_bet(), 20);
+    }
+
+    /// All in for a full raise counts towards the cap, and can't be made once the cap is reached.
+    /// All in for less than a call is still fine.
+    #[test]
+    fn fixed_limit_all_in_cap() {
+        let (mut gs, sb, bb) = fixed_limit_heads_up();
+        gs.player_calls(sb).unwrap();
+        gs.player_checks(bb).unwrap();
+        gs.player_bets(bb, 10).unwrap();
+        gs.player_raises(sb, 20).unwrap();
+        gs.player_raises(bb, 30).unwrap();
+        gs.player_raises(sb, 40).unwrap();
+        // the fourth raise is the BB all in for exactly one more increment
+        gs.players.player_by_id_mut(bb).unwrap().stack = 20;
+        gs.player_action(bb, BetAction::AllIn(50)).unwrap();
+        assert_eq!(gs.current_bet(), 50);
+        assert!(matches!(
+            gs.player_raises(sb, 60),
+            Err(GameError::InvalidBet)
+        ));
+        // and a cap reached by raises can't be raised by going all in either
+        let (mut gs, sb, bb) = fixed_limit_heads_up();
+        gs.player_calls(sb).unwrap();
+        gs.player_checks(bb).unwrap();
+        gs.player_bets(bb, 10).unwrap();
+        let mut raiser = sb;
+        for to in [20, 30, 40, 50] {
+            gs.player_raises(raiser, to).unwrap();
+            raiser = 3 - raiser;
+        }
+        gs.players.player_by_id_mut(raiser).unwrap().stack = 20;
+        assert!(matches!(
+            gs.player_action(raiser, BetAction::AllIn(60)),
+            Err(GameError::InvalidBet)
+        ));
+        gs.players.player_by_id_mut(raiser).unwrap().stack = 5;
+        gs.player_action(raiser, BetAction::AllIn(45)).unwrap();
+        assert_eq!(
+            gs.players.player_by_id(raiser).unwrap().bet_status,
+            BetStatus::AllIn(45)
+        );
+    }
+
+    /// Everyone antes before the blinds. A player that can't cover the ante is all in for what
+    /// they have and the rest of the antes go in a side pot.
+    #[test]
+    fn antes() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 4).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.try_sit(3, 1000).unwrap();
+        gs.try_sit(4, 1000).unwrap();
+        let config = GameConfig {
+            ante: AnteStyle::PerPlayer(10),
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        gs.start_hand().unwrap();
+        let short = gs.players.player_by_id(1).unwrap();
+        assert_eq!(short.bet_status, BetStatus::AllIn(4));
+        assert!(short.pocket.is_some());
+        // main pot of 4 from everyone and a side pot of the other 6 from everyone else
+        let logs: Vec<_> = gs.logs.items_since(0).map(|(_, item)| item).collect();
+        assert!(logs
+            .iter()
+            .any(|item| matches!(item, LogItem::Pot(pot::LogItem::RoundEnd(2)))));
+        // the blinds go on top of the antes
+        let blinds = if gs.players.players[gs.players.token_sb].unwrap().id == 1 {
+            DEF_BB
+        } else {
+            DEF_SB + DEF_BB
+        };
+        assert_eq!(gs.pot_total_value(), 4 + 3 * 10 + blinds);
+        assert_eq!(gs.current_bet(), DEF_BB);
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        assert_eq!(gs.total_chips_in_play(), 3004);
+    }
+
+    /// With a big blind ante, only the BB antes. A BB with exactly enough for the ante is all in
+    /// without posting the blind, and the antes are still there for anyone to win.
+    #[test]
+    fn big_blind_ante() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.try_sit(3, 1000).unwrap();
+        let config = GameConfig {
+            ante: AnteStyle::BigBlind,
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        // the first hand's BB is in seat 0; get them down to exactly one big blind
+        gs.players.players[0].as_mut().unwrap().stack = DEF_BB;
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_bb, 0);
+        let bb = gs.players.players[0].unwrap();
+        assert_eq!(bb.bet_status, BetStatus::AllIn(DEF_BB));
+        assert_eq!(bb.stack, 0);
+        assert!(bb.pocket.is_some());
+        // just the ante and the SB
+        assert_eq!(gs.pot_total_value(), DEF_BB + DEF_SB);
+        assert_eq!(gs.current_bet(), DEF_BB);
+        // everyone else folds, and the BB gets back the SB on top of their ante
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert_eq!(gs.players.players[0].unwrap().stack, DEF_BB + DEF_SB);
+        assert_eq!(gs.total_chips_in_play(), 2000 + DEF_BB);
+    }
+
+    /// The antes are winnable by everyone, not just the BB that put them in
+    #[test]
+    fn big_blind_ante_is_dead_money() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        let config = GameConfig {
+            ante: AnteStyle::BigBlind,
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.pot_total_value(), DEF_BB + DEF_SB + DEF_BB);
+        // the BB folds to a raise, so the raiser wins everything, ante included
+        let raiser = gs.nta().unwrap().1.id;
+        gs.player_raises(raiser, 100).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        let winner = gs.players.player_by_id(raiser).unwrap();
+        assert_eq!(winner.stack, 1000 + DEF_SB + DEF_BB + DEF_BB);
+    }
+
+    /// No more players can sit than the config allows
+    #[test]
+    fn max_players() {
+        let mut gs = GameState::default();
+        let config = GameConfig {
+            max_players: 2,
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        assert!(matches!(gs.try_sit(3, 1000), Err(GameError::TableFull)));
+    }
+
+    /// Stacks outside the table's buy-in limits can't sit, but ones right at a limit can
+    #[test]
+    fn buyin_limits() {
+        let mut gs = GameState::default();
+        let config = GameConfig {
+            min_buyin: Some(400),
+            max_buyin: Some(1000),
+            ..Default::default()
+        };
+        gs.set_config(config.clone()).unwrap();
+        assert!(matches!(gs.try_sit(1, 1001), Err(GameError::InvalidBuyIn)));
+        assert!(matches!(gs.try_sit(1, 399), Err(GameError::InvalidBuyIn)));
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 400).unwrap();
+        let backwards = GameConfig {
+            min_buyin: Some(1001),
+            ..config
+        };
+        assert!(matches!(
+            gs.set_config(backwards),
+            Err(GameError::InvalidConfig)
+        ));
+    }
+
+    /// Replaying a hand's log rebuilds the stacks, both partway through and at the end
+    #[test]
+    fn replay() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let stacks = |gs: &GameState| -> Vec<(PlayerId, Currency)> {
+            gs.players
+                .players_iter(PlayerFilter::ALL)
+                .map(|(_, p)| (p.id, p.stack))
+                .collect()
+        };
+        let mut midway = None;
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            if matches!(gs.state(), State::Street(Street::Turn)) && midway.is_none() {
+                gs.player_bets(pid, 50).unwrap();
+                let seq = gs.logs.items_since(0).last().unwrap().0;
+                let nta = gs.nta().map(|(seat, _)| seat);
+                midway = Some((seq, stacks(&gs), gs.pot_total_value(), gs.community, nta));
+            } else if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let logs: Vec<_> = gs.logs.items_since(0).collect();
+        let base = match &logs[0].1 {
+            LogItem::NewBaseState(bs) => bs.clone(),
+            _ => panic!("hand log should start with a base state"),
+        };
+        let (seq, mid_stacks, mid_pot, mid_community, mid_nta) = midway.unwrap();
+        let replayed = GameState::replay(&base, &logs, seq);
+        assert_eq!(stacks(&replayed), mid_stacks);
+        assert_eq!(replayed.pot_total_value(), mid_pot);
+        assert_eq!(replayed.community, mid_community);
+        assert_eq!(replayed.nta().map(|(seat, _)| seat), mid_nta);
+        let replayed = GameState::replay(&base, &logs, logs.last().unwrap().0);
+        assert_eq!(stacks(&replayed), stacks(&gs));
+        assert_eq!(replayed.state(), State::EndOfHand);
+        assert_eq!(replayed.community, gs.community);
+    }
+
+    /// Players can pick their seat, as long as it exists and nobody's in it
+    #[test]
+    fn try_sit_at() {
+        let mut gs = GameState::default();
+        gs.try_sit_at(1, 1000, 4).unwrap();
+        assert_eq!(gs.seat_of(1), Some(4));
+        assert!(matches!(
+            gs.try_sit_at(2, 1000, 4),
+            Err(GameError::SeatTaken)
+        ));
+        assert!(matches!(
+            gs.try_sit_at(2, 1000, MAX_PLAYERS),
+            Err(GameError::InvalidSeat)
+        ));
+        assert!(matches!(
+            gs.try_sit_at(1, 1000, 5),
+            Err(GameError::PlayerAlreadySeated)
+        ));
+        // the next empty seat is still the first one
+        gs.try_sit(2, 1000).unwrap();
+        assert_eq!(gs.seat_of(2), Some(0));
+    }
+
+    /// Nobody still in the hand may leave, but anyone who has folded or is between hands may
+    #[test]
+    fn stand_up() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        assert!(matches!(gs.stand_up(4), Err(GameError::PlayerNotFound)));
+        gs.start_hand().unwrap();
+        let first = gs.nta().unwrap().1.id;
+        assert!(matches!(
+            gs.stand_up(first),
+            Err(GameError::BettingPlayerCantStand)
+        ));
+        gs.player_folds(first).unwrap();
+        assert_eq!(gs.stand_up(first).unwrap(), 1000);
+        assert_eq!(gs.seat_of(first), None);
+        let next = gs.nta().unwrap().1.id;
+        assert!(matches!(
+            gs.stand_up(next),
+            Err(GameError::BettingPlayerCantStand)
+        ));
+        gs.player_folds(next).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        let last = (1..=3).find(|pid| ![first, next].contains(pid)).unwrap();
+        assert!(gs.stand_up(last).unwrap() > 1000);
+    }
+
+    /// Someone can take the seat of a player that folded and left mid-hand, or any other empty
+    /// seat, but they aren't in the hand until the next one
+    #[test]
+    fn sit_mid_hand() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let first = gs.nta().unwrap().1.id;
+        let seat = gs.seat_of(first).unwrap();
+        gs.player_folds(first).unwrap();
+        gs.stand_up(first).unwrap();
+        gs.try_sit_at(4, 1000, seat).unwrap();
+        gs.try_sit(5, 1000).unwrap();
+        for pid in [4, 5] {
+            let p = gs.players.player_by_id(pid).unwrap();
+            assert_eq!(p.bet_status, BetStatus::Folded);
+            assert!(p.pocket.is_none());
+        }
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            assert!(![4, 5].contains(&pid));
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        assert_eq!(gs.players.player_by_id(4).unwrap().stack, 1000);
+        assert_eq!(gs.players.player_by_id(5).unwrap().stack, 1000);
+        assert_eq!(gs.total_chips_in_play(), 4000);
+        // both are dealt in next hand: one is the BB, and the other sat down on the wrong side of
+        // the button so posts one to come in
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_bb, seat);
+        for pid in [4, 5] {
+            let p = gs.players.player_by_id(pid).unwrap();
+            assert!(p.pocket.is_some());
+            assert_eq!(p.bet_status, BetStatus::In(DEF_BB));
+        }
+    }
+
+    /// Players can top up between hands, to no more than the max buy-in, but not during a hand
+    #[test]
+    fn add_chips() {
+        let mut gs = GameState::default();
+        let config = GameConfig {
+            max_buyin: Some(1000),
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 500).unwrap();
+        gs.add_chips(2, 300).unwrap();
+        assert_eq!(gs.players.player_by_id(2).unwrap().stack, 800);
+        assert!(gs
+            .logs
+            .items_since(0)
+            .any(|(_, li)| li == LogItem::ChipsAdded(2, 300, 800)));
+        assert!(matches!(gs.add_chips(2, 201), Err(GameError::InvalidBuyIn)));
+        assert!(matches!(gs.add_chips(2, 0), Err(GameError::InvalidBuyIn)));
+        assert!(matches!(gs.add_chips(3, 1), Err(GameError::PlayerNotFound)));
+        gs.start_hand().unwrap();
+        assert!(matches!(
+            gs.add_chips(2, 100),
+            Err(GameError::HandInProgress)
+        ));
+        let total = gs.total_chips_in_play();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        gs.add_chips(pid, 5).unwrap();
+        assert_eq!(gs.total_chips_in_play(), total + 5);
+    }
+
+    /// Every hand started gets the next number, whether or not it's finished
+    #[test]
+    fn hand_num() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        assert_eq!(gs.hand_num(), 0);
+        for _ in 0..3 {
+            gs.start_hand().unwrap();
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        assert_eq!(gs.hand_num(), 3);
+    }
+
+    /// Everyone dealt in is in the hand start log item, with their stack before the blinds
+    #[test]
+    fn hand_start_log() {
+        let mut gs = GameState::default();
+        for pid in 1..=4 {
+            gs.try_sit(pid, 1000 * pid as Currency).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let (hand_num, button, players) = gs
+            .logs
+            .items_since(0)
+            .find_map(|(_, li)| match li {
+                LogItem::HandStart {
+                    hand_num,
+                    button,
+                    players,
+                } => Some((hand_num, button, players)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(hand_num, 1);
+        assert_eq!(button, gs.players.token_dealer);
+        assert_eq!(players.len(), 4);
+        for (seat, pid, stack) in players {
+            assert_eq!(gs.seat_of(pid), Some(seat));
+            assert_eq!(stack, 1000 * pid as Currency);
+        }
+        assert!(gs
+            .filtered_changes_since(0, 1)
+            .any(|(_, li)| matches!(li, LogItem::HandStart { .. })));
+    }
+
+    /// The seed revealed after the hand matches the commitment logged before it, and shuffles the
+    /// same deck
+    #[test]
+    fn deck_commitment() {
+        let seed = DeckSeed::new([9; 32]);
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        let mut other = gs.clone();
+        gs.start_hand_with_seed(seed).unwrap();
+        let commitment = gs
+            .logs
+            .items_since(0)
+            .find_map(|(_, li)| match li {
+                LogItem::DeckCommitment(c) => Some(c),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(gs.revealed_seed(), None);
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_folds(pid).unwrap();
+        let revealed = gs.revealed_seed().unwrap();
+        assert_eq!(revealed.commitment(), commitment);
+        assert_ne!(DeckSeed::new([8; 32]).commitment(), commitment);
+        other.start_hand_with_deck(Deck::new(&revealed)).unwrap();
+        for pid in [1, 2] {
+            assert_eq!(
+                gs.players.player_by_id(pid).unwrap().pocket,
+                other.players.player_by_id(pid).unwrap().pocket,
+            );
+        }
+        assert_eq!(gs.deck, other.deck);
+    }
+
+    /// Folding the small blind isn't voluntarily putting money in, but calling it is
+    #[test]
+    fn player_stats() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        assert_eq!(gs.player_stats(1), None);
+        gs.start_hand().unwrap();
+        // heads up, the SB acts first preflop
+        let sb = gs.nta().unwrap().1.id;
+        let bb = 3 - sb;
+        gs.player_folds(sb).unwrap();
+        let stats = gs.player_stats(sb).unwrap();
+        assert_eq!((stats.hands_dealt, stats.vpip_hands), (1, 0));
+        assert!(stats.net_won < 0);
+        assert_eq!(gs.player_stats(bb).unwrap().net_won, -stats.net_won);
+        assert_eq!(gs.player_stats(bb).unwrap().vpip_hands, 0);
+
+        gs.start_hand().unwrap();
+        assert_eq!(gs.nta().unwrap().1.id, bb);
+        gs.player_calls(bb).unwrap();
+        gs.player_checks(sb).unwrap();
+        // betting on the flop isn't preflop
+        gs.player_bets(sb, 20).unwrap();
+        gs.player_folds(bb).unwrap();
+        let bb_stats = gs.player_stats(bb).unwrap();
+        assert_eq!((bb_stats.hands_dealt, bb_stats.vpip_hands), (2, 1));
+        let sb_stats = gs.player_stats(sb).unwrap();
+        assert_eq!((sb_stats.hands_dealt, sb_stats.vpip_hands), (2, 0));
+        assert_eq!(bb_stats.net_won + sb_stats.net_won, 0);
+        assert_eq!(
+            gs.players.player_by_id(sb).unwrap().stack,
+            1000 + sb_stats.net_won
+        );
+        assert_eq!(gs.player_stats(3), None);
+    }
+
+    /// Exactly one player's turn at a time, and nobody's between hands
+    #[test]
+    fn is_players_turn() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        assert!((1..=4).all(|pid| !gs.is_players_turn(pid)));
+        gs.start_hand().unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let nta = gs.nta().unwrap().1.id;
+            for pid in 1..=4 {
+                assert_eq!(gs.is_players_turn(pid), pid == nta);
+            }
+            gs.player_folds(nta).unwrap();
+        }
+        assert!((1..=4).all(|pid| !gs.is_players_turn(pid)));
+    }
+
+    /// Decision help is only for the player to act, and carries their pot odds
+    #[test]
+    fn decision_help() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let nta = gs.nta().unwrap().1.id;
+        let other = 3 - nta;
+        assert_eq!(gs.pot_odds(other), None);
+        assert!(gs.decision_help(other).is_none());
+        // heads up, the SB acts first preflop and has to complete to the BB
+        let odds = gs.pot_odds(nta).unwrap();
+        let to_call = DEF_BB - DEF_SB;
+        assert_eq!(odds, to_call as f64 / (DEF_SB + DEF_BB + to_call) as f64);
+        let help = gs.decision_help(nta).unwrap();
+        assert_eq!(help.pot_odds, Some(odds));
+        assert!((0.0..=1.0).contains(&help.equity));
+        // no board yet, so no outs to count
+        assert!(help.outs.is_empty());
+    }
+
+    /// Calling a bet of 50 into 100 needs a quarter of the final pot to break even, and there are
+    /// no odds to get when checking is free
+    #[test]
+    fn pot_odds() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_raises(pid, 50).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_calls(pid).unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        assert_eq!(gs.pot_total_value(), 100);
+        let bettor = gs.nta().unwrap().1.id;
+        assert_eq!(gs.pot_odds(bettor), None);
+        assert_eq!(gs.decision_help(bettor).unwrap().pot_odds, None);
+        gs.player_bets(bettor, 50).unwrap();
+        assert_eq!(gs.pot_odds(3 - bettor), Some(0.25));
+    }
+
+    /// A player that can't cover the bet only gets odds on what they have left to put in
+    #[test]
+    fn pot_odds_all_in_for_less() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.try_sit(3, 30).unwrap();
+        gs.start_hand().unwrap();
+        while gs.nta().unwrap().1.id != 3 {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_raises(pid, 100).unwrap();
+        }
+        let pot = gs.pot_total_value();
+        let stack = gs.players.player_by_id(3).unwrap().stack;
+        assert_eq!(gs.pot_odds(3), Some(stack as f64 / (pot + stack) as f64));
+    }
+
+    /// Playing out a whole hand neither creates nor destroys chips
+    #[test]
+    fn total_chips_in_play() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 500).unwrap();
+        gs.try_sit(3, 250).unwrap();
+        assert_eq!(gs.total_chips_in_play(), 1750);
+        gs.start_hand().unwrap();
+        assert_eq!(gs.total_chips_in_play(), 1750);
+        while !matches!(gs.state(), State::EndOfHand) {
+            let player = gs.nta().unwrap().1;
+            let pid = player.id;
+            if pid == 3 && player.stack > 0 {
+                // everything player 3 sat down with
+                gs.player_action(pid, BetAction::AllIn(250)).unwrap();
+            } else if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+            assert_eq!(gs.total_chips_in_play(), 1750);
+        }
+        assert_eq!(gs.pot_total_value(), 0);
+        assert_eq!(gs.total_chips_in_play(), 1750);
+    }
+
+    /// A stacked deck without enough cards for the hand is rejected up front, before anything
+    /// about the table changes
+    #[test]
+    fn deck_exhausted() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        let before = gs.clone();
+        // 3 players need 6 pocket cards, 5 board cards, and 3 burns
+        let cards: Vec<Card> = Card::all().take(13).collect();
+        assert!(matches!(
+            gs.start_hand_with_deck(Deck::from_cards(cards.clone())),
+            Err(GameError::DeckExhausted)
+        ));
+        assert_eq!(
+            gs.logs.items_since(0).count(),
+            before.logs.items_since(0).count()
+        );
+        assert_eq!(gs, before);
+        let mut cards = cards;
+        cards.push(Card::all().last().unwrap());
+        gs.start_hand_with_deck(Deck::from_cards(cards)).unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        // and the next hand gets a full deck of its own
+        gs.start_hand().unwrap();
+        assert_eq!(gs.deck.len(), 52 - 6);
+    }
+
+    /// A player that times out checks when they can and folds when they can't, and both are
+    /// logged as automatic actions.
+    #[test]
+    fn auto_action() {
+        let auto_actions = |gs: &GameState| -> Vec<LogItem> {
+            gs.logs
+                .items_since(0)
+                .map(|(_, item)| item)
+                .filter(|item| matches!(item, LogItem::AutoAction { .. }))
+                .collect()
+        };
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        // heads up: player 2 is SB and acts first
+        assert!(matches!(
+            gs.auto_action(1, AutoActionReason::Timeout),
+            Err(GameError::OutOfTurn)
+        ));
+        gs.player_calls(2).unwrap();
+        // BB has the option, so times out into a check
+        gs.auto_action(1, AutoActionReason::Timeout).unwrap();
+        assert_eq!(
+            auto_actions(&gs),
+            vec![LogItem::AutoAction {
+                player_id: 1,
+                action: BetAction::Check,
+                reason: AutoActionReason::Timeout,
+            }]
+        );
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        // facing a bet, a disconnected player folds
+        gs.player_bets(1, 20).unwrap();
+        gs.auto_action(2, AutoActionReason::Disconnect).unwrap();
+        assert_eq!(
+            auto_actions(&gs)[1],
+            LogItem::AutoAction {
+                player_id: 2,
+                action: BetAction::Fold,
+                reason: AutoActionReason::Disconnect,
+            }
+        );
+        assert!(matches!(gs.state(), State::EndOfHand));
+    }
+
+    /// A player that asks to sit out mid-hand plays the hand out, isn't dealt the next one, and
+    /// is dealt in again once they sit back in
+    #[test]
+    fn sit_out() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        assert!(matches!(
+            gs.request_sit_out(4),
+            Err(GameError::PlayerNotFound)
+        ));
+        gs.request_sit_out(3).unwrap();
+        let status = |gs: &GameState, pid| gs.players.player_by_id(pid).unwrap().play_status;
+        assert_eq!(status(&gs, 3), PlayStatus::WantsSitOut);
+        let mut acted = vec![];
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            acted.push(pid);
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        assert!(acted.contains(&3));
+        gs.tick().unwrap();
+        assert_eq!(status(&gs, 3), PlayStatus::SittingOut);
+        assert!(gs.players.player_by_id(3).unwrap().pocket.is_none());
+        assert!(gs.players.player_by_id(1).unwrap().pocket.is_some());
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            assert_ne!(pid, 3);
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        gs.sit_back_in(3).unwrap();
+        gs.tick().unwrap();
+        assert_eq!(status(&gs, 3), PlayStatus::Playing);
+        assert!(gs.players.player_by_id(3).unwrap().pocket.is_some());
+    }
+
+    /// Running out of time folds a player that can't check, and the next player gets a fresh
+    /// clock
+    #[test]
+    fn action_timeout() {
+        let mut gs = GameState::default();
+        let config = GameConfig {
+            action_timeout: Some(30_000),
+            ..Default::default()
+        };
+        gs.set_config(config).unwrap();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.tick_with_now(1_000).unwrap();
+        // heads up: player 2 is SB and acts first
+        assert_eq!(gs.nta().unwrap().1.id, 2);
+        assert_eq!(gs.action_deadline(), Some(31_000));
+        gs.tick_with_now(30_999).unwrap();
+        assert_eq!(gs.nta().unwrap().1.id, 2);
+        gs.player_calls(2).unwrap();
+        assert_eq!(gs.action_deadline(), Some(60_999));
+        gs.tick_with_now(61_000).unwrap();
+        // player 1 could check, so they did
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        gs.player_bets(gs.nta().unwrap().1.id, 20).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        assert_eq!(gs.action_deadline(), Some(91_000));
+        gs.tick_with_now(91_000).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        assert_eq!(gs.action_deadline(), None);
+        assert!(gs.logs.items_since(0).any(|(_, li)| li
+            == LogItem::AutoAction {
+                player_id: pid,
+                action: BetAction::Fold,
+                reason: AutoActionReason::Timeout,
+            }));
+    }
+
+    /// Every time someone new is next to act with a running clock, clients are told how long they
+    /// have
+    #[test]
+    fn action_deadline_logs() {
+        let mut gs = GameState::default();
+        gs.set_config(GameConfig {
+            action_timeout: Some(30_000),
+            ..Default::default()
+        })
+        .unwrap();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.tick_with_now(1_000).unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        let items: Vec<LogItem> = gs.logs.items_since(0).map(|(_, li)| li).collect();
+        let mut ntas = 0;
+        for (i, li) in items.iter().enumerate() {
+            if let LogItem::NextToAct(seat) = li {
+                ntas += 1;
+                assert_eq!(items[i + 1], LogItem::ActionDeadline(*seat, 30_000));
+            }
+        }
+        // preflop and three streets after
+        assert!(ntas >= 4);
+        let deadlines = items
+            .iter()
+            .filter(|li| matches!(li, LogItem::ActionDeadline(_, _)))
+            .count();
+        assert_eq!(deadlines, ntas);
+    }
+
+    /// An admin can fold players both out of turn and on their turn, and the rest of the hand
+    /// carries on in the usual order.
+    #[test]
+    fn force_action() {
+        let mut gs = GameState::default();
+        for pid in 1..=4 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let btn = gs.player_at(gs.players.token_dealer).unwrap().id;
+        let sb = gs.player_at(gs.players.token_sb).unwrap().id;
+        let bb = gs.player_at(gs.players.token_bb).unwrap().id;
+        let utg = gs.nta().unwrap().1.id;
+        assert!(![btn, sb, bb].contains(&utg));
+        // forced actions still have to be legal: the SB can't check facing the BB, or raise to
+        // less than the min raise, and a refused one leaves no trace
+        let before = gs.clone();
+        assert!(gs.force_action(sb, BetAction::Check).is_err());
+        assert!(gs.force_action(sb, BetAction::Raise(DEF_BB + 1)).is_err());
+        assert_eq!(gs, before);
+        assert_eq!(gs.nta().unwrap().1.id, utg);
+        // out of turn: BTN is folded, but UTG is still next to act
+        gs.force_action(btn, BetAction::Fold).unwrap();
+        assert_eq!(
+            gs.players.player_by_id(btn).unwrap().bet_status,
+            BetStatus::Folded
+        );
+        assert!(gs
+            .logs
+            .items_since(0)
+            .any(|(_, li)| li == LogItem::ForcedAction(btn, BetAction::Fold)));
+        assert_eq!(gs.nta().unwrap().1.id, utg);
+        assert!(gs.force_action(btn, BetAction::Fold).is_err());
+        // on their turn: UTG folds and action moves to the SB, skipping the folded BTN
+        gs.force_action(utg, BetAction::Fold).unwrap();
+        assert_eq!(gs.nta().unwrap().1.id, sb);
+        assert!(matches!(gs.state(), State::Street(Street::PreFlop)));
+        // the rest of the hand plays normally
+        gs.player_calls(sb).unwrap();
+        gs.player_checks(bb).unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        assert_eq!(gs.pot_total_value(), 2 * DEF_BB);
+    }
+
+    /// Diffing a state before and after a single raise gives what the game itself logged for it.
+    #[test]
+    fn diff() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let before = gs.clone();
+        assert!(before.diff(&gs).is_empty());
+        let n_logs = gs.logs.items_since(0).count();
+        // heads up: player 2 is SB and acts first
+        gs.player_raises(2, 30).unwrap();
+        let expected = vec![
+            LogItem::CurrentBetSet(DEF_BB, 30, 2 * DEF_BB, 50),
+            pot::LogItem::Bet(2, BetAction::Raise(30)).into(),
+            LogItem::NextToAct(gs.seat_of(1).unwrap()),
+        ];
+        assert_eq!(before.diff(&gs), expected);
+        let logged: Vec<LogItem> = gs
+            .logs
+            .items_since(0)
+            .skip(n_logs)
+            .map(|(_, i)| i)
+            .collect();
+        assert_eq!(logged, expected);
+        // a stack changing for no reason means the client needs a new base state
+        let mut other = gs.clone();
+        other.players.player_by_id_mut(1).unwrap().stack += 100;
+        assert!(matches!(
+            gs.diff(&other)[..],
+            [LogItem::NewBaseState(_), ..]
+        ));
+    }
+
+    /// With a min chip of 5, raises and bets are rounded down to multiples of 5, but never below
+    /// what's legal, and amounts that were illegal to begin with are still rejected.
+    #[test]
+    fn min_chip() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        assert!(matches!(gs.set_min_chip(0), Err(GameError::InvalidMinChip)));
+        gs.set_min_chip(5).unwrap();
+        gs.start_hand().unwrap();
+        // heads up: player 2 is SB. Too small a raise is still too small
+        assert!(matches!(
+            gs.player_raises(2, 19),
+            Err(GameError::InvalidBet)
+        ));
+        gs.player_raises(2, 27).unwrap();
+        assert_eq!(gs.current_bet(), 25);
+        assert_eq!(gs.players.player_by_id(2).unwrap().stack, 975);
+        // rounding down would put this under the min raise of 40, so it's snapped to 40 instead
+        gs.player_raises(1, 42).unwrap();
+        assert_eq!(gs.current_bet(), 40);
+        gs.player_calls(2).unwrap();
+        // post flop, bets are rounded down too
+        gs.player_bets(1, 33).unwrap();
+        assert_eq!(gs.current_bet(), 30);
+        assert_eq!(gs.players.player_by_id(1).unwrap().stack, 930);
+    }
+
+    /// A min chip the blinds aren't multiples of doesn't change the blinds, only what players bet
+    #[test]
+    fn min_chip_uneven_blinds() {
+        for min_chip in [3, 25] {
+            let mut gs = GameState::default();
+            for pid in 1..=3 {
+                gs.try_sit(pid, 1000).unwrap();
+            }
+            gs.set_min_chip(min_chip).unwrap();
+            gs.start_hand().unwrap();
+            let sb = gs.players.players[gs.players.token_sb].unwrap();
+            let bb = gs.players.players[gs.players.token_bb].unwrap();
+            assert_eq!(sb.bet_status, BetStatus::In(DEF_SB));
+            assert_eq!(bb.bet_status, BetStatus::In(DEF_BB));
+            assert_eq!(gs.current_bet(), DEF_BB);
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_raises(pid, 61).unwrap();
+            assert_eq!(gs.current_bet(), 61 - 61 % min_chip);
+        }
+    }
+
+    /// When everyone folds to a raise, the part of it nobody called goes back to the raiser
+    /// instead of being won as part of the pot
+    #[test]
+    fn uncalled_bet_returned() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_calls(pid).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_checks(pid).unwrap();
+        // on the flop, B bets 40, A raises to 100, and B folds
+        let b = gs.nta().unwrap().1.id;
+        let a = 3 - b;
+        gs.player_bets(b, 40).unwrap();
+        gs.player_raises(a, 100).unwrap();
+        gs.player_folds(b).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        let logs: Vec<_> = gs.logs.items_since(0).collect();
+        assert!(logs
+            .iter()
+            .any(|(_, li)| *li == LogItem::Pot(pot::LogItem::UncalledBetReturned(a, 60))));
+        // only the matched 40 of the raise was in the pot
+        assert_eq!(gs.last_hand_net_pot(), Some(2 * DEF_BB + 40 + 40));
+        assert_eq!(
+            gs.players.player_by_id(a).unwrap().stack,
+            1000 + DEF_BB + 40
+        );
+        assert_eq!(
+            gs.players.player_by_id(b).unwrap().stack,
+            1000 - DEF_BB - 40
+        );
+        // and replaying the log gives the raiser back the same
+        let base = match &logs[0].1 {
+            LogItem::NewBaseState(bs) => bs.clone(),
+            _ => panic!("hand log should start with a base state"),
+        };
+        let replayed = GameState::replay(&base, &logs, logs.last().unwrap().0);
+        assert_eq!(
+            replayed.players.player_by_id(a).unwrap().stack,
+            1000 + DEF_BB + 40
+        );
+    }
+
+    /// The net pot is what the winners were paid, and it's remembered after the next hand starts.
+    #[test]
+    fn last_hand_net_pot() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        assert_eq!(gs.last_hand_net_pot(), None);
+        gs.start_hand().unwrap();
+        // heads up, SB (player 2) raises and BB (player 1) folds, so the 20 of the raise nobody
+        // called goes back instead of being won
+        gs.player_raises(2, 30).unwrap();
+        let pot = gs.pot_total_value() - 20;
+        gs.player_folds(1).unwrap();
+        assert!(matches!(gs.state(), State::EndOfHand));
+        let payouts: Currency = gs
+            .logs
+            .items_since(0)
+            .filter_map(|(_, item)| match item {
+                LogItem::Pot(pot::LogItem::Payouts(Some(_), payouts)) => {
+                    Some(payouts.values().sum::<Currency>())
+                }
+                _ => None,
+            })
+            .sum();
+        assert_eq!(gs.last_hand_net_pot(), Some(payouts));
+        assert_eq!(gs.last_hand_net_pot(), Some(pot));
+        gs.start_hand().unwrap();
+        assert_eq!(gs.last_hand_net_pot(), Some(pot));
+    }
+
+    /// With a 5% rake, a 100 chip pot nets the winner 95
+    #[test]
+    fn last_hand_net_pot_raked() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.set_config(GameConfig {
+            rake: RakeConfig {
+                percent: 5,
+                cap: 10,
+                no_flop_no_drop: true,
+            },
+            ..gs.config().clone()
+        })
+        .unwrap();
+        gs.start_hand().unwrap();
+        gs.player_raises(2, 50).unwrap();
+        gs.player_calls(1).unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_checks(pid).unwrap();
+        }
+        let payouts: Currency = gs
+            .logs
+            .items_since(0)
+            .filter_map(|(_, item)| match item {
+                LogItem::Pot(pot::LogItem::Payouts(None, payouts)) => {
+                    Some(payouts.values().sum::<Currency>())
+                }
+                _ => None,
+            })
+            .sum();
+        assert_eq!(gs.last_hand_net_pot(), Some(95));
+        assert_eq!(payouts, 95);
+        assert_eq!(gs.rake_collected(), 5);
+        assert_eq!(gs.total_chips_in_play(), 1995);
+        // no flop, no drop
+        gs.start_hand().unwrap();
+        let nta = gs.nta().unwrap().1.id;
+        gs.player_folds(nta).unwrap();
+        assert_eq!(gs.rake_collected(), 5);
+    }
+
+    #[test]
+    fn chop_remaining_pot() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 500).unwrap();
+        gs.try_sit(3, 100).unwrap();
+        // doesn't conserve chips
+        let shares = HashMap::from([(1, 600), (2, 600), (3, 600)]);
+        assert!(matches!(
+            gs.chop_remaining_pot(&shares),
+            Err(GameError::InvalidDeal)
+        ));
+        // leaves a player out
+        let shares = HashMap::from([(1, 800), (2, 800)]);
+        assert!(matches!(
+            gs.chop_remaining_pot(&shares),
+            Err(GameError::InvalidDeal)
+        ));
+        // includes someone not at the table
+        let shares = HashMap::from([(1, 800), (2, 700), (3, 0), (4, 100)]);
+        assert!(matches!(
+            gs.chop_remaining_pot(&shares),
+            Err(GameError::InvalidDeal)
+        ));
+        assert_eq!(gs.player_at(0).unwrap().stack, 1000);
+        // valid
+        let shares = HashMap::from([(1, 700), (2, 550), (3, 350)]);
+        gs.chop_remaining_pot(&shares).unwrap();
+        for (pid, stack) in shares.iter() {
+            assert_eq!(gs.players.player_by_id(*pid).unwrap().stack, *stack);
+        }
+        assert!(matches!(
+            gs.logs.items_since(0).last(),
+            Some((_, LogItem::Deal(_)))
+        ));
+        // not allowed mid-hand
+        gs.start_hand().unwrap();
+        assert!(matches!(
+            gs.chop_remaining_pot(&shares),
+            Err(GameError::HandInProgress)
+        ));
+    }
+
+    /// Return the pockets that the given player would see dealt, by player ID
+    fn visible_pockets(gs: &GameState, viewer: PlayerId) -> Vec<PlayerId> {
+        gs.filtered_changes_since(0, viewer)
+            .filter_map(|(_, item)| match item {
+                LogItem::PocketDealt(pid, Some(_)) => Some(pid),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reveal_to_spectators() {
+        const SPECTATOR: PlayerId = 99;
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.set_reveal_to_spectators(1, true).unwrap();
+        assert!(gs.set_reveal_to_spectators(SPECTATOR, true).is_err());
+        gs.start_hand().unwrap();
+        // the spectator sees the opted-in player's pocket, but not the other's
+        assert_eq!(visible_pockets(&gs, SPECTATOR), vec![1]);
+        // seated opponents still only see their own
+        assert_eq!(visible_pockets(&gs, 3), vec![3]);
+    }
+
+    /// A broadcast of the table shows no pockets until showdown, opted in or not
+    #[test]
+    fn spectator_changes() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.set_reveal_to_spectators(1, true).unwrap();
+        gs.start_hand().unwrap();
+        gs.player_calls(2).unwrap();
+        gs.player_checks(1).unwrap();
+        let face_up = |gs: &GameState| {
+            gs.spectator_changes_since(0)
+                .filter(|(_, li)| {
+                    matches!(
+                        li,
+                        LogItem::PocketDealt(_, Some(_)) | LogItem::HandReveal(_, [Some(_), _])
+                    )
+                })
+                .count()
+        };
+        assert_eq!(face_up(&gs), 0);
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_checks(pid).unwrap();
+        }
+        assert!(face_up(&gs) > 0);
+        assert!(!gs
+            .spectator_changes_since(0)
+            .any(|(_, li)| matches!(li, LogItem::PocketDealt(_, Some(_)))));
+    }
+
+    /// At a tournament table, players that bust are out for good and finish in order: of two
+    /// that bust on the same hand, the one that started it with more chips finishes higher.
+    #[test]
+    fn tournament_elimination() {
+        let mut gs = GameState::default();
+        gs.set_config(GameConfig {
+            table_type: TableType::Tournament,
+            ..gs.config().clone()
+        })
+        .unwrap();
+        let stacks: HashMap<PlayerId, Currency> = HashMap::from([(1, 1000), (2, 500), (3, 300)]);
+        for pid in 1..=3 {
+            gs.try_sit(pid, stacks[&pid]).unwrap();
+        }
+        assert!(gs.standings().is_empty());
+        // pockets, then a burn before every street
+        let cards = crate::deck::cards_from_str("AhAdKsKc7c2d5sQsJh9d6h4c8s3h");
+        gs.start_hand_with_deck(Deck::from_cards(cards.clone()))
+            .unwrap();
+        gs.players.player_by_id_mut(1).unwrap().pocket = Some([cards[0], cards[1]]);
+        gs.players.player_by_id_mut(2).unwrap().pocket = Some([cards[2], cards[3]]);
+        gs.players.player_by_id_mut(3).unwrap().pocket = Some([cards[4], cards[5]]);
+        while !matches!(gs.state(), State::EndOfHand) {
+            match gs.nta() {
+                Some((_, p)) => {
+                    let pid = p.id;
+                    gs.player_action(pid, BetAction::AllIn(stacks[&pid]))
+                        .unwrap();
+                }
+                None => gs.run_out().unwrap(),
+            }
+        }
+        let status = |gs: &GameState, pid| gs.players.player_by_id(pid).unwrap().play_status;
+        assert_eq!(gs.players.player_by_id(1).unwrap().stack, 1800);
+        assert_eq!(status(&gs, 2), PlayStatus::Eliminated { place: 2 });
+        assert_eq!(status(&gs, 3), PlayStatus::Eliminated { place: 3 });
+        assert_eq!(gs.standings(), vec![(1, 1), (2, 2), (3, 3)]);
+        assert!(matches!(
+            gs.sit_back_in(3),
+            Err(GameError::PlayerEliminated)
+        ));
+        assert!(matches!(
+            gs.request_sit_out(2),
+            Err(GameError::PlayerEliminated)
+        ));
+    }
+
+    /// When the SB leaves, the button moves onto their empty seat and last hand's BB posts the SB
+    /// rather than getting the button. When the BB leaves, no one posts a SB.
+    #[test]
+    fn dead_button() {
+        let fold_around = |gs: &mut GameState| {
+            while !matches!(gs.state(), State::EndOfHand) {
+                let pid = gs.nta().unwrap().1.id;
+                gs.player_folds(pid).unwrap();
+            }
+        };
+        let posted = |gs: &GameState| -> Vec<(SeatIdx, BetStatus)> {
+            gs.players
+                .players_iter(PlayerFilter::ALL)
+                .filter(|(_, p)| p.bet_status != BetStatus::Waiting)
+                .map(|(seat, p)| (seat, p.bet_status))
+                .collect()
+        };
+        let mut gs = GameState::default();
+        for pid in 0..5 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        assert_eq!(
+            posted(&gs),
+            vec![(2, BetStatus::In(DEF_SB)), (3, BetStatus::In(DEF_BB))]
+        );
+        fold_around(&mut gs);
+        // the SB leaves: dead button
+        gs.stand_up(2).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_dealer, 2);
+        assert_eq!(
+            posted(&gs),
+            vec![(3, BetStatus::In(DEF_SB)), (4, BetStatus::In(DEF_BB))]
+        );
+        assert_eq!(gs.nta().unwrap().0, 0);
+        fold_around(&mut gs);
+        // the BB leaves: dead small blind
+        gs.stand_up(4).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_dealer, 3);
+        assert_eq!(gs.players.token_sb, 4);
+        assert_eq!(posted(&gs), vec![(0, BetStatus::In(DEF_BB))]);
+        assert_eq!(gs.nta().unwrap().0, 1);
+        fold_around(&mut gs);
+        // and the button follows the SB onto the empty seat again
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_dealer, 4);
+        assert_eq!(
+            posted(&gs),
+            vec![(0, BetStatus::In(DEF_SB)), (1, BetStatus::In(DEF_BB))]
+        );
+    }
+
+    /// Someone that sat down between the SB and BB is between the BTN and SB next hand. The BTN
+    /// went to last hand's SB, so the blinds still come first, even though the player after the
+    /// BTN isn't the SB.
+    #[test]
+    fn dead_button_player_before_sb() {
+        let mut gs = GameState::default();
+        for (pid, seat) in [(0, 0), (1, 3), (2, 5), (3, 7)] {
+            gs.try_sit_at(pid, 1000, seat).unwrap();
+        }
+        gs.start_hand().unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        gs.try_sit_at(4, 1000, 6).unwrap();
+        gs.start_hand().unwrap();
+        assert_eq!(
+            (
+                gs.players.token_dealer,
+                gs.players.token_sb,
+                gs.players.token_bb
+            ),
+            (5, 7, 0)
+        );
+        assert_eq!(gs.player_at(7).unwrap().bet_status, BetStatus::In(DEF_SB));
+        assert_eq!(gs.player_at(0).unwrap().bet_status, BetStatus::In(DEF_BB));
+        assert_eq!(gs.nta().unwrap().0, 3);
+    }
+
+    /// Someone sitting down between the button and the big blind has to post a big blind when
+    /// they're dealt in, while someone sitting down where the big blind is headed doesn't.
+    #[test]
+    fn post_to_enter() {
+        let mut gs = GameState::default();
+        for (pid, seat) in [(0, 0), (1, 3), (2, 5), (3, 7)] {
+            gs.try_sit_at(pid, 1000, seat).unwrap();
+        }
+        gs.start_hand().unwrap();
+        assert_eq!(
+            (
+                gs.players.token_dealer,
+                gs.players.token_sb,
+                gs.players.token_bb
+            ),
+            (3, 5, 7)
+        );
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        gs.try_sit_at(4, 1000, 4).unwrap();
+        gs.try_sit_at(8, 1000, 8).unwrap();
+        assert!(gs.player_at(4).unwrap().must_post);
+        assert!(!gs.player_at(8).unwrap().must_post);
+        // seat 4 isn't dealt in until they post
+        assert!(gs.player_at(4).unwrap().pocket.is_none());
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_bb, 8);
+        let posted: Vec<(SeatIdx, BetStatus)> = gs
+            .players
+            .players_iter(PlayerFilter::ALL)
+            .filter(|(_, p)| p.bet_status != BetStatus::Waiting)
+            .map(|(seat, p)| (seat, p.bet_status))
+            .collect();
+        assert_eq!(
+            posted,
+            vec![
+                (4, BetStatus::In(DEF_BB)),
+                (7, BetStatus::In(DEF_SB)),
+                (8, BetStatus::In(DEF_BB))
+            ]
+        );
+        assert!(gs.player_at(4).unwrap().pocket.is_some());
+        assert!(!gs.player_at(4).unwrap().must_post);
+        // the blind is live, so they can check when it comes back to them unraised
+        while gs.nta().unwrap().0 != 4 {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_calls(pid).unwrap();
+        }
+        assert!(gs.can_check(4));
+    }
+
+    /// A straddle raises the bet to call and makes the straddler the last to act preflop
+    #[test]
+    fn straddle() {
+        let mut gs = GameState::default();
+        for pid in 0..4 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand().unwrap();
+        assert_eq!(gs.players.token_bb, 3);
+        // only the player left of the BB can straddle, and for at least two BBs
+        assert!(matches!(
+            gs.post_straddle(1, DEF_BB * 2),
+            Err(GameError::InvalidStraddle)
+        ));
+        assert!(matches!(
+            gs.post_straddle(0, DEF_BB * 2 - 1),
+            Err(GameError::InvalidBet)
+        ));
+        gs.post_straddle(0, DEF_BB * 2).unwrap();
+        assert_eq!(gs.current_bet(), DEF_BB * 2);
+        assert_eq!(gs.min_raise(), DEF_BB * 4);
+        assert!(gs
+            .logs
+            .items_since(0)
+            .any(|(_, li)| li == LogItem::Straddle(0, DEF_BB * 2)));
+        assert!(matches!(
+            gs.post_straddle(0, DEF_BB * 3),
+            Err(GameError::InvalidStraddle)
+        ));
+        for pid in 1..4 {
+            assert_eq!(gs.nta().unwrap().1.id, pid);
+            gs.player_calls(pid).unwrap();
+        }
+        assert_eq!(gs.nta().unwrap().1.id, 0);
+        assert!(gs.can_check(0));
+        gs.player_checks(0).unwrap();
+        assert_eq!(gs.state(), State::Street(Street::Flop));
+    }
+
+    /// A player going all in for less than the minimum raise does not change the minimum raise.
+    /// Furthermore, the original raiser does not get the chance to raise again after them
+    /// (because they'd be raising themselves).
+    #[test]
+    fn minraise_fullbet_rule() {
+        let mut gs = GameState::default();
+        // The button moves to seat 1 for the first hand, so the player that acts first sits first
+        gs.try_sit(3, 1000).unwrap();
+        gs.try_sit(0, 600).unwrap(); // dealer
+        gs.try_sit(1, 1000).unwrap(); // small blind
+        gs.try_sit(2, 1000).unwrap(); // big blind
+        gs.start_hand().unwrap();
+        // sanity checks
+        assert_eq!(gs.players.token_dealer, 1);
+        assert_eq!(gs.players.token_sb, 2);
+        assert_eq!(gs.players.token_bb, 3);
+        // First raise. Not the critical moment
+        gs.player_raises(3, 500).unwrap();
+        assert_eq!(gs.min_raise(), 990);
+        assert_eq!(gs.current_bet(), 500);
+        // Second "raise" that's an all in. This is the first critical moment. The min raise
+        // shouldn't change, but current_bet should.
+        gs.player_action(0, BetAction::AllIn(600)).unwrap();
+        assert_eq!(gs.min_raise(), 990);
+        assert_eq!(gs.current_bet(), 600);
+
+        // player 1 gets out of the way and nothing changes
+        gs.player_folds(1).unwrap();
+        assert_eq!(gs.min_raise(), 990);
+        assert_eq!(gs.current_bet(), 600);
+        // player 2 calls and again nothing changes
+        gs.player_calls(2).unwrap();
+        assert_eq!(gs.min_raise(), 990);
+        assert_eq!(gs.current_bet(), 600);
+
+        // player 3 can't raise because that'd be raising themself. This is the second critial
+        // moment.
+        assert_eq!(gs.nta().unwrap().1.id, 3);
+        assert!(matches!(
+            gs.player_raises(3, 990),
+            Err(GameError::CantRaiseSelf)
+        ));
+        // but they can still call
+        gs.player_calls(3).unwrap();
+        assert_eq!(gs.current_bet(), 0);
+    }
+
+    /// A street can't end with a player that's in for less than the current bet
+    #[test]
+    fn uneven_bets() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_raises(pid, DEF_BB * 3).unwrap();
+        // the other blind hasn't called, but pretend nobody needs to act
+        let need_bets_from = std::mem::take(&mut gs.players.need_bets_from);
+        assert!(matches!(gs.advance_street(), Err(GameError::UnevenBets)));
+        assert_eq!(gs.state(), State::Street(Street::PreFlop));
+        assert!(gs.community.iter().all(Option::is_none));
+        // and once they call, the street ends as normal
+        gs.players.need_bets_from = need_bets_from;
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_calls(pid).unwrap();
+        assert_eq!(gs.state(), State::Street(Street::Flop));
+    }
+
+    /// State from a newer schema version is refused, and state from before there were versions is
+    /// upgraded
+    #[test]
+    fn migrate() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        let json = serde_json::to_string(&gs).unwrap();
+        assert_eq!(GameState::migrate(&json).unwrap(), gs);
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["schema_version"] = (SCHEMA_VERSION + 1).into();
+        let e = GameState::migrate(&value.to_string()).unwrap_err();
+        assert!(matches!(e, GameError::UnsupportedSchemaVersion(v) if v == SCHEMA_VERSION + 1));
+        assert!(e.to_string().contains(&SCHEMA_VERSION.to_string()));
+
+        for bad in ["{garbage", "[]", "{}"] {
+            assert!(matches!(
+                GameState::migrate(bad),
+                Err(GameError::InvalidState(_))
+            ));
+        }
+    }
+
+    /// State saved before there were schema versions, between hands and in the middle of one,
+    /// reads back with its blinds in the config and can carry on being played
+    #[test]
+    fn migrate_v0() {
+        let mut gs = GameState::migrate(include_str!("../testdata/v0-between-hands.json")).unwrap();
+        assert_eq!(gs.schema_version(), SCHEMA_VERSION);
+        assert_eq!(gs.state(), State::EndOfHand);
+        assert_eq!(gs.config().small_blind, 5);
+        assert_eq!(gs.config().big_blind, 10);
+        assert_eq!(gs.players.player_by_id(1).unwrap().stack, 1005);
+        gs.start_hand().unwrap();
+        assert_eq!(gs.total_chips_in_play(), 2500);
+
+        let mut gs = GameState::migrate(include_str!("../testdata/v0-mid-hand.json")).unwrap();
+        assert_eq!(gs.state(), State::Street(Street::Flop));
+        assert_eq!(gs.current_bet(), 40);
+        assert_eq!(gs.pot_total_value(), 70);
+        assert_eq!(gs.total_chips_in_play(), 2500);
+        while !matches!(gs.state(), State::EndOfHand) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        assert_eq!(gs.total_chips_in_play(), 2500);
+        gs.start_hand().unwrap();
+        assert_eq!(gs.total_chips_in_play(), 2500);
+    }
+
+    /// The binary format reads back exactly what was written, mid-hand with bets in the pot, and is
+    /// smaller than the JSON
+    #[test]
+    fn bytes_round_trip() {
+        let mut gs = GameState::default();
+        for pid in 1..=3 {
+            gs.try_sit(pid, 1000).unwrap();
+        }
+        gs.start_hand_with_seed(DeckSeed::new([9; 32])).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_raises(pid, DEF_BB * 3).unwrap();
+        let pid = gs.nta().unwrap().1.id;
+        gs.player_calls(pid).unwrap();
+        let bytes = gs.to_bytes();
+        assert_eq!(GameState::from_bytes(&bytes).unwrap(), gs);
+        assert!(bytes.len() < serde_json::to_string(&gs).unwrap().len());
+
+        let mut newer = bytes.clone();
+        newer[..2].copy_from_slice(&(SCHEMA_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            GameState::from_bytes(&newer),
+            Err(GameError::UnsupportedSchemaVersion(_))
+        ));
+        assert!(matches!(
+            GameState::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(GameError::InvalidState(_))
+        ));
+    }
+
+    /// A client that's missed whole hands is sent this hand from its base state, and one that's
+    /// up to date this hand just gets what's new
+    #[test]
+    fn resync_payload() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        for _ in 0..5 {
+            gs.start_hand().unwrap();
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_folds(pid).unwrap();
+        }
+        gs.start_hand().unwrap();
+        let payload = gs.resync_payload(0, 1);
+        let first = gs.logs.first_active_seq().unwrap();
+        assert_eq!(payload[0].0, first);
+        assert!(matches!(payload[0].1, LogItem::NewBaseState(_)));
+        assert_eq!(
+            payload,
+            gs.filtered_changes_since(first - 1, 1).collect::<Vec<_>>()
+        );
+        // just the delta once the client has the start of this hand
+        let payload = gs.resync_payload(first, 1);
+        assert_eq!(
+            payload,
+            gs.filtered_changes_since(first, 1).collect::<Vec<_>>()
+        );
+        assert!(!payload
+            .iter()
+            .any(|(_, li)| matches!(li, LogItem::NewBaseState(_))));
+        // and nothing at all when it's seen everything
+        let last = payload.last().unwrap().0;
+        assert!(gs.resync_payload(last, 1).is_empty());
+    }
+
+    /// After the flop, the first bet has to be at least a big blind, unless it's all in
+    #[test]
+    fn min_open() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, DEF_BB + DEF_BB / 2).unwrap();
+        gs.start_hand().unwrap();
+        // heads up: player 2 is SB and acts first preflop, but last after
+        gs.player_calls(2).unwrap();
+        gs.player_checks(1).unwrap();
+        assert!(matches!(gs.state(), State::Street(Street::Flop)));
+        assert!(matches!(gs.player_bets(1, 1), Err(GameError::InvalidBet)));
+        assert!(matches!(
+            gs.player_bets(1, DEF_BB - 1),
+            Err(GameError::InvalidBet)
+        ));
+        gs.player_checks(1).unwrap();
+        // player 2 only has half a big blind left, and can bet all of it
+        let stack = gs.players.player_by_id(2).unwrap().stack;
+        assert!(stack < DEF_BB);
+        gs.player_action(2, BetAction::AllIn(stack)).unwrap();
+        assert_eq!(gs.current_bet(), stack);
+        gs.player_calls(1).unwrap();
+        while !matches!(gs.state(), State::EndOfHand) {
+            gs.player_checks(1).unwrap();
+        }
+
+        // betting the whole stack works the same as saying all in
+        gs.try_sit(3, 1000).unwrap();
+        gs.stand_up(2).unwrap();
+        gs.try_sit(2, DEF_BB + DEF_BB / 2).unwrap();
+        gs.start_hand().unwrap();
+        while matches!(gs.state(), State::Street(Street::PreFlop)) {
+            let pid = gs.nta().unwrap().1.id;
+            if gs.can_check(pid) {
+                gs.player_checks(pid).unwrap();
+            } else {
+                gs.player_calls(pid).unwrap();
+            }
+        }
+        while gs.nta().unwrap().1.id != 2 {
+            let pid = gs.nta().unwrap().1.id;
+            gs.player_checks(pid).unwrap();
+        }
+        let stack = gs.players.player_by_id(2).unwrap().stack;
+        gs.player_bets(2, stack).unwrap();
+        assert_eq!(gs.current_bet(), stack);
+    }
+
+    /// Chat is cleaned up, logged in among the game's events, and seen by everyone as it was said
+    #[test]
+    fn chat() {
+        let mut gs = GameState::default();
+        gs.try_sit(1, 1000).unwrap();
+        gs.try_sit(2, 1000).unwrap();
+        gs.start_hand().unwrap();
+        gs.chat(1, " nice\u{7} hand\n").unwrap();
+        assert!(matches!(gs.chat(3, "hi"), Err(GameError::PlayerNotFound)));
+        assert!(matches!(gs.chat(1, "\n\t "), Err(GameError::InvalidChat)));
+        let long = "a".repeat(MAX_CHAT_LEN + 1);
+        assert!(matches!(gs.chat(1, &long), Err(GameError::InvalidChat)));
+        gs.chat(2, &long[1..]).unwrap();
+        let said = |items: Vec<(SeqNum, LogItem)>| -> Vec<LogItem> {
+            items
+                .into_iter()
+                .map(|(_, li)| li)
+                .filter(|li| matches!(li, LogItem::Chat(_, _)))
+                .collect()
+        };
+        let expected = vec![
+            LogItem::Chat(1, "nice hand".to_owned()),
+            LogItem::Chat(2, long[1..].to_owned()),
+        ];
+        for viewer in [1, 2, 3] {
+            assert_eq!(
+                said(gs.filtered_changes_since(0, viewer).collect()),
+                expected
+            );
+        }
+        assert_eq!(said(gs.spectator_changes_since(0).collect()), expected);
+    }
+
     /// When action folds to the SB and the SB just completes, the BB is allowed to raise
     #[test]
     fn bigblind_can_raise() {
@@ -4,6 +4,10 @@ use poker_core::Currency;
 use serde::{Deserialize, Serialize};
 
 /// Wrapper for all our types of messages to help de/serialize
+///
+/// Bet and raise amounts are the total the player will be in for this betting round, like
+/// [`poker_core::bet::BetAction::raise_to`], not the chips they're adding on top of what they
+/// already have in. Use [`poker_core::bet::BetAction::raise_by`] to get the total from that.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Msg {
     Fold,
@@ -1,11 +1,36 @@
 pub mod action;
 
+use poker_core::hand::HandClass;
 use poker_core::log::LogItem;
-use poker_core::SeqNum;
+use poker_core::state::DecisionHelp;
+use poker_core::{PlayerId, SeqNum};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Msg {
     Action(action::Msg),
     GameLogs(Vec<(SeqNum, LogItem)>),
+    /// Server --> client: pot odds, equity, and outs for the player to act, when they ask for it.
+    /// No pot odds when there's nothing to call.
+    DecisionHelp {
+        pot_odds: Option<f64>,
+        equity: f64,
+        outs: Vec<(HandClass, usize)>,
+    },
+    /// Client --> server: something a player said to the table. It comes back to everyone in
+    /// GameLogs once the server has logged it.
+    Chat {
+        from: PlayerId,
+        text: String,
+    },
+}
+
+impl From<DecisionHelp> for Msg {
+    fn from(dh: DecisionHelp) -> Self {
+        Self::DecisionHelp {
+            pot_odds: dh.pot_odds,
+            equity: dh.equity,
+            outs: dh.outs,
+        }
+    }
 }
@@ -53,7 +53,7 @@ def detail(request, table_id):
         try:
             new_state = poker_core_py.seat_player(state, user_id, stack)
         except ValueError as e:
-            if str(e) == 'PlayerAlreadySeated':
+            if str(e) == 'Player is already seated at this table':
                 return redirect('tables:play', table_id)
         new_state2 = poker_core_py.tick_state(new_state)
         save_state(table, new_state2)
@@ -96,6 +96,8 @@ def state_action(request, table_id, action, last_seq):
     # TODO: ensure user is seated at table
     user = request.user
     state = latest_state(table.id)
+    if not poker_core_py.is_players_turn(state, user.id):
+        return HttpResponse(status=409)
     new_state = poker_core_py.player_action(state, user.id, action)
     save_state(table, new_state)
     new_state2 = poker_core_py.tick_state(new_state)
@@ -103,6 +105,15 @@ def state_action(request, table_id, action, last_seq):
     changes = state_since(request, table_id, last_seq)
     return HttpResponse(changes)
 
+def state_decision_help(request, table_id):
+    table = get_object_or_404(Table, pk=table_id)
+    user = request.user
+    state = latest_state(table.id)
+    help = poker_core_py.decision_help(state, user.id)
+    if help is None:
+        return HttpResponseBadRequest()
+    return HttpResponse(help)
+
 @login_required
 def state(request, table_id):
     if request.method != 'POST':
@@ -112,6 +123,8 @@ def state(request, table_id):
         return state_action(request, table_id, obj['action'], obj['since'])
     elif 'since' in obj:
         return state_since(request, table_id, obj['since'])
+    elif obj.get('decision_help'):
+        return state_decision_help(request, table_id)
     return HttpResponseBadRequest()
 
 @login_required